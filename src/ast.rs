@@ -1,9 +1,11 @@
 use crate::compiler::Rule;
 use crate::data::ast_nodes::{Class, Constructor, Function, StmtNode};
-use crate::data::diagnostic::{ErrorType, LineInfo};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo};
 use crate::data::name_hash::{NameHash, with_name_map};
-use crate::data::{Validator, Value};
+use crate::data::scoping::Scoping;
+use crate::data::{SourceMap, Validator, Value};
 use crate::env::Env;
+use pest::Position;
 use pest::iterators::Pair;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
@@ -12,22 +14,29 @@ use std::fmt::{Display, Formatter};
 mod ast_io;
 pub mod builder;
 pub mod evaluator;
+mod flow;
 mod hasher;
+mod indexer;
+mod linter;
+mod suggestions;
 mod validator;
 
 use crate::compiler::errors::{compile_error, diagnostic};
 pub use hasher::hash_const;
 
 #[cfg(target_arch = "wasm32")]
-pub use ast_io::write_output;
+pub use ast_io::{write_diagnostic, write_output};
 
 pub struct AST {
-    pub source: String,
-    pub user_code_start_line: u32,
+    pub source_map: SourceMap,
     pub nodes: Vec<StmtNode>,
     pub hash_to_name_map: HashMap<NameHash, String>,
     pub static_classes: HashSet<NameHash>,
     pub class_map: HashMap<NameHash, Class>,
+    /// Warnings found while compiling, such as names that do not follow the style profile
+    pub warnings: Vec<Diagnostic>,
+    /// Which variables methods can see, chosen when compiling
+    pub scoping: Scoping,
 }
 
 impl Display for AST {
@@ -41,14 +50,15 @@ impl Display for AST {
 pub const MAIN_CLASS: NameHash = hash_const("main");
 
 impl AST {
-    pub fn new(source: String, user_code_start_line: u32) -> Self {
+    pub fn new(source_map: SourceMap) -> Self {
         let mut ast = Self {
-            source,
-            user_code_start_line,
+            source_map,
             nodes: Vec::new(),
             class_map: HashMap::new(),
             hash_to_name_map: HashMap::new(),
             static_classes: HashSet::new(),
+            warnings: Vec::new(),
+            scoping: Scoping::default(),
         };
         ast.hash("main"); // add into the hash map
         ast.class_map.insert(
@@ -64,22 +74,17 @@ impl AST {
         ast
     }
 
-    pub fn add_class(
-        &mut self,
-        line: &LineInfo,
-        class_name: &NameHash,
-        class: Class,
-        validator: &mut Validator,
-    ) {
-        if self.class_map.contains_key(class_name) {
+    pub fn add_class(&mut self, class_name: &NameHash, class: Class, validator: &mut Validator) {
+        if let Some(existing) = self.class_map.get(class_name) {
             let class_name = &self.hash_to_name_map[class_name];
             compile_error(
                 diagnostic(
-                    line,
-                    ErrorType::DuplicateName,
+                    &class.line_info,
+                    ErrorCode::DuplicateClass,
                     format!("class `{}` was already declared", class_name),
                     "duplicate name used",
-                ),
+                )
+                .with_label(&existing.line_info, "first declared here"),
                 validator,
             )
         } else {
@@ -89,23 +94,23 @@ impl AST {
 
     pub fn add_function(
         functions: &mut HashMap<NameHash, Function>,
-        line: &LineInfo,
         fn_name: &NameHash,
         fn_real_name: &str,
         function: Function,
         validator: &mut Validator,
     ) {
-        if functions.contains_key(fn_name) {
+        if let Some(existing) = functions.get(fn_name) {
             compile_error(
                 diagnostic(
-                    line,
-                    ErrorType::DuplicateName,
+                    &function.line_info,
+                    ErrorCode::DuplicateFunction,
                     format!(
                         "function `{}` was already declared in this scope",
                         fn_real_name
                     ),
                     "duplicate name used",
-                ),
+                )
+                .with_label(&existing.line_info, "first declared here"),
                 validator,
             )
         } else {
@@ -133,6 +138,38 @@ impl AST {
             .get(fn_name_hash)
     }
 
+    /// Starts the variables of a function call, which only sees the variables of its caller with
+    /// dynamic calls.
+    fn push_call(&self, env: &mut Env) {
+        if self.scoping.dynamic_calls {
+            env.push_scope();
+        } else {
+            env.push_frame();
+        }
+    }
+
+    fn pop_call(&self, env: &mut Env) {
+        if self.scoping.dynamic_calls {
+            env.pop_scope();
+        } else {
+            env.pop_frame();
+        }
+    }
+
+    /// Starts the variables of the body of an `if` or a loop, which only have their own scope
+    /// with block scoping.
+    fn push_block(&self, env: &mut Env) {
+        if self.scoping.block_scoped {
+            env.push_scope();
+        }
+    }
+
+    fn pop_block(&self, env: &mut Env) {
+        if self.scoping.block_scoped {
+            env.pop_scope();
+        }
+    }
+
     pub fn hash(&mut self, string: &str) -> NameHash {
         let name_hash = hash_const(string);
         self.hash_to_name_map
@@ -204,23 +241,33 @@ impl AST {
         }
     }
 
+    /// Line info of `pair` relative to the file it was written in
     pub fn as_line_info(&self, pair: &Pair<Rule>) -> LineInfo {
-        let span = pair.as_span();
-        let (start_line, start_col) = pair.line_col();
-        let (end_line, mut end_col) = span.end_pos().line_col();
-
-        if end_line > start_line {
-            let first_line_end_col = match span.lines().next() {
-                Some(line) => line.chars().count() - 1,
-                None => end_col,
-            };
-            end_col = first_line_end_col;
-        }
+        let mut line_info = line_info_of(pair);
+        let (file_id, start_line) = self.source_map.locate(line_info.start_line);
+        let (_, end_line) = self.source_map.locate(line_info.end_line);
 
-        LineInfo {
-            start_line: start_line as u32,
-            start_col: start_col as u16,
-            end_col: end_col as u16,
-        }
+        line_info.file_id = file_id;
+        line_info.start_line = start_line;
+        line_info.end_line = end_line;
+        line_info
+    }
+}
+
+pub fn line_info_of(pair: &Pair<Rule>) -> LineInfo {
+    let span = pair.as_span();
+    let (start_line, start_col) = pair.line_col();
+
+    // Some rules consume the line breaks after them, which should not be part of the span
+    let trimmed_len = span.as_str().trim_end().len();
+    let end_pos = Position::new(span.get_input(), span.start() + trimmed_len).unwrap();
+    let (end_line, end_col) = end_pos.line_col();
+
+    LineInfo {
+        file_id: 0,
+        start_line: start_line as u32,
+        start_col: start_col as u16,
+        end_line: end_line as u32,
+        end_col: end_col as u16,
     }
 }
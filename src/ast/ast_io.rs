@@ -1,5 +1,9 @@
 use crate::ast::AST;
+use crate::compiler::errors::diagnostic;
 use crate::data::Value;
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::env::InputSource;
 use crate::env::{Env, EnvMode};
 
 #[cfg(target_arch = "wasm32")]
@@ -13,37 +17,46 @@ use std::io::Write;
 extern "C" {
     fn blocking_request_input(prompt: &str) -> JsValue;
     pub fn write_output(s: &str);
+    pub fn write_diagnostic(html: &str);
 }
 
 impl AST {
-    pub fn exec_input(ask_string: &str, env: &mut Env) -> Value {
+    /// Reads the value of an `input` at `line`, which fails once the inputs given to a test run out.
+    pub fn exec_input(
+        line: &LineInfo,
+        ask_string: &str,
+        env: &mut Env,
+    ) -> Result<Value, Diagnostic> {
         let user_string = match &mut env.mode {
-            EnvMode::Release => {
-                #[cfg(target_arch = "wasm32")]
-                {
-                    let jsv = blocking_request_input(ask_string);
-                    jsv.as_string().unwrap_or_default()
-                }
-                #[cfg(not(target_arch = "wasm32"))]
-                {
-                    print!("{}: ", ask_string);
-                    std::io::stdout().flush().unwrap();
-
-                    let mut input = String::new();
-                    std::io::stdin().read_line(&mut input).unwrap();
-                    input
-                }
-            }
+            EnvMode::Release => request_input(ask_string),
+            #[cfg(not(target_arch = "wasm32"))]
+            EnvMode::Cli { input } => match input {
+                InputSource::Prompt => request_input(ask_string),
+                InputSource::Stdin => read_stdin_line(),
+                InputSource::Queue(inputs) => match inputs.pop_front() {
+                    Some(input) => input,
+                    None => request_input(ask_string),
+                },
+            },
             EnvMode::Test {
                 mock_inputs,
                 logs: _,
-            } => mock_inputs.pop_front().expect("no mock input available"),
+            } => mock_inputs.pop_front().ok_or_else(|| {
+                diagnostic(
+                    line,
+                    ErrorCode::MissingInput,
+                    "the program asked for more input values than it was given".to_string(),
+                    "no input left for this",
+                )
+            })?,
         };
-        parse_input_to_value(user_string.trim())
+        Ok(parse_input_to_value(user_string.trim()))
     }
 
     pub fn exec_output(output: String, env: &mut Env) {
         match &mut env.mode {
+            #[cfg(not(target_arch = "wasm32"))]
+            EnvMode::Cli { .. } => println!("{}", &output),
             EnvMode::Release => {
                 #[cfg(target_arch = "wasm32")]
                 {
@@ -62,6 +75,27 @@ impl AST {
     }
 }
 
+fn request_input(ask_string: &str) -> String {
+    #[cfg(target_arch = "wasm32")]
+    {
+        let jsv = blocking_request_input(ask_string);
+        jsv.as_string().unwrap_or_default()
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        print!("{}: ", ask_string);
+        std::io::stdout().flush().unwrap();
+        read_stdin_line()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_stdin_line() -> String {
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input).unwrap();
+    input
+}
+
 fn parse_input_to_value(input: &str) -> Value {
     let input = input.trim();
     match input.parse::<f64>() {
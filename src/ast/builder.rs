@@ -1,8 +1,9 @@
+use crate::ast::flow::{Flow, flow};
 use crate::ast::{AST, hash_const};
 use crate::compiler::Rule;
 use crate::compiler::errors::{compile_error, diagnostic};
-use crate::data::ast_nodes::{AssignTarget, Expr, ExprNode, Function, Stmt};
-use crate::data::diagnostic::ErrorType;
+use crate::data::ast_nodes::{AssignTarget, Expr, ExprNode, Function, StmtNode};
+use crate::data::diagnostic::{ErrorCode, Label, LineInfo};
 use crate::data::{NameHash, Validator};
 use pest::iterators::{Pair, Pairs};
 
@@ -22,41 +23,47 @@ impl AST {
     fn build_fn(&mut self, pair: Pair<Rule>, validator: &mut Validator) -> (NameHash, Function) {
         let mut inner = pair.into_inner();
 
-        let fn_name = inner.next().unwrap().as_str();
-        let fn_args = self.build_args(&mut inner);
-        let mut fn_returns = false;
+        let fn_name_pair = inner.next().unwrap();
+        let fn_line = self.as_line_info(&fn_name_pair);
+        let fn_name = fn_name_pair.as_str();
+        let (fn_args, fn_arg_lines) = self.build_args(&mut inner);
+        let fn_body: Vec<StmtNode> = inner.map(|pair| self.build_stmt(pair, validator)).collect();
 
-        let mut fn_body = Vec::new();
-        for pair in inner {
-            let stmt_node = self.build_stmt(pair, validator);
-            if let Stmt::MethodReturn(_) = &stmt_node.stmt {
-                fn_returns = true;
-            }
-            fn_body.push(stmt_node);
-        }
+        let missing_return = match flow(&fn_body) {
+            Flow::Returns => None,
+            Flow::FallsThrough(path) => Some(path.unwrap_or_else(|| Label {
+                line_info: fn_line.clone(),
+                message: format!("`{}` never returns a value", fn_name),
+            })),
+        };
         (
             self.hash(fn_name),
             Function {
+                line_info: fn_line,
                 args: fn_args,
+                arg_lines: fn_arg_lines,
                 body: fn_body,
-                returns: fn_returns,
+                missing_return,
             },
         )
     }
 
-    fn build_args(&mut self, inner: &mut Pairs<Rule>) -> Vec<NameHash> {
+    /// Names of the declared parameters, with the location of each of them
+    fn build_args(&mut self, inner: &mut Pairs<Rule>) -> (Vec<NameHash>, Vec<LineInfo>) {
         let mut args = Vec::new();
-        if let Some(try_inner) = inner.clone().next() {
-            if try_inner.as_rule() == Rule::decl_param_list {
-                inner.next(); // consume outer
-                let inner = try_inner.into_inner();
+        let mut arg_lines = Vec::new();
+        if let Some(try_inner) = inner.clone().next()
+            && try_inner.as_rule() == Rule::decl_param_list
+        {
+            inner.next(); // consume outer
+            let inner = try_inner.into_inner();
 
-                for arg in inner {
-                    args.push(self.hash(arg.as_str()));
-                }
+            for arg in inner {
+                arg_lines.push(self.as_line_info(&arg));
+                args.push(self.hash(arg.as_str()));
             }
         }
-        args
+        (args, arg_lines)
     }
 
     fn get_assign_target(assignee: ExprNode, validator: &mut Validator) -> AssignTarget {
@@ -67,7 +74,7 @@ impl AST {
                 compile_error(
                     diagnostic(
                         &assignee.line_info,
-                        ErrorType::Unsupported,
+                        ErrorCode::InvalidAssignTarget,
                         "can only assign into a local variable or an index expression".into(),
                         "unsupported assign target",
                     ),
@@ -120,14 +120,15 @@ impl AST {
             Rule::class_new => {
                 let mut inner = first.into_inner();
 
-                let name = inner.next().unwrap().as_str();
+                let name_pair = inner.next().unwrap();
+                let name_line = self.as_line_info(&name_pair);
                 let args = inner
                     .next()
                     .unwrap()
                     .into_inner()
                     .map(|inner| self.build_expr(inner))
                     .collect();
-                Expr::ClassNew(self.hash(name), args)
+                Expr::ClassNew(name_line, self.hash(name_pair.as_str()), args)
             }
             Rule::class_ident => Expr::Var(self.hash(first.as_str())),
             _ => self.build_expr(first).expr,
@@ -1,4 +1,5 @@
 use crate::ast::{AST, MAIN_CLASS};
+use crate::common::fix_quotes_plain;
 use crate::compiler::Rule;
 use crate::data::Validator;
 use crate::data::ast_nodes::{AssignOperator, Class, Constructor, Stmt, StmtNode};
@@ -85,13 +86,20 @@ impl AST {
             }
             Rule::for_loop_stmt => {
                 let mut inner = pair.into_inner();
-                let ident = inner.next().unwrap().as_str();
+                let ident_pair = inner.next().unwrap();
+                let ident_line = self.as_line_info(&ident_pair);
                 let start_num = self.build_expr(inner.next().unwrap());
                 let end_num = self.build_expr(inner.next().unwrap());
                 let body = inner
                     .map(|inner| self.build_stmt(inner, validator))
                     .collect();
-                Stmt::For(self.hash(ident), start_num, end_num, body)
+                Stmt::For(
+                    ident_line,
+                    self.hash(ident_pair.as_str()),
+                    start_num,
+                    end_num,
+                    body,
+                )
             }
             Rule::loop_until_stmt => {
                 let mut inner = pair.into_inner();
@@ -101,10 +109,17 @@ impl AST {
                     .collect();
                 Stmt::Until(expr, body)
             }
+            Rule::import_stmt => {
+                let mut inner = pair.into_inner();
+                Stmt::Import(fix_quotes_plain(inner.next().unwrap().as_str()))
+            }
             Rule::input_stmt => {
                 let mut inner = pair.into_inner();
-                let ident = inner.next().unwrap().as_str();
-                Stmt::Input(self.hash(ident))
+                let ident_pair = inner.next().unwrap();
+                Stmt::Input(
+                    self.as_line_info(&ident_pair),
+                    self.hash(ident_pair.as_str()),
+                )
             }
             Rule::output_stmt => {
                 let inner = pair.into_inner();
@@ -122,33 +137,36 @@ impl AST {
 
                 let functions = &mut self.class_map.get_mut(&MAIN_CLASS).unwrap().functions;
                 let fn_real_name = &self.hash_to_name_map[&fn_name];
-                Self::add_function(functions, line, &fn_name, fn_real_name, function, validator);
+                Self::add_function(functions, &fn_name, fn_real_name, function, validator);
 
                 Stmt::FunctionDeclaration(fn_name)
             }
             Rule::class_decl => {
                 let mut inner = pair.into_inner();
 
-                let class_name;
                 let mut is_static = false;
                 if let Rule::static_keyword = inner.peek().unwrap().as_rule() {
                     inner.next();
                     is_static = true;
-                    class_name = self.hash_static_class(inner.next().unwrap().as_str());
-                } else {
-                    class_name = self.hash(inner.next().unwrap().as_str());
                 }
 
+                let class_name_pair = inner.next().unwrap();
+                let class_line = self.as_line_info(&class_name_pair);
+                let class_name = if is_static {
+                    self.hash_static_class(class_name_pair.as_str())
+                } else {
+                    self.hash(class_name_pair.as_str())
+                };
+
                 let constructor_info = self.as_line_info(&inner.peek().unwrap());
-                let args = self.build_args(&mut inner);
+                let (args, arg_lines) = self.build_args(&mut inner);
 
                 let mut constructors = Vec::new();
+                let mut var_lines = Vec::new();
                 let mut functions = HashMap::new();
                 let mut public_vars = HashSet::new();
 
                 for stmt in inner {
-                    let line = &self.as_line_info(&stmt);
-
                     match stmt.as_rule() {
                         Rule::class_constructor_stmt => {
                             let mut inner = stmt.into_inner();
@@ -159,7 +177,9 @@ impl AST {
                                 is_public = true;
                             }
 
-                            let var_name = self.hash(inner.next().unwrap().as_str());
+                            let var_pair = inner.next().unwrap();
+                            var_lines.push(self.as_line_info(&var_pair));
+                            let var_name = self.hash(var_pair.as_str());
                             let expr = self.build_expr(inner.next().unwrap());
 
                             if is_public {
@@ -174,7 +194,6 @@ impl AST {
                             let fn_real_name = &self.hash_to_name_map[&fn_name];
                             Self::add_function(
                                 &mut functions,
-                                line,
                                 &fn_name,
                                 fn_real_name,
                                 function,
@@ -186,16 +205,17 @@ impl AST {
                 }
 
                 self.add_class(
-                    line,
                     &class_name,
                     Class {
-                        line_info: line.clone(),
+                        line_info: class_line,
                         functions,
                         public_vars,
                         constructor: Constructor {
                             line_info: constructor_info,
                             constructors,
+                            var_lines,
                             args,
+                            arg_lines,
                         },
                         is_static,
                     },
@@ -1,11 +1,13 @@
 use crate::ast::AST;
-use crate::data::Value;
+use crate::compiler::errors::no_return_error;
 use crate::data::ast_nodes::{ExprNode, Function, Operand, StmtNode};
 use crate::data::diagnostic::{Diagnostic, LineInfo};
+use crate::data::{NameHash, Value};
 use crate::env::Env;
 
 mod eval_expr;
 mod exec_stmt;
+mod limits;
 
 impl AST {
     pub fn traverse(&self, env: &mut Env) -> Result<(), Diagnostic> {
@@ -33,16 +35,34 @@ impl AST {
 
     fn exec_fn(
         &self,
+        line: &LineInfo,
+        fn_name: &NameHash,
         def: &Function,
         params: &[Value],
         env: &mut Env,
     ) -> Result<Option<Value>, Diagnostic> {
-        env.push_scope();
+        Self::enter_call(line, fn_name, env)?;
+        self.push_call(env);
         Self::define_method_params(def, params, env);
-        let returned = self.exec_body(&def.body, env)?;
-        env.pop_scope();
+        // Left on errors too, the call frames decide which variables are visible afterwards
+        // and the calls count towards the call depth
+        let returned = self.exec_body(&def.body, env);
+        self.pop_call(env);
+        Self::exit_call(env);
 
-        Ok(returned)
+        returned
+    }
+
+    /// The value of a call that reaches the end of `def` without a `return` is an error, which
+    /// is ignored when the call is a statement of its own.
+    fn returned_value(
+        returned: Option<Value>,
+        line: &LineInfo,
+        class_name: &NameHash,
+        fn_name: &NameHash,
+        def: &Function,
+    ) -> Result<Value, Diagnostic> {
+        returned.ok_or_else(|| no_return_error(line, fn_name, class_name, def))
     }
 
     fn is_true(&self, cond: &ExprNode, env: &mut Env) -> Result<bool, Diagnostic> {
@@ -56,15 +76,16 @@ impl AST {
     }
 
     fn exec_body(&self, body: &Vec<StmtNode>, env: &mut Env) -> Result<Option<Value>, Diagnostic> {
-        env.push_scope();
+        self.push_block(env);
+        let mut returned = Ok(None);
         for stmt in body {
-            if let Some(returned_val) = self.exec_stmt(stmt, env)? {
-                env.pop_scope();
-                return Ok(Some(returned_val));
+            returned = self.exec_stmt(stmt, env);
+            if !matches!(returned, Ok(None)) {
+                break;
             }
         }
-        env.pop_scope();
-        Ok(None)
+        self.pop_block(env);
+        returned
     }
 
     fn and_or_operations(
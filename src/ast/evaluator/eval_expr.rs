@@ -1,17 +1,24 @@
 use crate::ast::AST;
 use crate::compiler::errors::{
     diagnostic, invalid_number_of_params_error, invalid_type_call_error, no_public_var_error,
-    no_return_error, out_of_bounds_error, undefined_fn_in_class_error, unsupported_operand_error,
+    out_of_bounds_error, undefined_fn_in_class_error, unsupported_operand_error,
 };
 use crate::data::Value;
 use crate::data::ast_nodes::{Expr, ExprNode, NativeMethod, UnaryOp};
-use crate::data::diagnostic::{Diagnostic, ErrorType};
+use crate::data::diagnostic::{Diagnostic, ErrorCode};
 use crate::env::Env;
 use rand::Rng;
 use std::collections::VecDeque;
 
 impl AST {
     pub fn eval_expr(&self, expr_node: &ExprNode, env: &mut Env) -> Result<Value, Diagnostic> {
+        Self::enter_nesting(&expr_node.line_info, env)?;
+        let val = self.eval_nested_expr(expr_node, env);
+        Self::exit_nesting(env);
+        val
+    }
+
+    fn eval_nested_expr(&self, expr_node: &ExprNode, env: &mut Env) -> Result<Value, Diagnostic> {
         let line = &expr_node.line_info;
         match &expr_node.expr {
             Expr::Var(name) => Ok(env.get(name).unwrap()),
@@ -23,6 +30,7 @@ impl AST {
                     array.push_back(self.eval_expr(expr, env)?)
                 }
 
+                Self::count_array_elements(line, array.len(), env)?;
                 let id = env.create_array(array);
                 Ok(Value::ArrayId(id))
             }
@@ -83,7 +91,7 @@ impl AST {
                     } else {
                         Value::String("".into())
                     };
-                    Ok(Self::exec_input(&text.fmt(), env))
+                    Self::exec_input(line, &text.fmt(), env)
                 }
                 NativeMethod::MathRandom => {
                     let mut rng = rand::rng();
@@ -135,11 +143,8 @@ impl AST {
                     resolved_params.push(self.eval_expr(param, env)?);
                 }
 
-                // Local methods are already validated and no checks are needed
-                let returned = self
-                    .exec_fn(fn_def, &resolved_params, env)?
-                    .unwrap_or(Value::Number(0.0));
-                Ok(returned)
+                let returned = self.exec_fn(line, fn_name, fn_def, &resolved_params, env)?;
+                Self::returned_value(returned, line, class_name, fn_name, fn_def)
             }
             Expr::Index(left, index) => {
                 let index = self.eval_expr(index, env)?.as_num(&index.line_info)? as i64;
@@ -174,7 +179,7 @@ impl AST {
                     )),
                 }
             }
-            Expr::ClassNew(class_name_hash, params) => {
+            Expr::ClassNew(_, class_name_hash, params) => {
                 let class_def = self.get_class(class_name_hash).unwrap();
                 let id = env.create_local_env(class_name_hash.clone());
 
@@ -210,9 +215,10 @@ impl AST {
 
                 if let Value::InstanceId(id) = val {
                     let class_name = &env.get_class_name_hash(&id).clone();
-                    let fn_def = self
-                        .get_function(class_name, fn_name)
-                        .ok_or_else(|| undefined_fn_in_class_error(fn_line, class_name, fn_name))?;
+                    let fn_def = self.get_function(class_name, fn_name).ok_or_else(|| {
+                        undefined_fn_in_class_error(fn_line, class_name, fn_name)
+                            .with_help(self.suggest_function(class_name, fn_name))
+                    })?;
 
                     if params.len() != fn_def.args.len() {
                         return Err(invalid_number_of_params_error(
@@ -228,17 +234,15 @@ impl AST {
                     }
 
                     env.push_local_env(id);
-                    let returned = self.exec_fn(fn_def, &resolved_params, env)?;
+                    let returned = self.exec_fn(fn_line, fn_name, fn_def, &resolved_params, env);
                     env.pop_local_env();
+                    let returned = returned?;
 
-                    return match returned {
-                        Some(val) => Ok(val),
-                        None => Err(no_return_error(fn_line, fn_name, class_name)),
-                    };
+                    return Self::returned_value(returned, fn_line, class_name, fn_name, fn_def);
                 }
                 Err(diagnostic(
                     line,
-                    ErrorType::InvalidType,
+                    ErrorCode::CallOnNonInstance,
                     format!(
                         "tried invoking a method `{}` not on an instance of a class: `{}`",
                         fn_name, val
@@ -266,7 +270,7 @@ impl AST {
                     }
                     _ => Err(diagnostic(
                         line,
-                        ErrorType::InvalidType,
+                        ErrorCode::AccessOnNonInstance,
                         format!(
                             "tried accessing a variable `{}` not on an instance of a class: `{}`",
                             var_name, val
@@ -275,7 +279,7 @@ impl AST {
                     )),
                 }
             }
-            Expr::StaticFunctionCall(_, class_name, fn_name, params) => {
+            Expr::StaticFunctionCall(fn_line, class_name, fn_name, params) => {
                 let fn_def = self.get_function(class_name, fn_name).unwrap();
 
                 let mut resolved_params = Vec::new();
@@ -285,12 +289,10 @@ impl AST {
 
                 let id = env.static_envs[class_name];
                 env.push_local_env(id);
-                // Static methods are already validated and no checks are needed
-                let returned = self
-                    .exec_fn(fn_def, &resolved_params, env)?
-                    .unwrap_or(Value::Number(0.0));
+                let returned = self.exec_fn(fn_line, fn_name, fn_def, &resolved_params, env);
                 env.pop_local_env();
-                Ok(returned)
+                let returned = returned?;
+                Self::returned_value(returned, fn_line, class_name, fn_name, fn_def)
             }
             Expr::StaticGetVar(_, class_name, var_name) => {
                 let id = env.static_envs[class_name];
@@ -2,7 +2,7 @@ use crate::ast::AST;
 use crate::compiler::errors::{diagnostic, invalid_type_call_error};
 use crate::data::Value;
 use crate::data::ast_nodes::{AssignOperator, AssignTarget, Stmt, StmtNode};
-use crate::data::diagnostic::{Diagnostic, ErrorType};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, ErrorType};
 use crate::env::Env;
 
 impl AST {
@@ -10,8 +10,20 @@ impl AST {
         &self,
         stmt_node: &StmtNode,
         env: &mut Env,
+    ) -> Result<Option<Value>, Diagnostic> {
+        Self::enter_nesting(&stmt_node.line_info, env)?;
+        let returned = self.exec_nested_stmt(stmt_node, env);
+        Self::exit_nesting(env);
+        returned
+    }
+
+    fn exec_nested_stmt(
+        &self,
+        stmt_node: &StmtNode,
+        env: &mut Env,
     ) -> Result<Option<Value>, Diagnostic> {
         let line = &stmt_node.line_info;
+        Self::count_step(line, env)?;
 
         match &stmt_node.stmt {
             Stmt::Assign(target, op, expr) => {
@@ -62,13 +74,14 @@ impl AST {
             }
             Stmt::While(cond, body) => {
                 while self.is_true(cond, env)? {
+                    Self::count_step(line, env)?;
                     if let Some(returned_val) = self.exec_body(body, env)? {
                         return Ok(Some(returned_val));
                     }
                 }
                 Ok(None)
             }
-            Stmt::For(ident, start_num, end_num, body) => {
+            Stmt::For(_, ident, start_num, end_num, body) => {
                 let mut control = self.eval_expr(start_num, env)?;
 
                 let previous_value = env.get(ident); // Save previous state
@@ -77,6 +90,7 @@ impl AST {
                 while control.as_num(&start_num.line_info)?
                     <= self.eval_expr(end_num, env)?.as_num(&end_num.line_info)?
                 {
+                    Self::count_step(line, env)?;
                     if let Some(returned_val) = self.exec_body(body, env)? {
                         return Ok(Some(returned_val));
                     }
@@ -86,7 +100,7 @@ impl AST {
                     if control.as_num(line).is_err() {
                         return Err(diagnostic(
                             line,
-                            ErrorType::InvalidType,
+                            ErrorCode::LoopVariableNotNumber,
                             format!(
                                 "for loop requires that the control variable `{}` persists to be a number. Found `{}`",
                                 ident, control
@@ -108,14 +122,15 @@ impl AST {
             }
             Stmt::Until(expr, body) => {
                 while !self.is_true(expr, env)? {
+                    Self::count_step(line, env)?;
                     if let Some(returned_val) = self.exec_body(body, env)? {
                         return Ok(Some(returned_val));
                     }
                 }
                 Ok(None)
             }
-            Stmt::Input(ident) => {
-                let input = Self::exec_input(self.get_name(ident), env);
+            Stmt::Input(_, ident) => {
+                let input = Self::exec_input(line, self.get_name(ident), env)?;
                 env.assign(ident, input);
                 Ok(None)
             }
@@ -131,6 +146,7 @@ impl AST {
                     self.format_val(&val, &mut output, env);
                 }
 
+                Self::count_output(line, &output, env)?;
                 Self::exec_output(output, env);
                 Ok(None)
             }
@@ -140,7 +156,7 @@ impl AST {
                 if left != right {
                     return Err(diagnostic(
                         line,
-                        ErrorType::AssertionFailed,
+                        ErrorCode::AssertionFailed,
                         format!("left != right: {} != {}", left, right),
                         "checked values were not the same",
                     ));
@@ -149,12 +165,18 @@ impl AST {
             }
             Stmt::MethodReturn(expr) => Ok(Some(self.eval_expr(expr, env)?)),
             Stmt::Expr(expr) => match self.eval_expr(expr, env) {
-                Err(e) => match e.error_type {
-                    ErrorType::NoReturn => Ok(None),
-                    _ => Err(e),
-                },
+                // The value of this call is thrown away, so it does not need one. A missing value
+                // inside the called method is still an error.
+                Err(e)
+                    if e.error_type == ErrorType::NoReturn
+                        && Some(&e.line_info) == expr.call_line() =>
+                {
+                    Ok(None)
+                }
+                Err(e) => Err(e),
                 Ok(_) => Ok(None),
             },
+            Stmt::Import(_) => Ok(None),
             Stmt::FunctionDeclaration(_) => Ok(None),
             Stmt::ClassDeclaration(_) => Ok(None),
             Stmt::EOI => Ok(None),
@@ -197,19 +219,22 @@ impl AST {
                         let index =
                             self.eval_expr(index_expr, env)?
                                 .as_num(&index_expr.line_info)? as i64;
-                        let array = env.get_array_mut(&id);
 
                         if index < 0 {
                             return Err(diagnostic(
                                 &array_expr.line_info,
-                                ErrorType::OutOfBounds,
+                                ErrorCode::NegativeIndex,
                                 format!("tried to access a negative index `{}`", index),
                                 "",
                             ));
                         }
                         let index = index as usize;
 
-                        let needed = index + 1;
+                        let needed = index.saturating_add(1);
+                        let length = env.get_array(&id).len();
+                        Self::count_array_elements(line, needed.saturating_sub(length), env)?;
+                        let array = env.get_array_mut(&id);
+
                         let target_capacity = needed.next_power_of_two().max(1);
 
                         if array.capacity() < target_capacity {
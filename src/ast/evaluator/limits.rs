@@ -0,0 +1,186 @@
+use crate::ast::AST;
+use crate::compiler::errors::diagnostic;
+use crate::data::NameHash;
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo};
+use crate::env::Env;
+use crate::env::limits::Call;
+
+impl AST {
+    /// Counts a statement or a pass of a loop, which stops the program once it is cancelled or has
+    /// run too many of them.
+    pub(super) fn count_step(line: &LineInfo, env: &mut Env) -> Result<(), Diagnostic> {
+        if env.limits.is_cancelled() {
+            return Err(diagnostic(
+                line,
+                ErrorCode::ExecutionCancelled,
+                "the program was stopped before it finished".to_string(),
+                "stopped here",
+            ));
+        }
+
+        env.usage.steps += 1;
+        match env.limits.max_steps {
+            Some(max) if env.usage.steps > max => Err(diagnostic(
+                line,
+                ErrorCode::StepLimitExceeded,
+                format!("the program ran more than `{}` statements", max),
+                "stopped here, a loop may never end",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Starts a call of `fn_name` at `line`, to be ended with `exit_call` once it returns.
+    pub(super) fn enter_call(
+        line: &LineInfo,
+        fn_name: &NameHash,
+        env: &mut Env,
+    ) -> Result<(), Diagnostic> {
+        env.usage.calls.push(Call {
+            fn_name: fn_name.clone(),
+            line_info: line.clone(),
+        });
+        match env.limits.max_call_depth {
+            Some(max) if env.usage.calls.len() > max => {
+                let calls = &env.usage.calls;
+                let count = calls
+                    .iter()
+                    .filter(|call| same_place(call, line, fn_name))
+                    .count();
+                Err(recursion_error(
+                    line,
+                    format!(
+                        "maximum recursion depth exceeded, more than `{}` calls are running at the \
+                         same time",
+                        max
+                    ),
+                    called(fn_name, count),
+                    calls,
+                    Some(fn_name),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn exit_call(env: &mut Env) {
+        env.usage.calls.pop();
+    }
+
+    /// Starts evaluating a statement or expression inside the ones being evaluated, to be ended
+    /// with `exit_nesting`.
+    pub(super) fn enter_nesting(line: &LineInfo, env: &mut Env) -> Result<(), Diagnostic> {
+        env.usage.nesting += 1;
+        match env.limits.max_nesting_depth {
+            Some(max) if env.usage.nesting > max => Err(recursion_error(
+                line,
+                format!(
+                    "maximum recursion depth exceeded, statements and expressions are nested more \
+                     than `{}` levels deep",
+                    max
+                ),
+                "nested too deeply here".to_string(),
+                &env.usage.calls,
+                None,
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub(super) fn exit_nesting(env: &mut Env) {
+        env.usage.nesting -= 1;
+    }
+
+    /// Counts the elements `added` to the arrays, before they are allocated.
+    pub(super) fn count_array_elements(
+        line: &LineInfo,
+        added: usize,
+        env: &mut Env,
+    ) -> Result<(), Diagnostic> {
+        env.usage.array_elements = env.usage.array_elements.saturating_add(added);
+        match env.limits.max_array_elements {
+            Some(max) if env.usage.array_elements > max => Err(diagnostic(
+                line,
+                ErrorCode::ArrayLimitExceeded,
+                format!("the arrays hold more than `{}` elements together", max),
+                "too many elements",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a line of `output`, before it is written.
+    pub(super) fn count_output(
+        line: &LineInfo,
+        output: &str,
+        env: &mut Env,
+    ) -> Result<(), Diagnostic> {
+        env.usage.output_lines += output.lines().count().max(1);
+        env.usage.output_bytes += output.len() + 1;
+
+        match env.limits.max_output_lines {
+            Some(max) if env.usage.output_lines > max => {
+                return Err(diagnostic(
+                    line,
+                    ErrorCode::OutputLinesExceeded,
+                    format!("the program output more than `{}` lines", max),
+                    "too much output",
+                ));
+            }
+            _ => {}
+        }
+        match env.limits.max_output_bytes {
+            Some(max) if env.usage.output_bytes > max => Err(diagnostic(
+                line,
+                ErrorCode::OutputBytesExceeded,
+                format!("the program output more than `{}` bytes", max),
+                "too much output",
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Points at every place of the chain of `calls` once, with the number of its calls still running,
+/// so that a recursion of a thousand calls stays readable. `line` is the main location, which is
+/// the call of `called_at_line` when the call depth is exceeded.
+fn recursion_error(
+    line: &LineInfo,
+    message: String,
+    note: String,
+    calls: &[Call],
+    called_at_line: Option<&NameHash>,
+) -> Diagnostic {
+    let mut places: Vec<(&Call, usize)> = Vec::new();
+    for call in calls {
+        match places
+            .iter_mut()
+            .find(|(place, _)| same_place(place, &call.line_info, &call.fn_name))
+        {
+            Some((_, count)) => *count += 1,
+            None => places.push((call, 1)),
+        }
+    }
+
+    let mut error = diagnostic(line, ErrorCode::CallDepthExceeded, message, &note).with_help(Some(
+        "make sure every recursive call gets closer to a case that returns without calling again"
+            .to_string(),
+    ));
+    for (place, count) in places {
+        if called_at_line.is_none_or(|fn_name| !same_place(place, line, fn_name)) {
+            error = error.with_label(&place.line_info, &called(&place.fn_name, count));
+        }
+    }
+    error
+}
+
+fn called(fn_name: &NameHash, count: usize) -> String {
+    match count {
+        1 => format!("`{}` is called here", fn_name),
+        _ => format!("`{}` is called here {} times", fn_name, count),
+    }
+}
+
+fn same_place(call: &Call, line: &LineInfo, fn_name: &NameHash) -> bool {
+    call.line_info == *line && call.fn_name == *fn_name
+}
@@ -0,0 +1,111 @@
+use crate::data::Value;
+use crate::data::ast_nodes::{Expr, ExprNode, Stmt, StmtNode};
+use crate::data::diagnostic::{Label, LineInfo};
+
+/// How running a block of statements can end.
+pub enum Flow {
+    /// Every way through the block ends with a `return`
+    Returns,
+    /// Some way reaches the end of the block. When only some ways return, the label points at the
+    /// `if` or loop that chooses a way without a `return`.
+    FallsThrough(Option<Label>),
+}
+
+impl Flow {
+    pub fn returns(&self) -> bool {
+        matches!(self, Flow::Returns)
+    }
+}
+
+/// Follows every way through `stmt_nodes`, to find whether all of them end with a `return`.
+pub fn flow(stmt_nodes: &[StmtNode]) -> Flow {
+    let mut path = None;
+
+    for stmt_node in stmt_nodes {
+        match stmt_flow(stmt_node) {
+            Flow::Returns => return Flow::Returns,
+            Flow::FallsThrough(label) => path = label.or(path),
+        }
+    }
+    Flow::FallsThrough(path)
+}
+
+fn stmt_flow(stmt_node: &StmtNode) -> Flow {
+    match &stmt_node.stmt {
+        Stmt::MethodReturn(_) => Flow::Returns,
+        Stmt::If {
+            cond,
+            then_branch,
+            elifs,
+            else_branch,
+        } => {
+            let branches = std::iter::once((cond, then_branch))
+                .chain(elifs.iter().map(|(cond, stmt_nodes)| (cond, stmt_nodes)));
+
+            // Whether some way through the branches returns
+            let mut any_returns = false;
+            let mut path = None;
+            let mut follow = |branch_flow: Flow, default: Label| match branch_flow {
+                Flow::Returns => any_returns = true,
+                Flow::FallsThrough(label) => {
+                    any_returns |= label.is_some();
+                    path.get_or_insert(label.unwrap_or(default));
+                }
+            };
+
+            for (cond, stmt_nodes) in branches {
+                follow(
+                    flow(stmt_nodes),
+                    no_value(&cond.line_info, "when this condition is true"),
+                );
+            }
+            match else_branch {
+                Some(stmt_nodes) => follow(flow(stmt_nodes), else_path(cond, elifs)),
+                None => follow(Flow::FallsThrough(None), else_path(cond, elifs)),
+            }
+
+            match path {
+                None => Flow::Returns,
+                // Only the ways without a `return` are worth pointing at
+                Some(label) if any_returns => Flow::FallsThrough(Some(label)),
+                Some(_) => Flow::FallsThrough(None),
+            }
+        }
+        Stmt::While(cond, body) => loop_flow(&cond.line_info, is_constant(cond, true), body),
+        Stmt::Until(cond, body) => loop_flow(&cond.line_info, is_constant(cond, false), body),
+        Stmt::For(counter_line, _, _, _, body) => loop_flow(counter_line, false, body),
+        _ => Flow::FallsThrough(None),
+    }
+}
+
+/// A loop that never stops on its own only ends with a `return`.
+fn loop_flow(line_info: &LineInfo, endless: bool, body: &[StmtNode]) -> Flow {
+    if endless {
+        return Flow::Returns;
+    }
+
+    match flow(body) {
+        Flow::FallsThrough(None) => Flow::FallsThrough(None),
+        // The loop may not run at all, or stop before a `return`
+        _ => Flow::FallsThrough(Some(no_value(line_info, "when this loop ends"))),
+    }
+}
+
+fn else_path(cond: &ExprNode, elifs: &[(ExprNode, Vec<StmtNode>)]) -> Label {
+    if elifs.is_empty() {
+        no_value(&cond.line_info, "when this condition is false")
+    } else {
+        no_value(&cond.line_info, "when no condition is true")
+    }
+}
+
+fn no_value(line_info: &LineInfo, path: &str) -> Label {
+    Label {
+        line_info: line_info.clone(),
+        message: format!("no value is returned {}", path),
+    }
+}
+
+fn is_constant(cond: &ExprNode, value: bool) -> bool {
+    matches!(cond.expr, Expr::Data(Value::Bool(b)) if b == value)
+}
@@ -0,0 +1,719 @@
+use crate::ast::{AST, MAIN_CLASS};
+use crate::data::NameHash;
+use crate::data::ast_nodes::{
+    AssignOperator, AssignTarget, Class, Expr, ExprNode, Function, Stmt, StmtNode,
+};
+use crate::data::diagnostic::LineInfo;
+use crate::data::semantic_index::{
+    Reference, ReferenceKind, Scope, ScopeId, ScopeKind, SemanticIndex, Symbol, SymbolId,
+    SymbolKind, UnresolvedReference,
+};
+use std::collections::HashMap;
+
+const THIS_PREFIX_LEN: u16 = "this.".len() as u16;
+
+impl AST {
+    /// Resolves every name of the program to its declaration, see `SemanticIndex`.
+    pub fn semantic_index(&self) -> SemanticIndex {
+        let mut indexer = Indexer {
+            ast: self,
+            index: SemanticIndex::default(),
+            declared: HashMap::new(),
+            classes: HashMap::new(),
+            methods: HashMap::new(),
+            class_scopes: HashMap::new(),
+            instance_classes: HashMap::new(),
+        };
+        indexer.index_program();
+
+        let mut index = indexer.index;
+        for (symbol, class_name) in indexer.instance_classes {
+            index.symbols[symbol].instance_of = Some(class_name);
+        }
+        index
+            .references
+            .sort_by_key(|reference| position(&reference.line_info));
+        index
+            .unresolved
+            .sort_by_key(|reference| position(&reference.line_info));
+        index
+    }
+}
+
+struct Indexer<'a> {
+    ast: &'a AST,
+    index: SemanticIndex,
+    /// Variables and class members by the scope they are declared in and their name
+    declared: HashMap<(ScopeId, NameHash), SymbolId>,
+    classes: HashMap<NameHash, SymbolId>,
+    /// Methods of the main program
+    methods: HashMap<NameHash, SymbolId>,
+    class_scopes: HashMap<NameHash, ScopeId>,
+    /// Class of the variables and class variables that were assigned a `new` object
+    instance_classes: HashMap<SymbolId, NameHash>,
+}
+
+impl Indexer<'_> {
+    fn index_program(&mut self) {
+        let ast = self.ast;
+        let program = self.add_scope(
+            ScopeKind::Program,
+            None,
+            MAIN_CLASS,
+            None,
+            LineInfo {
+                file_id: ast.source_map.main_file,
+                start_line: 1,
+                start_col: 1,
+                end_line: u32::MAX,
+                end_col: u16::MAX,
+            },
+        );
+
+        // Classes and methods can be used before they are declared, so they are added first
+        let mut classes: Vec<_> = ast
+            .class_map
+            .iter()
+            .filter(|(class_name, _)| *class_name != MAIN_CLASS)
+            .collect();
+        classes.sort_by_key(|(_, class)| position(&class.line_info));
+        for (class_name, class) in &classes {
+            self.declare(class_name, SymbolKind::Class, &class.line_info, program);
+        }
+
+        let methods = sorted_functions(&ast.class_map[&MAIN_CLASS]);
+        for (fn_name, function) in &methods {
+            self.declare(fn_name, SymbolKind::Method, &function.line_info, program);
+        }
+
+        for (class_name, class) in &classes {
+            self.declare_members(class_name, class, program);
+        }
+
+        self.index_stmts(&ast.nodes, program);
+
+        for (fn_name, function) in &methods {
+            self.index_function(&MAIN_CLASS, fn_name, function, program);
+        }
+        for (class_name, class) in &classes {
+            self.index_class(class_name, class);
+        }
+    }
+
+    fn declare_members(&mut self, class_name: &NameHash, class: &Class, program: ScopeId) {
+        let mut end_line = class.line_info.start_line;
+        for (_, expr_node) in &class.constructor.constructors {
+            end_line = end_line.max(expr_node.line_info.end_line);
+        }
+        for function in class.functions.values() {
+            end_line = end_line.max(function_end_line(function));
+        }
+
+        let scope = self.add_scope(
+            ScopeKind::Class,
+            Some(program),
+            class_name.clone(),
+            None,
+            lines(&class.line_info, end_line),
+        );
+        self.class_scopes.insert(class_name.clone(), scope);
+
+        for ((var_name, _), line_info) in class
+            .constructor
+            .constructors
+            .iter()
+            .zip(&class.constructor.var_lines)
+        {
+            let line_info = name_line(line_info, THIS_PREFIX_LEN, self.member_name(var_name));
+            if let Some(&symbol) = self.declared.get(&(scope, var_name.clone())) {
+                self.add_reference(symbol, ReferenceKind::Write, line_info, scope);
+            } else {
+                self.declare(var_name, SymbolKind::ClassVariable, &line_info, scope);
+            }
+        }
+
+        for (fn_name, function) in sorted_functions(class) {
+            let line_info = name_line(
+                &function.line_info,
+                THIS_PREFIX_LEN,
+                self.member_name(fn_name),
+            );
+            self.declare(fn_name, SymbolKind::ClassFunction, &line_info, scope);
+        }
+    }
+
+    fn index_class(&mut self, class_name: &NameHash, class: &Class) {
+        let class_scope = self.class_scopes[class_name];
+
+        // Constructor statements are spread over the class, so its scope covers all of it,
+        // the scopes of the class functions are added later and take precedence
+        let line_info = self.index.scopes[class_scope].line_info.clone();
+        let scope = self.add_scope(
+            ScopeKind::Method,
+            Some(class_scope),
+            class_name.clone(),
+            None,
+            line_info,
+        );
+        let constructor = &class.constructor;
+        for (arg, line_info) in constructor.args.iter().zip(&constructor.arg_lines) {
+            self.declare(arg, SymbolKind::Parameter, line_info, scope);
+        }
+        for (var_name, expr_node) in &constructor.constructors {
+            self.index_expr(expr_node, scope);
+            if let Some(class_name) = new_class(expr_node)
+                && let Some(&symbol) = self.declared.get(&(class_scope, var_name.clone()))
+            {
+                self.instance_classes.insert(symbol, class_name.clone());
+            }
+        }
+
+        for (fn_name, function) in sorted_functions(class) {
+            self.index_function(class_name, fn_name, function, class_scope);
+        }
+    }
+
+    fn index_function(
+        &mut self,
+        class_name: &NameHash,
+        fn_name: &NameHash,
+        function: &Function,
+        parent: ScopeId,
+    ) {
+        let scope = self.add_scope(
+            ScopeKind::Method,
+            Some(parent),
+            class_name.clone(),
+            Some(fn_name.clone()),
+            lines(&function.line_info, function_end_line(function)),
+        );
+        for (arg, line_info) in function.args.iter().zip(&function.arg_lines) {
+            self.declare(arg, SymbolKind::Parameter, line_info, scope);
+        }
+        self.index_stmts(&function.body, scope);
+    }
+
+    /// Indexes a body in its own scope, which only keeps its new variables with block scoping.
+    fn index_block(&mut self, stmt_nodes: &[StmtNode], parent: ScopeId) {
+        let (Some(first), Some(last)) = (stmt_nodes.first(), stmt_nodes.last()) else {
+            return;
+        };
+        let mut line_info = first.line_info.clone();
+        line_info.end_line = last.line_info.end_line;
+        line_info.end_col = last.line_info.end_col;
+
+        let scope = self.add_block(parent, line_info);
+        self.index_stmts(stmt_nodes, scope);
+    }
+
+    fn index_stmts(&mut self, stmt_nodes: &[StmtNode], scope: ScopeId) {
+        for stmt_node in stmt_nodes {
+            self.index_stmt(stmt_node, scope);
+        }
+    }
+
+    fn index_stmt(&mut self, stmt_node: &StmtNode, scope: ScopeId) {
+        match &stmt_node.stmt {
+            Stmt::Assign(target, operator, expr_node) => {
+                self.index_expr(expr_node, scope);
+                let kind = match operator {
+                    AssignOperator::Assign => ReferenceKind::Write,
+                    _ => ReferenceKind::ReadWrite,
+                };
+                let symbol = self.index_target(target, &stmt_node.line_info, kind, scope);
+
+                if let (Some(symbol), Some(class_name)) = (symbol, new_class(expr_node)) {
+                    self.instance_classes.insert(symbol, class_name.clone());
+                }
+            }
+            Stmt::Increment(target) | Stmt::Decrement(target) => {
+                self.index_target(
+                    target,
+                    &stmt_node.line_info,
+                    ReferenceKind::ReadWrite,
+                    scope,
+                );
+            }
+            Stmt::If {
+                cond,
+                then_branch,
+                elifs,
+                else_branch,
+            } => {
+                self.index_expr(cond, scope);
+                self.index_block(then_branch, scope);
+                for (cond, body) in elifs {
+                    self.index_expr(cond, scope);
+                    self.index_block(body, scope);
+                }
+                if let Some(body) = else_branch {
+                    self.index_block(body, scope);
+                }
+            }
+            Stmt::While(cond, body) | Stmt::Until(cond, body) => {
+                self.index_expr(cond, scope);
+                self.index_block(body, scope);
+            }
+            Stmt::For(line_info, name, start, end, body) => {
+                self.index_expr(start, scope);
+                self.index_expr(end, scope);
+
+                // A new counter only exists during the loop
+                let block = self.add_block(scope, stmt_node.line_info.clone());
+                match self.resolve_variable(name, scope) {
+                    Some(symbol) => {
+                        self.add_reference(symbol, ReferenceKind::Write, line_info.clone(), block)
+                    }
+                    None => {
+                        self.declare(name, SymbolKind::LoopCounter, line_info, block);
+                    }
+                }
+                self.index_stmts(body, block);
+            }
+            Stmt::Input(line_info, name) => {
+                self.write_variable(name, line_info.clone(), ReferenceKind::Write, scope);
+            }
+            Stmt::Output(expr_nodes) => {
+                for expr_node in expr_nodes {
+                    self.index_expr(expr_node, scope);
+                }
+            }
+            Stmt::Assert(expr_node, expected) => {
+                self.index_expr(expr_node, scope);
+                self.index_expr(expected, scope);
+            }
+            Stmt::Expr(expr_node) | Stmt::MethodReturn(expr_node) => {
+                self.index_expr(expr_node, scope);
+            }
+            Stmt::Import(_)
+            | Stmt::FunctionDeclaration(_)
+            | Stmt::ClassDeclaration(_)
+            | Stmt::EOI => {}
+        }
+    }
+
+    /// Indexes the assigned name of `target`, which is written at the start of the statement.
+    fn index_target(
+        &mut self,
+        target: &AssignTarget,
+        stmt_line: &LineInfo,
+        kind: ReferenceKind,
+        scope: ScopeId,
+    ) -> Option<SymbolId> {
+        match target {
+            // Stands in for targets that cannot be assigned, which are reported by the builder
+            AssignTarget::Ident(name) if !self.ast.hash_to_name_map.contains_key(name) => None,
+            AssignTarget::Ident(name) if name.this_keyword => {
+                let line_info = name_line(stmt_line, THIS_PREFIX_LEN, self.member_name(name));
+                let class_name = self.enclosing_class(scope);
+                self.member_reference(class_name.as_ref(), name, line_info, kind, scope)
+            }
+            AssignTarget::Ident(name) => {
+                let line_info = name_line(stmt_line, 0, self.ast.get_name(name));
+                Some(self.write_variable(name, line_info, kind, scope))
+            }
+            AssignTarget::Array(array, index) => {
+                self.index_expr(array, scope);
+                self.index_expr(index, scope);
+                None
+            }
+        }
+    }
+
+    fn index_expr(&mut self, expr_node: &ExprNode, scope: ScopeId) {
+        let line = &expr_node.line_info;
+        match &expr_node.expr {
+            Expr::Var(name) if name.this_keyword => {
+                let line_info = name_line(line, THIS_PREFIX_LEN, self.member_name(name));
+                let class_name = self.enclosing_class(scope);
+                self.member_reference(
+                    class_name.as_ref(),
+                    name,
+                    line_info,
+                    ReferenceKind::Read,
+                    scope,
+                );
+            }
+            Expr::Var(name) => {
+                let line_info = name_line(line, 0, self.ast.get_name(name));
+                match self.resolve_variable(name, scope) {
+                    Some(symbol) => {
+                        self.add_reference(symbol, ReferenceKind::Read, line_info, scope)
+                    }
+                    None => self.add_unresolved(name, ReferenceKind::Read, line_info, scope),
+                }
+            }
+            Expr::Data(_) => {}
+            Expr::Index(array, index) => {
+                self.index_expr(array, scope);
+                self.index_expr(index, scope);
+            }
+            Expr::ArrayNew(expr_nodes) => self.index_exprs(expr_nodes, scope),
+            Expr::ClassNew(line_info, class_name, params) => {
+                self.index_exprs(params, scope);
+                match self.classes.get(class_name) {
+                    Some(&symbol) => {
+                        self.add_reference(symbol, ReferenceKind::Call, line_info.clone(), scope)
+                    }
+                    None => self.add_unresolved(
+                        class_name,
+                        ReferenceKind::Call,
+                        line_info.clone(),
+                        scope,
+                    ),
+                }
+            }
+            Expr::Unary(_, expr_node) => self.index_expr(expr_node, scope),
+            Expr::BinOp(left, _, right) => {
+                self.index_expr(left, scope);
+                self.index_expr(right, scope);
+            }
+            Expr::NativeFunctionCall(_, receiver, _, params) => {
+                if let Some(receiver) = receiver {
+                    self.index_expr(receiver, scope);
+                }
+                self.index_exprs(params, scope);
+            }
+            Expr::LocalFunctionCall(fn_name, params) => {
+                self.index_exprs(params, scope);
+                if fn_name.this_keyword {
+                    let line_info = name_line(line, THIS_PREFIX_LEN, self.member_name(fn_name));
+                    let class_name = self.enclosing_class(scope);
+                    self.member_reference(
+                        class_name.as_ref(),
+                        fn_name,
+                        line_info,
+                        ReferenceKind::Call,
+                        scope,
+                    );
+                } else {
+                    let line_info = name_line(line, 0, self.ast.get_name(fn_name));
+                    match self.methods.get(fn_name) {
+                        Some(&symbol) => {
+                            self.add_reference(symbol, ReferenceKind::Call, line_info, scope)
+                        }
+                        None => self.add_unresolved(fn_name, ReferenceKind::Call, line_info, scope),
+                    }
+                }
+            }
+            Expr::StaticFunctionCall(post_line, class_name, fn_name, params) => {
+                self.index_exprs(params, scope);
+                self.static_class_reference(line, class_name, scope);
+                let line_info = name_line(post_line, 1, self.member_name(fn_name));
+                self.member_reference(
+                    Some(class_name),
+                    fn_name,
+                    line_info,
+                    ReferenceKind::Call,
+                    scope,
+                );
+            }
+            Expr::ClassFunctionCall {
+                expr,
+                fn_line,
+                fn_name,
+                params,
+            } => {
+                self.index_expr(expr, scope);
+                self.index_exprs(params, scope);
+                let class_name = self.receiver_class(expr, scope);
+                let line_info = name_line(fn_line, 1, self.member_name(fn_name));
+                self.member_reference(
+                    class_name.as_ref(),
+                    fn_name,
+                    line_info,
+                    ReferenceKind::Call,
+                    scope,
+                );
+            }
+            Expr::StaticGetVar(post_line, class_name, var_name) => {
+                self.static_class_reference(line, class_name, scope);
+                let line_info = name_line(post_line, 1, self.member_name(var_name));
+                self.member_reference(
+                    Some(class_name),
+                    var_name,
+                    line_info,
+                    ReferenceKind::Read,
+                    scope,
+                );
+            }
+            Expr::ClassGetVar(expr, post_line, var_name) => {
+                self.index_expr(expr, scope);
+                let class_name = self.receiver_class(expr, scope);
+                let line_info = name_line(post_line, 1, self.member_name(var_name));
+                self.member_reference(
+                    class_name.as_ref(),
+                    var_name,
+                    line_info,
+                    ReferenceKind::Read,
+                    scope,
+                );
+            }
+        }
+    }
+
+    fn index_exprs(&mut self, expr_nodes: &[ExprNode], scope: ScopeId) {
+        for expr_node in expr_nodes {
+            self.index_expr(expr_node, scope);
+        }
+    }
+
+    /// The name of a static class in `Class.member`, written at the start of the expression.
+    fn static_class_reference(&mut self, line: &LineInfo, class_name: &NameHash, scope: ScopeId) {
+        let line_info = name_line(line, 0, self.ast.get_name(class_name));
+        match self.classes.get(class_name) {
+            Some(&symbol) => self.add_reference(symbol, ReferenceKind::Read, line_info, scope),
+            None => self.add_unresolved(class_name, ReferenceKind::Read, line_info, scope),
+        }
+    }
+
+    /// Variable `name` can refer to in `scope`. Variables are looked up through the enclosing blocks,
+    /// up to the main program for main methods and up to the method for class functions.
+    fn resolve_variable(&self, name: &NameHash, scope: ScopeId) -> Option<SymbolId> {
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            if let Some(&symbol) = self.declared.get(&(scope, name.clone())) {
+                return Some(symbol);
+            }
+            if self.index.scopes[scope].kind == ScopeKind::Class {
+                return None;
+            }
+            current = self.index.scopes[scope].parent;
+        }
+        None
+    }
+
+    /// Assignments update the variable they can see, or declare a new one in the scope it lives in.
+    fn write_variable(
+        &mut self,
+        name: &NameHash,
+        line_info: LineInfo,
+        kind: ReferenceKind,
+        scope: ScopeId,
+    ) -> SymbolId {
+        match self.resolve_variable(name, scope) {
+            Some(symbol) => {
+                self.add_reference(symbol, kind, line_info, scope);
+                symbol
+            }
+            None => {
+                let scope = self.variable_scope(scope);
+                self.declare(name, SymbolKind::Variable, &line_info, scope)
+            }
+        }
+    }
+
+    /// Scope of the variables first assigned in `scope`, the enclosing method or main program
+    /// unless blocks have their own variables.
+    fn variable_scope(&self, mut scope: ScopeId) -> ScopeId {
+        if self.ast.scoping.block_scoped {
+            return scope;
+        }
+        while self.index.scopes[scope].kind == ScopeKind::Block {
+            match self.index.scopes[scope].parent {
+                Some(parent) => scope = parent,
+                None => break,
+            }
+        }
+        scope
+    }
+
+    /// Adds a reference to the member `name` of `class_name`. When the class is not known,
+    /// the member is resolved only if a single class declares it.
+    fn member_reference(
+        &mut self,
+        class_name: Option<&NameHash>,
+        name: &NameHash,
+        line_info: LineInfo,
+        kind: ReferenceKind,
+        scope: ScopeId,
+    ) -> Option<SymbolId> {
+        let symbol = match class_name {
+            Some(class_name) => self
+                .class_scopes
+                .get(class_name)
+                .and_then(|class_scope| self.declared.get(&(*class_scope, name.clone())))
+                .copied(),
+            None => {
+                let mut candidates = self
+                    .class_scopes
+                    .values()
+                    .filter_map(|class_scope| self.declared.get(&(*class_scope, name.clone())));
+                match (candidates.next(), candidates.next()) {
+                    (Some(&symbol), None) => Some(symbol),
+                    _ => None,
+                }
+            }
+        };
+
+        match symbol {
+            Some(symbol) => self.add_reference(symbol, kind, line_info, scope),
+            None => self.add_unresolved(name, kind, line_info, scope),
+        }
+        symbol
+    }
+
+    /// The class of the object `expr_node` evaluates to, if it can be known without running the program.
+    fn receiver_class(&self, expr_node: &ExprNode, scope: ScopeId) -> Option<NameHash> {
+        let symbol = match &expr_node.expr {
+            Expr::ClassNew(_, class_name, _) => return Some(class_name.clone()),
+            Expr::Var(name) if name.this_keyword => {
+                let class_scope = self.class_scopes.get(&self.enclosing_class(scope)?)?;
+                *self.declared.get(&(*class_scope, name.clone()))?
+            }
+            Expr::Var(name) => self.resolve_variable(name, scope)?,
+            _ => return None,
+        };
+        self.instance_classes.get(&symbol).cloned()
+    }
+
+    fn enclosing_class(&self, scope: ScopeId) -> Option<NameHash> {
+        let class_name = &self.index.scopes[scope].class_name;
+        (class_name != MAIN_CLASS).then(|| class_name.clone())
+    }
+
+    fn member_name(&self, name: &NameHash) -> &str {
+        let name = self.ast.get_name(name);
+        name.strip_prefix("this.").unwrap_or(name)
+    }
+
+    fn add_scope(
+        &mut self,
+        kind: ScopeKind,
+        parent: Option<ScopeId>,
+        class_name: NameHash,
+        fn_name: Option<NameHash>,
+        line_info: LineInfo,
+    ) -> ScopeId {
+        self.index.scopes.push(Scope {
+            kind,
+            parent,
+            class_name,
+            fn_name,
+            line_info,
+        });
+        self.index.scopes.len() - 1
+    }
+
+    fn add_block(&mut self, parent: ScopeId, line_info: LineInfo) -> ScopeId {
+        let parent_scope = &self.index.scopes[parent];
+        let (class_name, fn_name) = (
+            parent_scope.class_name.clone(),
+            parent_scope.fn_name.clone(),
+        );
+        self.add_scope(
+            ScopeKind::Block,
+            Some(parent),
+            class_name,
+            fn_name,
+            line_info,
+        )
+    }
+
+    fn declare(
+        &mut self,
+        name: &NameHash,
+        kind: SymbolKind,
+        line_info: &LineInfo,
+        scope: ScopeId,
+    ) -> SymbolId {
+        let symbol = self.index.symbols.len();
+        self.index.symbols.push(Symbol {
+            name: self.member_name(name).to_string(),
+            name_hash: name.clone(),
+            kind,
+            line_info: line_info.clone(),
+            scope,
+            instance_of: None,
+        });
+        match kind {
+            SymbolKind::Class => self.classes.insert(name.clone(), symbol),
+            SymbolKind::Method => self.methods.insert(name.clone(), symbol),
+            _ => self.declared.insert((scope, name.clone()), symbol),
+        };
+        self.add_reference(symbol, ReferenceKind::Declaration, line_info.clone(), scope);
+        symbol
+    }
+
+    fn add_reference(
+        &mut self,
+        symbol: SymbolId,
+        kind: ReferenceKind,
+        line_info: LineInfo,
+        scope: ScopeId,
+    ) {
+        self.index.references.push(Reference {
+            symbol,
+            kind,
+            line_info,
+            scope,
+        });
+    }
+
+    fn add_unresolved(
+        &mut self,
+        name: &NameHash,
+        kind: ReferenceKind,
+        line_info: LineInfo,
+        scope: ScopeId,
+    ) {
+        self.index.unresolved.push(UnresolvedReference {
+            name: self.member_name(name).to_string(),
+            kind,
+            line_info,
+            scope,
+        });
+    }
+}
+
+fn new_class(expr_node: &ExprNode) -> Option<&NameHash> {
+    match &expr_node.expr {
+        Expr::ClassNew(_, class_name, _) => Some(class_name),
+        _ => None,
+    }
+}
+
+/// Functions of `class` in the order they are written.
+fn sorted_functions(class: &Class) -> Vec<(&NameHash, &Function)> {
+    let mut functions: Vec<_> = class.functions.iter().collect();
+    functions.sort_by_key(|(_, function)| position(&function.line_info));
+    functions
+}
+
+/// The closing `end method` or `}` follows the last statement.
+fn function_end_line(function: &Function) -> u32 {
+    function
+        .body
+        .iter()
+        .map(|stmt_node| stmt_node.line_info.end_line)
+        .max()
+        .unwrap_or(function.line_info.start_line)
+        + 1
+}
+
+/// Whole lines from the start of `line_info` to `end_line`.
+fn lines(line_info: &LineInfo, end_line: u32) -> LineInfo {
+    LineInfo {
+        file_id: line_info.file_id,
+        start_line: line_info.start_line,
+        start_col: 1,
+        end_line,
+        end_col: u16::MAX,
+    }
+}
+
+/// Location of `name` written `offset` columns after the start of `line_info`.
+fn name_line(line_info: &LineInfo, offset: u16, name: &str) -> LineInfo {
+    let start_col = line_info.start_col + offset;
+    LineInfo {
+        file_id: line_info.file_id,
+        start_line: line_info.start_line,
+        start_col,
+        end_line: line_info.start_line,
+        end_col: start_col + name.chars().count() as u16,
+    }
+}
+
+fn position(line_info: &LineInfo) -> (u16, u32, u16) {
+    (line_info.file_id, line_info.start_line, line_info.start_col)
+}
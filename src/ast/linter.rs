@@ -0,0 +1,456 @@
+use crate::ast::AST;
+use crate::compiler::errors::diagnostic;
+use crate::data::NameHash;
+use crate::data::Value;
+use crate::data::ast_nodes::{AssignTarget, Expr, ExprNode, Operand, Stmt, StmtNode, UnaryOp};
+use crate::data::diagnostic::{Diagnostic, LineInfo};
+use crate::data::lint::{Lint, LintLevels};
+use crate::data::semantic_index::{ReferenceKind, SemanticIndex, SymbolKind};
+use std::collections::HashSet;
+
+impl AST {
+    /// Code of the main file that compiles but is likely a mistake, reported with the severity
+    /// `levels` gives to each lint. Imported and included files are not checked.
+    pub fn lint(&self, levels: &LintLevels) -> Vec<Diagnostic> {
+        let mut linter = Linter {
+            ast: self,
+            levels,
+            diagnostics: Vec::new(),
+        };
+        linter.lint_symbols(&self.semantic_index());
+        linter.lint_code();
+
+        let mut diagnostics = linter.diagnostics;
+        diagnostics.sort_by_key(|diagnostic| {
+            let line_info = &diagnostic.line_info;
+            (line_info.start_line, line_info.start_col)
+        });
+        diagnostics
+    }
+}
+
+struct Linter<'a> {
+    ast: &'a AST,
+    levels: &'a LintLevels,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, lint: Lint, diagnostic: Diagnostic) {
+        if let Some(severity) = self.levels.severity(lint) {
+            self.diagnostics.push(Diagnostic {
+                severity,
+                ..diagnostic
+            });
+        }
+    }
+
+    fn is_main_file(&self, line_info: &LineInfo) -> bool {
+        line_info.file_id == self.ast.source_map.main_file
+    }
+
+    fn lint_symbols(&mut self, index: &SemanticIndex) {
+        // With dynamic calls a method reads the variables of its callers, which are only known
+        // when the program runs, so any variable with the name of such a read may be the one read
+        let dynamic_reads: HashSet<&str> = match self.ast.scoping.dynamic_calls {
+            true => index
+                .unresolved
+                .iter()
+                .filter(|reference| {
+                    matches!(
+                        reference.kind,
+                        ReferenceKind::Read | ReferenceKind::ReadWrite
+                    )
+                })
+                .map(|reference| reference.name.as_str())
+                .collect(),
+            false => HashSet::new(),
+        };
+
+        for (id, symbol) in index.symbols.iter().enumerate() {
+            if !self.is_main_file(&symbol.line_info) {
+                continue;
+            }
+
+            let (mut used, mut read) = (false, false);
+            if matches!(symbol.kind, SymbolKind::Variable | SymbolKind::Parameter)
+                && dynamic_reads.contains(symbol.name.as_str())
+            {
+                (used, read) = (true, true);
+            }
+            for reference in index.references(id) {
+                match reference.kind {
+                    ReferenceKind::Declaration => {}
+                    ReferenceKind::Read | ReferenceKind::Call => (used, read) = (true, true),
+                    ReferenceKind::Write | ReferenceKind::ReadWrite => used = true,
+                }
+            }
+
+            let line_info = &symbol.line_info;
+            match symbol.kind {
+                SymbolKind::Class if !used => self.report(
+                    Lint::UnusedClass,
+                    diagnostic(
+                        line_info,
+                        Lint::UnusedClass.code(),
+                        format!("class `{}` is never used", symbol.name),
+                        "no object of this class is created",
+                    ),
+                ),
+                SymbolKind::Method if !used => self.report(
+                    Lint::UnusedMethod,
+                    diagnostic(
+                        line_info,
+                        Lint::UnusedMethod.code(),
+                        format!("method `{}` is never called", symbol.name),
+                        "unused method",
+                    ),
+                ),
+                SymbolKind::Variable | SymbolKind::Parameter if !used => {
+                    let kind = match symbol.kind {
+                        SymbolKind::Parameter => "parameter",
+                        _ => "variable",
+                    };
+                    self.report(
+                        Lint::UnusedVariable,
+                        diagnostic(
+                            line_info,
+                            Lint::UnusedVariable.code(),
+                            format!("{} `{}` is never used", kind, symbol.name),
+                            "unused variable",
+                        ),
+                    )
+                }
+                SymbolKind::Variable | SymbolKind::Parameter if !read => self.report(
+                    Lint::UnreadVariable,
+                    diagnostic(
+                        line_info,
+                        Lint::UnreadVariable.code(),
+                        format!("variable `{}` is assigned but never read", symbol.name),
+                        "the value is never used",
+                    ),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    fn lint_code(&mut self) {
+        let ast = self.ast;
+        self.lint_block(&ast.nodes);
+
+        for class in ast.class_map.values() {
+            for expr_node in class.constructor.constructors.iter().map(|(_, expr)| expr) {
+                if self.is_main_file(&expr_node.line_info) {
+                    self.lint_expr(expr_node);
+                }
+            }
+            for function in class.functions.values() {
+                if self.is_main_file(&function.line_info) {
+                    self.lint_block(&function.body);
+                }
+            }
+        }
+    }
+
+    fn lint_block(&mut self, stmt_nodes: &[StmtNode]) {
+        let code: Vec<&StmtNode> = stmt_nodes
+            .iter()
+            .filter(|stmt_node| self.is_main_file(&stmt_node.line_info))
+            .filter(|stmt_node| {
+                !matches!(
+                    stmt_node.stmt,
+                    Stmt::FunctionDeclaration(_)
+                        | Stmt::ClassDeclaration(_)
+                        | Stmt::Import(_)
+                        | Stmt::EOI
+                )
+            })
+            .collect();
+
+        let first_return = code
+            .iter()
+            .position(|stmt_node| matches!(stmt_node.stmt, Stmt::MethodReturn(_)));
+        if let Some(i) = first_return
+            && let (Some(first), Some(last)) = (code.get(i + 1), code.last())
+        {
+            let mut line_info = first.line_info.clone();
+            line_info.end_line = last.line_info.end_line;
+            line_info.end_col = last.line_info.end_col;
+            self.report(
+                Lint::UnreachableCode,
+                diagnostic(
+                    &line_info,
+                    Lint::UnreachableCode.code(),
+                    "unreachable code".to_string(),
+                    "never runs",
+                )
+                .with_label(
+                    &code[i].line_info,
+                    "any code after this `return` is skipped",
+                ),
+            );
+        }
+
+        for stmt_node in code {
+            self.lint_stmt(stmt_node);
+        }
+    }
+
+    fn lint_stmt(&mut self, stmt_node: &StmtNode) {
+        match &stmt_node.stmt {
+            Stmt::Assign(target, _, expr_node) => {
+                if let AssignTarget::Array(array, index) = target {
+                    self.lint_expr(array);
+                    self.lint_expr(index);
+                }
+                self.lint_expr(expr_node);
+            }
+            Stmt::Increment(AssignTarget::Array(array, index))
+            | Stmt::Decrement(AssignTarget::Array(array, index)) => {
+                self.lint_expr(array);
+                self.lint_expr(index);
+            }
+            Stmt::If {
+                cond,
+                then_branch,
+                elifs,
+                else_branch,
+            } => {
+                self.lint_condition(cond);
+                self.lint_block(then_branch);
+                for (cond, body) in elifs {
+                    self.lint_condition(cond);
+                    self.lint_block(body);
+                }
+                if let Some(body) = else_branch {
+                    self.lint_block(body);
+                }
+            }
+            Stmt::While(cond, body) | Stmt::Until(cond, body) => {
+                self.lint_condition(cond);
+                self.lint_block(body);
+            }
+            Stmt::For(line_info, name, start, end, body) => {
+                self.lint_expr(start);
+                self.lint_expr(end);
+                self.lint_counter_writes(line_info, name, body);
+                self.lint_block(body);
+            }
+            Stmt::Output(expr_nodes) => {
+                for expr_node in expr_nodes {
+                    self.lint_expr(expr_node);
+                }
+            }
+            Stmt::Assert(expr_node, expected) => {
+                self.lint_expr(expr_node);
+                self.lint_expr(expected);
+            }
+            Stmt::Expr(expr_node) | Stmt::MethodReturn(expr_node) => self.lint_expr(expr_node),
+            _ => {}
+        }
+    }
+
+    /// Reports the statements of `body`, and of the blocks inside it, that change the loop counter `name`.
+    fn lint_counter_writes(&mut self, counter_line: &LineInfo, name: &NameHash, body: &[StmtNode]) {
+        for stmt_node in body {
+            let writes = match &stmt_node.stmt {
+                Stmt::Assign(AssignTarget::Ident(target), _, _)
+                | Stmt::Increment(AssignTarget::Ident(target))
+                | Stmt::Decrement(AssignTarget::Ident(target))
+                | Stmt::Input(_, target)
+                | Stmt::For(_, target, _, _, _) => target == name,
+                _ => false,
+            };
+            if writes {
+                self.report(
+                    Lint::LoopVariableWritten,
+                    diagnostic(
+                        &stmt_node.line_info,
+                        Lint::LoopVariableWritten.code(),
+                        format!(
+                            "loop variable `{}` is changed inside the loop",
+                            self.ast.get_name(name)
+                        ),
+                        "changes the number of passes",
+                    )
+                    .with_label(counter_line, "counted by this loop"),
+                );
+            }
+
+            match &stmt_node.stmt {
+                Stmt::If {
+                    then_branch,
+                    elifs,
+                    else_branch,
+                    ..
+                } => {
+                    self.lint_counter_writes(counter_line, name, then_branch);
+                    for (_, body) in elifs {
+                        self.lint_counter_writes(counter_line, name, body);
+                    }
+                    if let Some(body) = else_branch {
+                        self.lint_counter_writes(counter_line, name, body);
+                    }
+                }
+                Stmt::While(_, body) | Stmt::Until(_, body) | Stmt::For(_, _, _, _, body) => {
+                    self.lint_counter_writes(counter_line, name, body)
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Checks `cond` like any expression, and for `=` used as a comparison.
+    fn lint_condition(&mut self, cond: &ExprNode) {
+        self.lint_single_equals(cond);
+        self.lint_expr(cond);
+    }
+
+    fn lint_single_equals(&mut self, expr_node: &ExprNode) {
+        match &expr_node.expr {
+            Expr::BinOp(left, operand, right) => {
+                if matches!(operand, Operand::Equal)
+                    && let Some((text, line_info)) = self.operator(left, right)
+                    && text == "="
+                {
+                    self.report(
+                        Lint::AssignmentAsEquality,
+                        diagnostic(
+                            &line_info,
+                            Lint::AssignmentAsEquality.code(),
+                            "`=` used to compare values".to_string(),
+                            "compares, but reads like an assignment",
+                        )
+                        .with_help(Some("use `==` to compare values".to_string())),
+                    );
+                }
+                self.lint_single_equals(left);
+                self.lint_single_equals(right);
+            }
+            Expr::Unary(_, expr_node) => self.lint_single_equals(expr_node),
+            _ => {}
+        }
+    }
+
+    fn lint_expr(&mut self, expr_node: &ExprNode) {
+        match &expr_node.expr {
+            Expr::BinOp(left, operand, right) => {
+                if is_comparison(operand)
+                    && let Expr::BinOp(_, inner, _) = &left.expr
+                    && is_comparison(inner)
+                    && let Some((text, _)) = self.operator(left, right)
+                    && !text.contains(')')
+                {
+                    self.report(
+                        Lint::ChainedComparison,
+                        diagnostic(
+                            &expr_node.line_info,
+                            Lint::ChainedComparison.code(),
+                            "comparisons cannot be chained".to_string(),
+                            "compares the result of the first comparison",
+                        )
+                        .with_help(Some(
+                            "join the comparisons with `AND`, as in `1 < X AND X < 5`".to_string(),
+                        )),
+                    );
+                }
+                if matches!(operand, Operand::Equal | Operand::NotEqual)
+                    && (is_fractional(left) || is_fractional(right))
+                {
+                    self.report(
+                        Lint::FloatEquality,
+                        diagnostic(
+                            &expr_node.line_info,
+                            Lint::FloatEquality.code(),
+                            "numbers with a fractional part are compared for equality".to_string(),
+                            "rounding errors can make equal numbers differ",
+                        )
+                        .with_help(Some(
+                            "check that the difference is small instead, as in `Math.abs(A - B) < 0.000001`"
+                                .to_string(),
+                        )),
+                    );
+                }
+                self.lint_expr(left);
+                self.lint_expr(right);
+            }
+            Expr::Unary(_, expr_node) => self.lint_expr(expr_node),
+            Expr::Index(array, index) => {
+                self.lint_expr(array);
+                self.lint_expr(index);
+            }
+            Expr::ClassGetVar(expr_node, _, _) => self.lint_expr(expr_node),
+            Expr::ClassFunctionCall { expr, params, .. } => {
+                self.lint_expr(expr);
+                self.lint_exprs(params);
+            }
+            Expr::NativeFunctionCall(_, expr_node, _, params) => {
+                if let Some(expr_node) = expr_node {
+                    self.lint_expr(expr_node);
+                }
+                self.lint_exprs(params);
+            }
+            Expr::ArrayNew(params)
+            | Expr::ClassNew(_, _, params)
+            | Expr::LocalFunctionCall(_, params)
+            | Expr::StaticFunctionCall(_, _, _, params) => self.lint_exprs(params),
+            Expr::Var(_) | Expr::Data(_) | Expr::StaticGetVar(_, _, _) => {}
+        }
+    }
+
+    fn lint_exprs(&mut self, expr_nodes: &[ExprNode]) {
+        for expr_node in expr_nodes {
+            self.lint_expr(expr_node);
+        }
+    }
+
+    /// The operator written between `left` and `right` when both are on the same line, with its location.
+    fn operator(&self, left: &ExprNode, right: &ExprNode) -> Option<(String, LineInfo)> {
+        let (left, right) = (&left.line_info, &right.line_info);
+        if left.end_line != right.start_line || !self.is_main_file(left) {
+            return None;
+        }
+
+        let line = self.ast.source_map.file(left.file_id).line(left.end_line)?;
+        let between: String = line
+            .chars()
+            .skip(left.end_col.checked_sub(1)? as usize)
+            .take(right.start_col.checked_sub(left.end_col)? as usize)
+            .collect();
+        let text = between.trim();
+        let offset = between.find(text)? as u16;
+
+        let start_col = left.end_col + offset;
+        let line_info = LineInfo {
+            file_id: left.file_id,
+            start_line: left.end_line,
+            start_col,
+            end_line: left.end_line,
+            end_col: start_col + text.chars().count() as u16,
+        };
+        Some((text.to_string(), line_info))
+    }
+}
+
+fn is_comparison(operand: &Operand) -> bool {
+    matches!(
+        operand,
+        Operand::Greater
+            | Operand::Less
+            | Operand::GreaterEqual
+            | Operand::LessEqual
+            | Operand::Equal
+            | Operand::NotEqual
+    )
+}
+
+/// Whether `expr_node` is a number with a fractional part, or a division that may result in one.
+fn is_fractional(expr_node: &ExprNode) -> bool {
+    match &expr_node.expr {
+        Expr::Data(Value::Number(n)) => n.is_finite() && n.fract() != 0.0,
+        Expr::Unary(UnaryOp::Neg, expr_node) => is_fractional(expr_node),
+        Expr::BinOp(_, Operand::Divide, _) => true,
+        _ => false,
+    }
+}
@@ -0,0 +1,64 @@
+use crate::ast::{AST, MAIN_CLASS};
+use crate::common::similar_names;
+use crate::data::NameHash;
+use crate::env::Env;
+
+impl AST {
+    /// Suggests variables visible in the current scope that are similar to `name`.
+    pub fn suggest_variable(&self, name: &NameHash, env: &Env) -> Option<String> {
+        let local_env = env.get_local_env();
+        let candidates = local_env
+            .visible_scopes()
+            .flat_map(|index| local_env.scopes[index].keys())
+            .filter_map(|hash| self.hash_to_name_map.get(hash))
+            .map(String::as_str);
+
+        did_you_mean(similar_names(self.get_name(name), candidates))
+    }
+
+    /// Suggests functions of `class_name` that are similar to `fn_name`.
+    pub fn suggest_function(&self, class_name: &NameHash, fn_name: &NameHash) -> Option<String> {
+        let class = self.get_class(class_name)?;
+        let candidates = class
+            .functions
+            .keys()
+            .filter_map(|hash| self.hash_to_name_map.get(hash))
+            .map(|name| strip_this(name));
+
+        did_you_mean(similar_names(
+            strip_this(self.get_name(fn_name)),
+            candidates,
+        ))
+    }
+
+    /// Suggests declared classes that are similar to `class_name`.
+    pub fn suggest_class(&self, class_name: &NameHash) -> Option<String> {
+        let candidates = self
+            .class_map
+            .keys()
+            .filter(|hash| **hash != MAIN_CLASS)
+            .filter_map(|hash| self.hash_to_name_map.get(hash))
+            .map(String::as_str);
+
+        did_you_mean(similar_names(self.get_name(class_name), candidates))
+    }
+}
+
+fn strip_this(name: &str) -> &str {
+    name.strip_prefix("this.").unwrap_or(name)
+}
+
+fn did_you_mean(names: Vec<&str>) -> Option<String> {
+    match names.as_slice() {
+        [] => None,
+        [name] => Some(format!("did you mean `{}`?", name)),
+        names => Some(format!(
+            "did you mean one of {}?",
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+    }
+}
@@ -1,13 +1,18 @@
+mod validate_assignment;
 mod validate_expr;
 mod validate_stmt;
+mod validate_style;
+mod validate_types;
 
 use crate::ast::{AST, MAIN_CLASS};
 use crate::compiler::errors::{compile_error, diagnostic, invalid_number_of_params_error};
 use crate::data::ast_nodes::{Class, ExprNode, Function};
-use crate::data::diagnostic::{ErrorType, LineInfo};
+use crate::data::diagnostic::{ErrorCode, LineInfo};
+use crate::data::validator::MethodScope;
 use crate::data::{NameHash, Validator, Value};
 use crate::env::Env;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 
 impl AST {
     pub fn validate(&self, env: &mut Env, validator: &mut Validator) {
@@ -28,10 +33,19 @@ impl AST {
         }
         env.pop_local_env();
 
+        self.validate_types(validator);
+
+        if let Some(profile) = validator.style.clone() {
+            self.validate_style(&profile, validator);
+        }
+
         // Sort by first line, as some errors might be caught later
         validator.errors.sort_by(|left, right| {
-            let mut ord = left.line_info.start_line.cmp(&right.line_info.start_line);
+            let mut ord = left.line_info.file_id.cmp(&right.line_info.file_id);
 
+            if ord == Ordering::Equal {
+                ord = left.line_info.start_line.cmp(&right.line_info.start_line);
+            };
             if ord == Ordering::Equal {
                 ord = left.line_info.start_col.cmp(&right.line_info.start_col);
             };
@@ -55,7 +69,7 @@ impl AST {
                     compile_error(
                         diagnostic(
                             &class.constructor.line_info,
-                            ErrorType::Unsupported,
+                            ErrorCode::StaticClassParameters,
                             format!(
                                 "constructor parameter(s) found for class `{}`. Static classes cannot have constructor parameters",
                                 class_name
@@ -96,8 +110,17 @@ impl AST {
             .validated_functions
             .entry(class_name.clone())
             .or_default();
-        if !entry.contains(fn_name) {
-            env.push_scope();
+        // Marked before the body is validated, so recursive calls do not validate it again
+        if entry.insert(fn_name.clone()) {
+            if class_name == MAIN_CLASS {
+                validator.method_scopes.push(MethodScope {
+                    name: fn_name.clone(),
+                    caller_scopes: env.scope_count(),
+                    assigned: HashSet::new(),
+                });
+            }
+
+            self.push_call(env);
             for arg in &function.args {
                 env.define(arg, Value::Number(0.0))
             }
@@ -105,13 +128,11 @@ impl AST {
             for stmt_node in &function.body {
                 self.validate_stmt(stmt_node, env, validator);
             }
-            env.pop_scope();
+            self.pop_call(env);
 
-            validator
-                .validated_functions
-                .get_mut(class_name)
-                .unwrap()
-                .insert(fn_name.clone());
+            if class_name == MAIN_CLASS {
+                validator.method_scopes.pop();
+            }
         }
     }
 
@@ -127,10 +148,11 @@ impl AST {
                 compile_error(
                     diagnostic(
                         line_info,
-                        ErrorType::Uninitialized,
+                        ErrorCode::UndefinedClass,
                         format!("cannot find class `{}`", class_name),
                         "class is not defined",
-                    ),
+                    )
+                    .with_help(self.suggest_class(class_name)),
                     validator,
                 );
                 None
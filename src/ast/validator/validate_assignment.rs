@@ -0,0 +1,279 @@
+use crate::ast::AST;
+use crate::ast::flow::flow;
+use crate::compiler::errors::{compile_error, diagnostic};
+use crate::data::ast_nodes::{ExprNode, StmtNode};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, Label, LineInfo};
+use crate::data::{NameHash, Validator, Value};
+use crate::env::Env;
+use std::collections::{HashMap, HashSet};
+
+type Scopes = Vec<HashMap<NameHash, Value>>;
+
+/// Variables at the end of one way through an `if` or loop.
+/// Variables that may not be assigned hold `Value::Undefined` while validating.
+struct Outcome {
+    scopes: Scopes,
+    /// Where the way is chosen, and when it is taken
+    line_info: LineInfo,
+    path: &'static str,
+}
+
+impl AST {
+    /// Validates every branch from the variables before the `if`, which afterwards keeps the
+    /// variables assigned on every branch that does not return.
+    pub(super) fn validate_if(
+        &self,
+        cond: &ExprNode,
+        then_branch: &Vec<StmtNode>,
+        elifs: &[(ExprNode, Vec<StmtNode>)],
+        else_branch: &Option<Vec<StmtNode>>,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        let before = env.scopes();
+        let mut outcomes = Vec::new();
+
+        let branches = std::iter::once((cond, then_branch))
+            .chain(elifs.iter().map(|(cond, stmt_nodes)| (cond, stmt_nodes)));
+        for (cond, stmt_nodes) in branches {
+            self.validate_expr(cond, env, validator);
+            self.validate_body(stmt_nodes, env, validator);
+
+            if !always_returns(stmt_nodes) {
+                outcomes.push(Outcome {
+                    scopes: env.scopes(),
+                    line_info: cond.line_info.clone(),
+                    path: "when this condition is true",
+                });
+            }
+            env.set_scopes(before.clone());
+        }
+
+        let path = if elifs.is_empty() {
+            "when this condition is false"
+        } else {
+            "when no condition is true"
+        };
+        match else_branch {
+            Some(stmt_nodes) => {
+                self.validate_body(stmt_nodes, env, validator);
+                if !always_returns(stmt_nodes) {
+                    outcomes.push(Outcome {
+                        scopes: env.scopes(),
+                        line_info: cond.line_info.clone(),
+                        path,
+                    });
+                }
+            }
+            None => outcomes.push(Outcome {
+                scopes: before.clone(),
+                line_info: cond.line_info.clone(),
+                path,
+            }),
+        }
+
+        merge_outcomes(before, outcomes, env, validator);
+    }
+
+    /// Validates a loop body, which may not run at all, so the variables it assigns first
+    /// may not be assigned after the loop.
+    pub(super) fn validate_loop_body(
+        &self,
+        line_info: &LineInfo,
+        path: &'static str,
+        body: &Vec<StmtNode>,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        let before = env.scopes();
+        let mut outcomes = vec![Outcome {
+            scopes: before.clone(),
+            line_info: line_info.clone(),
+            path,
+        }];
+
+        self.validate_body(body, env, validator);
+        if !always_returns(body) {
+            outcomes.push(Outcome {
+                scopes: env.scopes(),
+                line_info: line_info.clone(),
+                path,
+            });
+        }
+
+        merge_outcomes(before, outcomes, env, validator);
+    }
+
+    /// Checks that `name` has a value on every path that reaches `line`.
+    pub(super) fn validate_var_read(
+        &self,
+        line: &LineInfo,
+        name: &NameHash,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        match env.get(name) {
+            None => {
+                compile_error(
+                    diagnostic(
+                        line,
+                        ErrorCode::UndefinedVariable,
+                        format!("cannot find variable `{}` in this scope", name),
+                        "not found in this scope",
+                    )
+                    .with_help(self.suggest_variable(name, env)),
+                    validator,
+                );
+            }
+            Some(Value::Undefined) => {
+                let error = unassigned_error(line, name, validator.unassigned.get(name));
+                compile_error(error, validator);
+            }
+            Some(_) => record_caller_read(line, name, env, validator),
+        }
+    }
+
+    /// Assigns `name` for the rest of the validation.
+    pub(super) fn validate_var_write(
+        &self,
+        name: &NameHash,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        env.assign(name, Value::Number(0.0));
+
+        if let Some(method) = validator.method_scopes.last_mut() {
+            method.assigned.insert(name.clone());
+        }
+    }
+
+    /// Checks that the variables `fn_name` reads from its caller are assigned where it is called.
+    pub(super) fn validate_caller_reads(
+        &self,
+        call_line: &LineInfo,
+        fn_name: &NameHash,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        let Some(reads) = validator.caller_reads.get(fn_name).cloned() else {
+            return;
+        };
+
+        for (name, read_line) in reads {
+            // Without dynamic calls, the method only sees the variables of the main program
+            let value = if self.scoping.dynamic_calls {
+                env.get(&name)
+            } else {
+                env.get_global(&name)
+            };
+            match value {
+                Some(Value::Undefined) | None => {
+                    let error =
+                        unassigned_error(&read_line, &name, validator.unassigned.get(&name))
+                            .with_label(
+                                call_line,
+                                &format!(
+                                    "`{}` is called here, where `{}` may not be assigned",
+                                    fn_name, name
+                                ),
+                            );
+                    compile_error(error, validator);
+                }
+                Some(_) => record_caller_read(&read_line, &name, env, validator),
+            }
+        }
+    }
+}
+
+fn unassigned_error(line: &LineInfo, name: &NameHash, unassigned: Option<&Label>) -> Diagnostic {
+    let mut error = diagnostic(
+        line,
+        ErrorCode::UnassignedVariable,
+        format!("variable `{}` may be used before assignment", name),
+        "may not have a value here",
+    )
+    .with_help(Some(format!(
+        "assign `{}` a value on every path that reaches this point",
+        name
+    )));
+
+    if let Some(label) = unassigned {
+        error = error.with_label(&label.line_info, &label.message);
+    }
+    error
+}
+
+/// Remembers that the method being validated reads `name` from the scopes of its caller, unless
+/// it assigned `name` itself.
+fn record_caller_read(line: &LineInfo, name: &NameHash, env: &Env, validator: &mut Validator) {
+    let Some(method) = validator.method_scopes.last() else {
+        return;
+    };
+    let from_caller = env
+        .scope_index(name)
+        .is_some_and(|index| index < method.caller_scopes);
+    if !from_caller || method.assigned.contains(name) {
+        return;
+    }
+
+    let reads = validator
+        .caller_reads
+        .entry(method.name.clone())
+        .or_default();
+    if !reads
+        .iter()
+        .any(|(read, read_line)| read == name && read_line == line)
+    {
+        reads.push((name.clone(), line.clone()));
+    }
+}
+
+/// Joins the ways through an `if` or loop, a variable stays assigned only if every way assigns it.
+fn merge_outcomes(
+    before: Scopes,
+    outcomes: Vec<Outcome>,
+    env: &mut Env,
+    validator: &mut Validator,
+) {
+    // Every way returns, so the statements after are never run
+    let Some(first) = outcomes.first() else {
+        env.set_scopes(before);
+        return;
+    };
+
+    let mut merged = first.scopes.clone();
+    for (level, scope) in merged.iter_mut().enumerate() {
+        let names: HashSet<NameHash> = outcomes
+            .iter()
+            .flat_map(|outcome| outcome.scopes[level].keys())
+            .cloned()
+            .collect();
+
+        for name in names {
+            let value_in = |outcome: &Outcome| outcome.scopes[level].get(&name).cloned();
+
+            if let Some(skipped) = outcomes.iter().find(|outcome| value_in(outcome).is_none()) {
+                validator.unassigned.insert(
+                    name.clone(),
+                    Label {
+                        line_info: skipped.line_info.clone(),
+                        message: format!("`{}` is not assigned {}", name, skipped.path),
+                    },
+                );
+                scope.insert(name, Value::Undefined);
+            } else if outcomes
+                .iter()
+                .any(|outcome| matches!(value_in(outcome), Some(Value::Undefined)))
+            {
+                scope.insert(name, Value::Undefined);
+            } else {
+                scope.insert(name.clone(), value_in(first).unwrap());
+            }
+        }
+    }
+    env.set_scopes(merged);
+}
+
+fn always_returns(stmt_nodes: &[StmtNode]) -> bool {
+    flow(stmt_nodes).returns()
+}
@@ -4,7 +4,7 @@ use crate::compiler::errors::{
     no_return_error, undefined_fn_in_class_error,
 };
 use crate::data::ast_nodes::{Expr, ExprNode, Function, NativeMethod};
-use crate::data::diagnostic::{ErrorType, LineInfo};
+use crate::data::diagnostic::{ErrorCode, LineInfo};
 use crate::data::{NameHash, Validator};
 use crate::env::Env;
 
@@ -12,19 +12,7 @@ impl AST {
     pub fn validate_expr(&self, expr_node: &ExprNode, env: &mut Env, validator: &mut Validator) {
         let line = &expr_node.line_info;
         match &expr_node.expr {
-            Expr::Var(name) => {
-                let _ = env.get(name).ok_or_else(|| {
-                    compile_error(
-                        diagnostic(
-                            line,
-                            ErrorType::Uninitialized,
-                            format!("cannot find variable `{}` in this scope", name),
-                            "not found in this scope",
-                        ),
-                        validator,
-                    )
-                });
-            }
+            Expr::Var(name) => self.validate_var_read(line, name, env, validator),
             Expr::Data(_) => {}
             Expr::ArrayNew(data) => {
                 for expr in data {
@@ -49,10 +37,11 @@ impl AST {
                     compile_error(
                         diagnostic(
                             line,
-                            ErrorType::Uninitialized,
+                            ErrorCode::UndefinedFunction,
                             format!("cannot find function `{}` in this scope", fn_name),
                             "not found in this scope",
-                        ),
+                        )
+                        .with_help(self.suggest_function(class_name, fn_name)),
                         validator,
                     );
                     return;
@@ -60,6 +49,7 @@ impl AST {
 
                 if class_name == MAIN_CLASS {
                     self.validate_fn_definition(class_name, fn_name, fn_def, env, validator);
+                    self.validate_caller_reads(line, fn_name, env, validator);
                 }
                 Self::validate_fn_call(line, class_name, fn_name, fn_def, params, validator);
             }
@@ -72,7 +62,8 @@ impl AST {
 
                 let Some(fn_def) = self.get_function(class_name, fn_name) else {
                     compile_error(
-                        undefined_fn_in_class_error(fn_line, class_name, fn_name),
+                        undefined_fn_in_class_error(fn_line, class_name, fn_name)
+                            .with_help(self.suggest_function(class_name, fn_name)),
                         validator,
                     );
                     return;
@@ -107,12 +98,12 @@ impl AST {
             Expr::ClassGetVar(expr, _, _) => {
                 self.validate_expr(expr, env, validator);
             }
-            Expr::ClassNew(class_name_hash, params) => {
+            Expr::ClassNew(_, class_name_hash, params) => {
                 if self.static_classes.contains(class_name_hash) {
                     compile_error(
                         diagnostic(
                             line,
-                            ErrorType::Unsupported,
+                            ErrorCode::StaticClassInstance,
                             format!(
                                 "cannot make an instance of a static class `{}`",
                                 class_name_hash
@@ -176,7 +167,7 @@ impl AST {
     }
 
     #[allow(clippy::too_many_arguments)]
-    fn validate_fn_call(
+    pub(super) fn validate_fn_call(
         line: &LineInfo,
         class_name: &NameHash,
         fn_name: &NameHash,
@@ -191,8 +182,11 @@ impl AST {
             );
         }
 
-        if !fn_def.returns {
-            compile_error(no_return_error(line, fn_name, class_name), validator);
+        if !fn_def.returns() {
+            compile_error(
+                no_return_error(line, fn_name, class_name, fn_def),
+                validator,
+            );
         }
     }
 }
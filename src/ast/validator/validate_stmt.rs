@@ -1,21 +1,23 @@
 use crate::ast::AST;
 use crate::ast::validator::Validator;
 use crate::data::Value;
-use crate::data::ast_nodes::{AssignTarget, Stmt, StmtNode};
-use crate::data::diagnostic::ErrorType;
+use crate::data::ast_nodes::{AssignOperator, AssignTarget, Expr, ExprNode, Stmt, StmtNode};
+use crate::data::diagnostic::{ErrorType, LineInfo};
 use crate::env::Env;
 
 impl AST {
     pub fn validate_stmt(&self, stmt_node: &StmtNode, env: &mut Env, validator: &mut Validator) {
         match &stmt_node.stmt {
-            Stmt::Assign(target, _, expr) => {
-                self.valid_assign_stmt(target, env, validator);
+            Stmt::Assign(target, op, expr) => {
+                // The value is worked out before it is assigned
                 self.validate_expr(expr, env, validator);
-            }
-            Stmt::Increment(target) => {
+                if !matches!(op, AssignOperator::Assign) {
+                    self.validate_target_read(&stmt_node.line_info, target, env, validator);
+                }
                 self.valid_assign_stmt(target, env, validator);
             }
-            Stmt::Decrement(target) => {
+            Stmt::Increment(target) | Stmt::Decrement(target) => {
+                self.validate_target_read(&stmt_node.line_info, target, env, validator);
                 self.valid_assign_stmt(target, env, validator);
             }
             Stmt::If {
@@ -24,29 +26,35 @@ impl AST {
                 elifs,
                 else_branch,
             } => {
-                self.validate_expr(cond, env, validator);
-                self.validate_body(then_branch, env, validator);
-
-                for (cond, stmt_nodes) in elifs {
-                    let _ = self.validate_expr(cond, env, validator);
-
-                    self.validate_body(stmt_nodes, env, validator);
-                }
-                if let Some(else_branch) = else_branch {
-                    self.validate_body(else_branch, env, validator);
-                }
+                self.validate_if(cond, then_branch, elifs, else_branch, env, validator);
             }
             Stmt::While(cond, body) => {
                 self.validate_expr(cond, env, validator);
-                self.validate_body(body, env, validator);
+                self.validate_loop_body(
+                    &cond.line_info,
+                    "when this condition is false at the start",
+                    body,
+                    env,
+                    validator,
+                );
             }
-            Stmt::For(name_hash, start_num, end_num, body) => {
+            Stmt::For(counter_line, name_hash, start_num, end_num, body) => {
                 let previous_value = env.get(name_hash); // Save previous state
-                env.assign(name_hash, Value::Number(0.0));  // Override control variable
                 
                 self.validate_expr(start_num, env, validator);
                 self.validate_expr(end_num, env, validator);
-                self.validate_body(body, env, validator);
+                self.validate_var_write(name_hash, env, validator); // Override control variable
+                if runs_at_least_once(start_num, end_num) {
+                    self.validate_body(body, env, validator);
+                } else {
+                    self.validate_loop_body(
+                        counter_line,
+                        "when the loop runs zero times",
+                        body,
+                        env,
+                        validator,
+                    );
+                }
                 
                 match previous_value {
                     None => env.undefine(name_hash), // Remove control variable
@@ -55,10 +63,16 @@ impl AST {
             }
             Stmt::Until(expr, body) => {
                 self.validate_expr(expr, env, validator);
-                self.validate_body(body, env, validator);
+                self.validate_loop_body(
+                    &expr.line_info,
+                    "when this condition is true at the start",
+                    body,
+                    env,
+                    validator,
+                );
             }
-            Stmt::Input(name_hash) => {
-                env.assign(name_hash, Value::Number(0.0));
+            Stmt::Input(_, name_hash) => {
+                self.validate_var_write(name_hash, env, validator);
             }
             Stmt::Output(body) => {
                 for expr_node in body {
@@ -73,13 +87,16 @@ impl AST {
                 validator.start_record();
                 self.validate_expr(expr_node, env, validator);
 
-                if validator.is_last_recorded_expr_error(ErrorType::NoReturn) {
+                // Only the value of this call is thrown away, errors inside the called method are kept
+                if validator.is_last_recorded_expr_error(ErrorType::NoReturn, expr_node.call_line())
+                {
                     validator.errors.pop();
                 }
             }
             Stmt::MethodReturn(expr) => {
                 self.validate_expr(expr, env, validator);
             }
+            Stmt::Import(_) => {}
             Stmt::FunctionDeclaration(_) => {}
             Stmt::ClassDeclaration(_) => {}
             Stmt::EOI => {}
@@ -89,7 +106,7 @@ impl AST {
     fn valid_assign_stmt(&self, target: &AssignTarget, env: &mut Env, validator: &mut Validator) {
         match target {
             AssignTarget::Ident(name_hash) => {
-                env.assign(name_hash, Value::Number(0.0));
+                self.validate_var_write(name_hash, env, validator);
             }
             AssignTarget::Array(array_expr, index_expr) => {
                 self.validate_expr(array_expr, env, validator);
@@ -98,11 +115,37 @@ impl AST {
         }
     }
 
-    fn validate_body(&self, body: &Vec<StmtNode>, env: &mut Env, validator: &mut Validator) {
-        env.push_scope();
+    /// Compound assignments such as `X += 1` read the variable before writing it.
+    fn validate_target_read(
+        &self,
+        line: &LineInfo,
+        target: &AssignTarget,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        if let AssignTarget::Ident(name_hash) = target {
+            self.validate_var_read(line, name_hash, env, validator);
+        }
+    }
+
+    pub(super) fn validate_body(
+        &self,
+        body: &Vec<StmtNode>,
+        env: &mut Env,
+        validator: &mut Validator,
+    ) {
+        self.push_block(env);
         for stmt_node in body {
             self.validate_stmt(stmt_node, env, validator);
         }
-        env.pop_scope();
+        self.pop_block(env);
+    }
+}
+
+/// A loop from a number to a number that is not smaller runs its body at least once.
+fn runs_at_least_once(start: &ExprNode, end: &ExprNode) -> bool {
+    match (&start.expr, &end.expr) {
+        (Expr::Data(Value::Number(start)), Expr::Data(Value::Number(end))) => start <= end,
+        _ => false,
     }
 }
@@ -0,0 +1,169 @@
+use crate::ast::AST;
+use crate::compiler::errors::diagnostic;
+use crate::data::Validator;
+use crate::data::diagnostic::{Diagnostic, ErrorCode, Severity};
+use crate::data::semantic_index::SymbolKind;
+use crate::data::style::StyleProfile;
+use std::collections::HashSet;
+
+/// Names that are only ever used as placeholders, whatever their case
+const PLACEHOLDER_NAMES: [&str; 12] = [
+    "temp", "tmp", "foo", "bar", "baz", "thing", "stuff", "data", "var", "asdf", "xxx", "abc",
+];
+
+impl AST {
+    /// Checks the names declared in the main file against `profile`, reported as warnings.
+    pub fn validate_style(&self, profile: &StyleProfile, validator: &mut Validator) {
+        let index = self.semantic_index();
+        // A name declared again in another block or method is reported once
+        let mut reported = HashSet::new();
+
+        for symbol in &index.symbols {
+            if symbol.line_info.file_id != self.source_map.main_file
+                || !reported.insert((symbol.kind, symbol.name.as_str()))
+            {
+                continue;
+            }
+
+            let name = symbol.name.as_str();
+            let line_info = &symbol.line_info;
+            let warning = match symbol.kind {
+                SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::LoopCounter
+                    if profile.uppercase_variables && !is_uppercase(name) =>
+                {
+                    let kind = match symbol.kind {
+                        SymbolKind::Parameter => "parameter",
+                        SymbolKind::LoopCounter => "loop variable",
+                        _ => "variable",
+                    };
+                    Some(
+                        diagnostic(
+                            line_info,
+                            ErrorCode::VariableNameStyle,
+                            format!("{} `{}` should be written in UPPERCASE", kind, name),
+                            "not in UPPERCASE",
+                        )
+                        .with_help(Some(rename_help(&uppercase(name)))),
+                    )
+                }
+                SymbolKind::Variable if profile.meaningful_names && is_meaningless(name) => Some(
+                    diagnostic(
+                        line_info,
+                        ErrorCode::MeaninglessName,
+                        format!("variable name `{}` does not say what it holds", name),
+                        "not a meaningful name",
+                    )
+                    .with_help(Some(
+                        "choose a name that describes the value, such as `TOTAL` or `STUDENT_COUNT`"
+                            .to_string(),
+                    )),
+                ),
+                SymbolKind::Method | SymbolKind::ClassFunction
+                    if profile.camel_case_methods && !is_camel_case(name) =>
+                {
+                    Some(
+                        diagnostic(
+                            line_info,
+                            ErrorCode::MethodNameStyle,
+                            format!("method `{}` should be written in camelCase", name),
+                            "not in camelCase",
+                        )
+                        .with_help(Some(rename_help(&camel_case(name)))),
+                    )
+                }
+                SymbolKind::Class if profile.pascal_case_classes && !is_pascal_case(name) => Some(
+                    diagnostic(
+                        line_info,
+                        ErrorCode::ClassNameStyle,
+                        format!("class `{}` should be written in PascalCase", name),
+                        "not in PascalCase",
+                    )
+                    .with_help(Some(rename_help(&pascal_case(name)))),
+                ),
+                _ => None,
+            };
+
+            if let Some(warning) = warning {
+                // Warnings do not count as errors of the expression being validated
+                validator.errors.push(Diagnostic {
+                    severity: Severity::Warning,
+                    ..warning
+                });
+            }
+        }
+    }
+}
+
+fn rename_help(suggestion: &str) -> String {
+    format!("rename it to `{}`", suggestion)
+}
+
+fn is_uppercase(name: &str) -> bool {
+    !name.chars().any(|c| c.is_ascii_lowercase())
+}
+
+fn is_camel_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase()) && !name.contains('_')
+}
+
+/// Names in capitals of more than two letters, such as `BOX`, look like variables.
+fn is_pascal_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && !name.contains('_')
+        && (name.len() <= 2 || name.chars().any(|c| c.is_ascii_lowercase()))
+}
+
+fn is_meaningless(name: &str) -> bool {
+    name.chars().count() == 1
+        || PLACEHOLDER_NAMES
+            .iter()
+            .any(|placeholder| placeholder.eq_ignore_ascii_case(name))
+}
+
+/// Splits `name` into lowercase words at underscores and at the capitals of camelCase.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        // A part in capitals is a single word, such as the `TOTAL` of `TOTAL_SCORE`
+        if is_uppercase(part) {
+            words.push(part.to_ascii_lowercase());
+            continue;
+        }
+
+        let mut word = String::new();
+        for c in part.chars() {
+            if c.is_ascii_uppercase() && !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            word.push(c.to_ascii_lowercase());
+        }
+        words.push(word);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+fn uppercase(name: &str) -> String {
+    words(name).join("_").to_ascii_uppercase()
+}
+
+fn camel_case(name: &str) -> String {
+    let words = words(name);
+    let mut camel = words.first().cloned().unwrap_or_default();
+    for word in words.iter().skip(1) {
+        camel.push_str(&capitalize(word));
+    }
+    camel
+}
+
+fn pascal_case(name: &str) -> String {
+    words(name).iter().map(|word| capitalize(word)).collect()
+}
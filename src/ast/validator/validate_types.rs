@@ -0,0 +1,673 @@
+use crate::ast::flow::flow;
+use crate::ast::{AST, MAIN_CLASS};
+use crate::compiler::errors::{
+    compile_error, diagnostic, invalid_type_call_error, no_public_var_error,
+    undefined_fn_in_class_error, unsupported_operand_types_error,
+};
+use crate::data::ast_nodes::{
+    AssignOperator, AssignTarget, Class, Expr, ExprNode, NativeMethod, Operand, Stmt, StmtNode,
+    UnaryOp,
+};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, ErrorType, LineInfo};
+use crate::data::types::{Type, TypeSet};
+use crate::data::{NameHash, Validator};
+use std::collections::{HashMap, HashSet};
+
+/// Types of the variables at one point of the program, a missing variable can be anything
+type TypeEnv = HashMap<NameHash, TypeSet>;
+
+/// Loops whose variables still change type after this many passes are given up on
+const MAX_LOOP_PASSES: usize = 8;
+
+impl AST {
+    /// Infers the types of values without running the program, and reports the operations that
+    /// fail on every way the program can reach them. Uncertain cases are left to the runtime.
+    pub fn validate_types(&self, validator: &mut Validator) {
+        let mut checker = TypeChecker {
+            ast: self,
+            validator,
+            class_name: MAIN_CLASS,
+            vars: TypeEnv::new(),
+            report: true,
+            main_writes: self.main_writes(),
+        };
+
+        checker.check_body(&self.nodes);
+
+        for (class_name, class) in &self.class_map {
+            checker.class_name = class_name.clone();
+            checker.vars = TypeEnv::new();
+
+            for (name, expr_node) in &class.constructor.constructors {
+                let ty = checker.infer(expr_node);
+                checker.vars.insert(name.clone(), ty);
+            }
+
+            for function in class.functions.values() {
+                // Fields and variables of the caller can be changed between calls
+                checker.vars = TypeEnv::new();
+                checker.check_body(&function.body);
+            }
+        }
+    }
+
+    /// Variables the methods of the main program can assign, which are shared with their caller.
+    fn main_writes(&self) -> HashSet<NameHash> {
+        let mut names = HashSet::new();
+        for function in self.class_map[&MAIN_CLASS].functions.values() {
+            let mut writes = HashSet::new();
+            collect_writes(&function.body, &mut writes);
+
+            names.extend(
+                writes
+                    .into_iter()
+                    .filter(|name| !function.args.contains(name)),
+            );
+        }
+        names
+    }
+}
+
+struct TypeChecker<'a> {
+    ast: &'a AST,
+    validator: &'a mut Validator,
+    /// Class of the code being checked, local calls call its functions
+    class_name: NameHash,
+    vars: TypeEnv,
+    /// Turned off while a loop body is checked again until its types settle
+    report: bool,
+    main_writes: HashSet<NameHash>,
+}
+
+impl<'a> TypeChecker<'a> {
+    fn error(&mut self, diagnostic: Diagnostic) {
+        if self.report {
+            compile_error(diagnostic, self.validator);
+        }
+    }
+
+    fn var(&self, name: &NameHash) -> TypeSet {
+        self.vars.get(name).cloned().unwrap_or(TypeSet::Unknown)
+    }
+
+    fn check_body(&mut self, stmt_nodes: &[StmtNode]) {
+        for stmt_node in stmt_nodes {
+            self.check_stmt(stmt_node);
+        }
+    }
+
+    fn check_stmt(&mut self, stmt_node: &StmtNode) {
+        let line = &stmt_node.line_info;
+        match &stmt_node.stmt {
+            Stmt::Assign(target, op, expr_node) => {
+                let value = self.infer(expr_node);
+                self.check_assign(line, target, op, value);
+            }
+            Stmt::Increment(target) => {
+                let one = TypeSet::of(Type::Number);
+                self.check_assign(line, target, &AssignOperator::AssignAdd, one);
+            }
+            Stmt::Decrement(target) => {
+                let one = TypeSet::of(Type::Number);
+                self.check_assign(line, target, &AssignOperator::AssignSubtract, one);
+            }
+            Stmt::If {
+                cond,
+                then_branch,
+                elifs,
+                else_branch,
+            } => {
+                let before = self.vars.clone();
+                let mut outcomes = Vec::new();
+
+                let branches = std::iter::once((cond, then_branch))
+                    .chain(elifs.iter().map(|(cond, stmt_nodes)| (cond, stmt_nodes)));
+                for (cond, stmt_nodes) in branches {
+                    self.vars = before.clone();
+                    self.check_condition(cond);
+                    self.check_branch(stmt_nodes, &mut outcomes);
+                }
+
+                self.vars = before.clone();
+                match else_branch {
+                    Some(stmt_nodes) => self.check_branch(stmt_nodes, &mut outcomes),
+                    None => outcomes.push(before.clone()),
+                }
+
+                // Every branch returns, so the statements after are never run
+                self.vars = if outcomes.is_empty() {
+                    before
+                } else {
+                    merge(&outcomes)
+                };
+            }
+            Stmt::While(cond, body) | Stmt::Until(cond, body) => self.check_loop(Some(cond), body),
+            Stmt::For(_, name, start, end, body) => {
+                self.check_number(start);
+                self.check_number(end);
+
+                let previous = self.vars.insert(name.clone(), TypeSet::of(Type::Number));
+                self.check_loop(None, body);
+
+                // The loop counter is put back once the loop ends
+                match previous {
+                    Some(ty) => self.vars.insert(name.clone(), ty),
+                    None => self.vars.remove(name),
+                };
+            }
+            Stmt::Input(_, name) => {
+                let input = TypeSet::OneOf(vec![Type::Number, Type::String]);
+                self.vars.insert(name.clone(), input);
+            }
+            Stmt::Output(expr_nodes) => {
+                for expr_node in expr_nodes {
+                    self.infer(expr_node);
+                }
+            }
+            Stmt::Assert(expr_node, expected) => {
+                self.infer(expr_node);
+                self.infer(expected);
+            }
+            Stmt::Expr(expr_node) => {
+                self.validator.start_record();
+                self.infer(expr_node);
+
+                // The returned value is thrown away, so there does not have to be one
+                if self
+                    .validator
+                    .is_last_recorded_expr_error(ErrorType::NoReturn, expr_node.call_line())
+                {
+                    self.validator.errors.pop();
+                }
+            }
+            Stmt::MethodReturn(expr_node) => {
+                self.infer(expr_node);
+            }
+            Stmt::Import(_)
+            | Stmt::FunctionDeclaration(_)
+            | Stmt::ClassDeclaration(_)
+            | Stmt::EOI => {}
+        }
+    }
+
+    fn check_branch(&mut self, stmt_nodes: &[StmtNode], outcomes: &mut Vec<TypeEnv>) {
+        self.check_body(stmt_nodes);
+        if !flow(stmt_nodes).returns() {
+            outcomes.push(self.vars.clone());
+        }
+    }
+
+    /// Checks the body with the types its variables can have at the start of any pass, found by
+    /// going through it until they stop changing. The body may also not run at all.
+    fn check_loop(&mut self, cond: Option<&ExprNode>, body: &[StmtNode]) {
+        let report = self.report;
+        self.report = false;
+
+        let mut start = self.vars.clone();
+        for pass in 0.. {
+            self.vars = start.clone();
+            if let Some(cond) = cond {
+                self.check_condition(cond);
+            }
+            self.check_body(body);
+
+            let next = merge(&[start.clone(), self.vars.clone()]);
+            if next == start {
+                break;
+            }
+            start = if pass == MAX_LOOP_PASSES {
+                TypeEnv::new()
+            } else {
+                next
+            };
+        }
+
+        self.report = report;
+        self.vars = start.clone();
+        if let Some(cond) = cond {
+            self.check_condition(cond);
+        }
+        self.check_body(body);
+        self.vars = start;
+    }
+
+    fn check_assign(
+        &mut self,
+        line: &LineInfo,
+        target: &AssignTarget,
+        op: &AssignOperator,
+        value: TypeSet,
+    ) {
+        match target {
+            AssignTarget::Ident(name) => {
+                let ty = match op {
+                    AssignOperator::Assign => value,
+                    _ => {
+                        let current = self.var(name);
+                        self.compound_assign(line, current, op, value)
+                    }
+                };
+                self.vars.insert(name.clone(), ty);
+            }
+            AssignTarget::Array(array_expr, index_expr) => {
+                let array = self.infer(array_expr);
+                self.check_number(index_expr);
+
+                if array.never(|ty| *ty == Type::Array) {
+                    self.error(invalid_type_call_error(
+                        line,
+                        "assignment into an index expression",
+                        &array,
+                        "arrays",
+                        "invalid index expression",
+                    ));
+                }
+            }
+        }
+    }
+
+    /// Type of a variable after `+=`, `-=`, `*=` or `/=`, which unlike the operators join a
+    /// string with any value.
+    fn compound_assign(
+        &mut self,
+        line: &LineInfo,
+        current: TypeSet,
+        op: &AssignOperator,
+        value: TypeSet,
+    ) -> TypeSet {
+        let (Some(lefts), Some(rights)) = (current.types(), value.types()) else {
+            return match op {
+                AssignOperator::AssignAdd => TypeSet::Unknown,
+                _ => TypeSet::of(Type::Number),
+            };
+        };
+
+        let is_add = matches!(op, AssignOperator::AssignAdd);
+        let result = union_of(lefts, rights, |left, right| {
+            if is_add && (*left == Type::String || *right == Type::String) {
+                Some(Type::String)
+            } else if left.is_numeric() && right.is_numeric() {
+                Some(Type::Number)
+            } else {
+                None
+            }
+        });
+
+        result.unwrap_or_else(|| {
+            let operand = match op {
+                AssignOperator::AssignAdd => Operand::Add,
+                AssignOperator::AssignSubtract => Operand::Subtract,
+                AssignOperator::AssignMultiply => Operand::Multiply,
+                AssignOperator::Assign | AssignOperator::AssignDivide => Operand::Divide,
+            };
+            // Adding converts both sides to numbers, the others only take numbers
+            let error = if is_add {
+                let not_number = if current.never(Type::is_numeric) {
+                    &current
+                } else {
+                    &value
+                };
+                not_a_number_error(line, not_number)
+            } else {
+                unsupported_operand_types_error(line, &current, &operand, &value)
+            };
+            self.error(error);
+            TypeSet::Unknown
+        })
+    }
+
+    fn check_condition(&mut self, expr_node: &ExprNode) {
+        let ty = self.infer(expr_node);
+        self.check_bool(&expr_node.line_info, &ty);
+    }
+
+    fn check_bool(&mut self, line: &LineInfo, ty: &TypeSet) {
+        if ty.never(Type::is_scalar) {
+            self.error(diagnostic(
+                line,
+                ErrorCode::NotABoolean,
+                format!("cannot convert `{}` to a boolean", ty),
+                "never a number, boolean or string",
+            ));
+        }
+    }
+
+    fn check_number(&mut self, expr_node: &ExprNode) {
+        let ty = self.infer(expr_node);
+        if ty.never(Type::is_numeric) {
+            self.error(not_a_number_error(&expr_node.line_info, &ty));
+        }
+    }
+
+    /// Type of the value of `expr_node`, reporting the operations in it that always fail.
+    fn infer(&mut self, expr_node: &ExprNode) -> TypeSet {
+        let line = &expr_node.line_info;
+        match &expr_node.expr {
+            Expr::Var(name) => self.var(name),
+            Expr::Data(value) => TypeSet::of_value(value),
+            Expr::ArrayNew(expr_nodes) => {
+                for expr_node in expr_nodes {
+                    self.infer(expr_node);
+                }
+                TypeSet::of(Type::Array)
+            }
+            Expr::Unary(op, expr_node) => {
+                let ty = self.infer(expr_node);
+                let (name, result) = match op {
+                    UnaryOp::Neg => ("negate", Type::Number),
+                    UnaryOp::Not => ("not", Type::Bool),
+                };
+
+                if ty.never(Type::is_numeric) {
+                    self.error(diagnostic(
+                        line,
+                        ErrorCode::InvalidUnaryOperand,
+                        format!("cannot apply `{}` operand on `{}`", name, ty),
+                        "only numbers and booleans are supported",
+                    ));
+                }
+                TypeSet::of(result)
+            }
+            Expr::BinOp(left, op, right) => {
+                let left_ty = self.infer(left);
+                let right_ty = self.infer(right);
+
+                if matches!(op, Operand::And | Operand::Or) {
+                    self.check_bool(&left.line_info, &left_ty);
+                    self.check_bool(&right.line_info, &right_ty);
+                    return TypeSet::of(Type::Bool);
+                }
+                self.binop(line, left_ty, op, right_ty)
+            }
+            Expr::NativeFunctionCall(native_method, target, fn_line, params) => {
+                for param in params {
+                    match native_method {
+                        NativeMethod::SubstringCall => self.check_number(param),
+                        _ => {
+                            self.infer(param);
+                        }
+                    }
+                }
+
+                match native_method {
+                    NativeMethod::Input => TypeSet::OneOf(vec![Type::Number, Type::String]),
+                    NativeMethod::MathRandom => TypeSet::of(Type::Number),
+                    NativeMethod::SubstringCall => {
+                        let ty = self.infer(target.as_ref().unwrap());
+                        if ty.never(|ty| *ty == Type::String) {
+                            self.error(invalid_type_call_error(
+                                fn_line,
+                                "`.substring(start, end)`",
+                                &ty,
+                                "strings",
+                                "method",
+                            ));
+                        }
+                        TypeSet::of(Type::String)
+                    }
+                    NativeMethod::LengthCall => {
+                        let target = target.as_ref().unwrap();
+                        let ty = self.infer(target);
+                        if ty.never(|ty| matches!(ty, Type::String | Type::Array)) {
+                            self.error(invalid_type_call_error(
+                                &target.line_info,
+                                "`.length`",
+                                &ty,
+                                "strings and arrays",
+                                "has no length",
+                            ));
+                        }
+                        TypeSet::of(Type::Number)
+                    }
+                }
+            }
+            Expr::LocalFunctionCall(_, params) => {
+                for param in params {
+                    self.infer(param);
+                }
+
+                // Methods of the main program can change the variables of their caller
+                if self.class_name == MAIN_CLASS {
+                    let main_writes = &self.main_writes;
+                    self.vars.retain(|name, _| !main_writes.contains(name));
+                }
+                TypeSet::Unknown
+            }
+            Expr::StaticFunctionCall(_, _, _, params) => {
+                for param in params {
+                    self.infer(param);
+                }
+                TypeSet::Unknown
+            }
+            Expr::StaticGetVar(..) => TypeSet::Unknown,
+            Expr::ClassFunctionCall {
+                expr,
+                fn_line,
+                fn_name,
+                params,
+            } => {
+                let ty = self.infer(expr);
+                for param in params {
+                    self.infer(param);
+                }
+
+                if let Some((class_name, class)) = self.instance_class(&ty) {
+                    match class.functions.get(fn_name) {
+                        Some(fn_def) => {
+                            if self.report {
+                                AST::validate_fn_call(
+                                    fn_line,
+                                    class_name,
+                                    fn_name,
+                                    fn_def,
+                                    params,
+                                    self.validator,
+                                );
+                            }
+                        }
+                        None => self.error(
+                            undefined_fn_in_class_error(fn_line, class_name, fn_name)
+                                .with_help(self.ast.suggest_function(class_name, fn_name)),
+                        ),
+                    }
+                } else if ty.never(|ty| matches!(ty, Type::Instance(_))) {
+                    self.error(diagnostic(
+                        line,
+                        ErrorCode::CallOnNonInstance,
+                        format!(
+                            "tried invoking a method `{}` not on an instance of a class: `{}`",
+                            fn_name, ty
+                        ),
+                        "not an object",
+                    ));
+                }
+                TypeSet::Unknown
+            }
+            Expr::ClassGetVar(expr, var_line, var_name) => {
+                let ty = self.infer(expr);
+                if let Some((class_name, class)) = self.instance_class(&ty) {
+                    if !class.public_vars.contains(var_name) {
+                        self.error(no_public_var_error(var_line, var_name, class_name));
+                    }
+                } else if ty.never(|ty| matches!(ty, Type::Instance(_))) {
+                    self.error(diagnostic(
+                        line,
+                        ErrorCode::AccessOnNonInstance,
+                        format!(
+                            "tried accessing a variable `{}` not on an instance of a class: `{}`",
+                            var_name, ty
+                        ),
+                        "not an object",
+                    ));
+                }
+                TypeSet::Unknown
+            }
+            Expr::ClassNew(_, class_name, params) => {
+                for param in params {
+                    self.infer(param);
+                }
+
+                // Making an instance of a missing or static class is already an error
+                match self.ast.get_class(class_name) {
+                    Some(class) if !class.is_static => {
+                        TypeSet::of(Type::Instance(class_name.clone()))
+                    }
+                    _ => TypeSet::Unknown,
+                }
+            }
+            Expr::Index(left, index) => {
+                self.check_number(index);
+                let ty = self.infer(left);
+
+                if ty.never(|ty| matches!(ty, Type::String | Type::Array)) {
+                    self.error(invalid_type_call_error(
+                        line,
+                        "index expression",
+                        &ty,
+                        "strings and arrays",
+                        "invalid index expression",
+                    ));
+                }
+                // Elements of arrays can be anything
+                match ty.types() {
+                    Some([Type::String]) => TypeSet::of(Type::String),
+                    _ => TypeSet::Unknown,
+                }
+            }
+        }
+    }
+
+    /// Class of the instance `ty` always is.
+    fn instance_class<'t>(&self, ty: &'t TypeSet) -> Option<(&'t NameHash, &'a Class)> {
+        match ty.types() {
+            Some([Type::Instance(class_name)]) => {
+                Some((class_name, self.ast.get_class(class_name)?))
+            }
+            _ => None,
+        }
+    }
+
+    /// Type of a binary operation other than `&&` and `||`, like the evaluator works it out.
+    fn binop(&mut self, line: &LineInfo, left: TypeSet, op: &Operand, right: TypeSet) -> TypeSet {
+        let is_comparison = matches!(
+            op,
+            Operand::Greater
+                | Operand::Less
+                | Operand::GreaterEqual
+                | Operand::LessEqual
+                | Operand::Equal
+                | Operand::NotEqual
+        );
+
+        let (Some(lefts), Some(rights)) = (left.types(), right.types()) else {
+            return match op {
+                _ if is_comparison => TypeSet::of(Type::Bool),
+                Operand::Add => TypeSet::Unknown,
+                _ => TypeSet::of(Type::Number),
+            };
+        };
+
+        let result = union_of(lefts, rights, |left, right| {
+            let with_string = (*left == Type::String && right.is_scalar())
+                || (left.is_scalar() && *right == Type::String);
+
+            if with_string {
+                match op {
+                    Operand::Add => Some(Type::String),
+                    _ if is_comparison => Some(Type::Bool),
+                    _ => None,
+                }
+            } else if matches!(op, Operand::Equal | Operand::NotEqual) {
+                Some(Type::Bool)
+            } else if left.is_numeric() && right.is_numeric() {
+                Some(if is_comparison {
+                    Type::Bool
+                } else {
+                    Type::Number
+                })
+            } else {
+                None
+            }
+        });
+
+        result.unwrap_or_else(|| {
+            self.error(unsupported_operand_types_error(line, &left, op, &right));
+            TypeSet::Unknown
+        })
+    }
+}
+
+/// Types of the results of `op` for every pair of types, nothing when it fails for all of them.
+fn union_of(
+    lefts: &[Type],
+    rights: &[Type],
+    op: impl Fn(&Type, &Type) -> Option<Type>,
+) -> Option<TypeSet> {
+    let mut result: Option<TypeSet> = None;
+    for left in lefts {
+        for right in rights {
+            if let Some(ty) = op(left, right) {
+                let ty = TypeSet::of(ty);
+                result = Some(match result {
+                    Some(result) => result.union(&ty),
+                    None => ty,
+                });
+            }
+        }
+    }
+    result
+}
+
+/// Types after any of the ways that lead to the same point, a variable is only known if it is
+/// known on all of them.
+fn merge(outcomes: &[TypeEnv]) -> TypeEnv {
+    let mut merged = outcomes[0].clone();
+    for outcome in &outcomes[1..] {
+        merged = merged
+            .into_iter()
+            .filter_map(|(name, ty)| Some((name.clone(), ty.union(outcome.get(&name)?))))
+            .collect();
+    }
+    merged
+}
+
+fn not_a_number_error(line: &LineInfo, ty: &TypeSet) -> Diagnostic {
+    diagnostic(
+        line,
+        ErrorCode::NotANumber,
+        format!("cannot convert `{}` to a number", ty),
+        "never a number",
+    )
+}
+
+/// Variables `stmt_nodes` assign, apart from loop counters which are put back after the loop.
+fn collect_writes(stmt_nodes: &[StmtNode], names: &mut HashSet<NameHash>) {
+    for stmt_node in stmt_nodes {
+        match &stmt_node.stmt {
+            Stmt::Assign(AssignTarget::Ident(name), ..)
+            | Stmt::Increment(AssignTarget::Ident(name))
+            | Stmt::Decrement(AssignTarget::Ident(name))
+            | Stmt::Input(_, name) => {
+                names.insert(name.clone());
+            }
+            Stmt::If {
+                then_branch,
+                elifs,
+                else_branch,
+                ..
+            } => {
+                collect_writes(then_branch, names);
+                for (_, stmt_nodes) in elifs {
+                    collect_writes(stmt_nodes, names);
+                }
+                if let Some(else_branch) = else_branch {
+                    collect_writes(else_branch, names);
+                }
+            }
+            Stmt::While(_, body) | Stmt::Until(_, body) | Stmt::For(.., body) => {
+                collect_writes(body, names);
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,12 @@
+//! Language server for IB pseudocode, talking the Language Server Protocol over stdio.
+
+fn main() {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::process::exit(ib_pcode_compiler::lsp::run_stdio());
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        ib_pcode_compiler::ensure_dependency();
+    }
+}
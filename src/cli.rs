@@ -0,0 +1,628 @@
+use crate::ast::AST;
+use crate::compiler::error_json::diagnostic_to_json;
+use crate::compiler::error_print::{
+    no_color_requested, print_diagnostic_error, set_colored_output, set_context_lines,
+};
+use crate::compiler::explain::{explain, explanation};
+use crate::compiler::formatter::{FormatError, format_code};
+use crate::compiler::imports::ImportResolver;
+use crate::compiler::{CompileErrors, CompileOptions, compile_with_options, compile_with_path};
+use crate::data::SourceMap;
+use crate::data::diagnostic::{Diagnostic, ErrorCode, Severity};
+use crate::data::lint::{Lint, LintLevel, LintLevels};
+use crate::data::scoping::Scoping;
+use crate::data::style::StyleProfile;
+use crate::env::limits::{DEFAULT_MAX_CALL_DEPTH, ExecutionLimits, NESTING_PER_CALL};
+use crate::env::{Env, EnvMode, InputSource};
+use crate::{COMPILE_ERROR_EXIT_CODE, RUNTIME_ERROR_CATEGORY, print_runtime_error, run};
+use std::collections::VecDeque;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use std::{fs, thread};
+
+/// Process exit code used when the command line arguments are invalid.
+pub const USAGE_EXIT_CODE: i32 = 64;
+/// Process exit code used when a file cannot be read.
+pub const IO_ERROR_EXIT_CODE: i32 = 66;
+/// Process exit code used when `test` finds at least one failing program.
+pub const TEST_FAILURE_EXIT_CODE: i32 = 3;
+/// Process exit code used when `fmt --check` finds a file that is not formatted.
+pub const UNFORMATTED_EXIT_CODE: i32 = 4;
+
+/// File read when the binary is started without arguments.
+const LEGACY_SOURCE: &str = "source";
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+const USAGE: &str = "\
+Usage: ib_pcode_compiler <command> <path> [options]
+
+Commands:
+  run <file>     Compile and run a program
+  check <file>   Compile a program without running it
+  ast <file>     Print the syntax tree of a program
+  test <dir>     Run every program in a directory and compare the output
+                 with `<name>.out`, feeding inputs from `<name>.in`
+  fmt <file>     Format a program in place
+
+Options:
+  --inputs <file>  Take `input` values from the lines of <file>
+  --stdin          Read `input` values from stdin without printing prompts
+  --prompt         Ask for every `input` value with a prompt (default)
+  --color          Always colour error messages
+  --no-color       Never colour error messages, also set by the NO_COLOR variable
+  --context <n>    Show <n> lines of code around every error location
+  --watch          Run the command again every time the file, a file in the directory
+                   given to `test`, or a file they import changes
+  --check          With `fmt`, only report whether the file is formatted
+  --allow <lint>   Do not report the lint, `all` for every lint
+  --warn <lint>    Report the lint as a warning (default)
+  --deny <lint>    Report the lint as an error, which stops the program from running
+  --style          Warn about names that do not follow the IB conventions: UPPERCASE
+                   variables, camelCase methods, PascalCase classes and meaningful names
+  --dynamic-scoping
+                   Let methods see and change the variables of their caller, as older
+                   versions did, instead of only their own and those of the main program
+  --block-scoping  Drop the variables first assigned in an `if` or loop body at the end
+                   of the body, instead of keeping them until the end of the method
+  --max-call-depth <n>
+                   Stop the program once more than <n> calls run at the same time (default 1000)
+  --message-format=<human|json>
+                   Print errors for people (default), or as one JSON object per line on
+                   stdout; `run` prints them on stderr, apart from the program output
+  --explain <code> Explain an error code such as P0301, or list all codes
+  -h, --help       Print this message";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    Run,
+    Check,
+    Ast,
+    Test,
+    Fmt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputMode {
+    Prompt,
+    Stdin,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub path: PathBuf,
+    pub inputs: Option<PathBuf>,
+    pub input_mode: InputMode,
+    pub color: Option<bool>,
+    pub watch: bool,
+    pub message_format: MessageFormat,
+    pub context_lines: u32,
+    pub check: bool,
+    /// Levels of the lints checked by `run` and `check`
+    pub lints: LintLevels,
+    /// Check the naming conventions of the IB style profile
+    pub style: bool,
+    pub scoping: Scoping,
+    /// Calls a program may nest before it is stopped, such as by an endless recursion
+    pub max_call_depth: usize,
+}
+
+impl Options {
+    fn new(command: Command, path: PathBuf) -> Self {
+        Self {
+            command,
+            path,
+            inputs: None,
+            input_mode: InputMode::Prompt,
+            color: None,
+            watch: false,
+            message_format: MessageFormat::Human,
+            context_lines: 0,
+            check: false,
+            lints: LintLevels::default(),
+            style: false,
+            scoping: Scoping::default(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+        }
+    }
+}
+
+/// Runs the command line interface with `args` (without the program name) and returns the exit code.
+pub fn main(args: impl IntoIterator<Item = String>) -> i32 {
+    let args: Vec<String> = args.into_iter().collect();
+
+    if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{}", USAGE);
+        return 0;
+    }
+
+    if let Some(position) = args.iter().position(|arg| arg == "--explain") {
+        return explain_code(args.get(position + 1));
+    }
+
+    let options = if args.is_empty() {
+        Options::new(Command::Run, PathBuf::from(LEGACY_SOURCE))
+    } else {
+        match parse_args(&args) {
+            Ok(options) => options,
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                return USAGE_EXIT_CODE;
+            }
+        }
+    };
+
+    set_colored_output(
+        options
+            .color
+            .unwrap_or_else(|| std::io::stderr().is_terminal() && !no_color_requested()),
+    );
+    set_context_lines(options.context_lines);
+
+    if options.watch {
+        watch(&options)
+    } else {
+        execute(&options)
+    }
+}
+
+pub fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut args = args.iter();
+
+    let command = match args.next().map(String::as_str) {
+        Some("run") => Command::Run,
+        Some("check") => Command::Check,
+        Some("ast") => Command::Ast,
+        Some("test") => Command::Test,
+        Some("fmt") => Command::Fmt,
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+        None => return Err("missing command".to_string()),
+    };
+
+    let mut path = None;
+    let mut options = Options::new(command, PathBuf::new());
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--inputs" => match args.next() {
+                Some(file) => options.inputs = Some(PathBuf::from(file)),
+                None => return Err("`--inputs` expects a file".to_string()),
+            },
+            "--stdin" => options.input_mode = InputMode::Stdin,
+            "--prompt" => options.input_mode = InputMode::Prompt,
+            "--color" => options.color = Some(true),
+            "--no-color" => options.color = Some(false),
+            "--watch" => options.watch = true,
+            "--check" => options.check = true,
+            "--style" => options.style = true,
+            "--dynamic-scoping" => options.scoping.dynamic_calls = true,
+            "--block-scoping" => options.scoping.block_scoped = true,
+            "--allow" | "--warn" | "--deny" => {
+                let level = match arg.as_str() {
+                    "--allow" => LintLevel::Allow,
+                    "--warn" => LintLevel::Warn,
+                    _ => LintLevel::Deny,
+                };
+                match args.next() {
+                    Some(name) if name == "all" => options.lints.set_all(level),
+                    Some(name) => match Lint::parse(name) {
+                        Some(lint) => options.lints.set(lint, level),
+                        None => return Err(format!("unknown lint `{}`", name)),
+                    },
+                    None => return Err(format!("`{}` expects a lint name", arg)),
+                }
+            }
+            "--max-call-depth" => match args.next().and_then(|depth| depth.parse().ok()) {
+                Some(depth) => options.max_call_depth = depth,
+                None => return Err("`--max-call-depth` expects a number of calls".to_string()),
+            },
+            "--context" => match args.next().and_then(|lines| lines.parse().ok()) {
+                Some(lines) => options.context_lines = lines,
+                None => return Err("`--context` expects a number of lines".to_string()),
+            },
+            "--message-format" => match args.next() {
+                Some(format) => options.message_format = parse_message_format(format)?,
+                None => return Err("`--message-format` expects `human` or `json`".to_string()),
+            },
+            flag if flag.starts_with("--message-format=") => {
+                options.message_format = parse_message_format(&flag["--message-format=".len()..])?;
+            }
+            flag if flag.starts_with('-') => return Err(format!("unknown option `{}`", flag)),
+            file => {
+                if path.is_some() {
+                    return Err(format!("unexpected argument `{}`", file));
+                }
+                path = Some(PathBuf::from(file));
+            }
+        }
+    }
+
+    match path {
+        Some(path) => options.path = path,
+        None => return Err("missing file or directory".to_string()),
+    }
+    Ok(options)
+}
+
+fn parse_message_format(format: &str) -> Result<MessageFormat, String> {
+    match format {
+        "human" => Ok(MessageFormat::Human),
+        "json" => Ok(MessageFormat::Json),
+        other => Err(format!("unknown message format `{}`", other)),
+    }
+}
+
+pub fn execute(options: &Options) -> i32 {
+    match options.command {
+        Command::Run => run_file(options),
+        Command::Check => check_file(options),
+        Command::Ast => print_ast(options),
+        Command::Test => test_dir(options),
+        Command::Fmt => format_file(options),
+    }
+}
+
+fn run_file(options: &Options) -> i32 {
+    let Some(code) = read_file(&options.path) else {
+        return IO_ERROR_EXIT_CODE;
+    };
+
+    let input = match &options.inputs {
+        Some(inputs_path) => {
+            let Some(inputs) = read_file(inputs_path) else {
+                return IO_ERROR_EXIT_CODE;
+            };
+            InputSource::Queue(inputs.lines().map(str::to_string).collect())
+        }
+        None => match options.input_mode {
+            InputMode::Prompt => InputSource::Prompt,
+            InputMode::Stdin => InputSource::Stdin,
+        },
+    };
+
+    let ast = match compile_with_options(&code, Some(&options.path), &compile_options(options)) {
+        Ok(ast) => ast,
+        Err(errors) => {
+            report_compile_errors(&errors, options);
+            return COMPILE_ERROR_EXIT_CODE;
+        }
+    };
+    if !report_warnings(&ast, options) {
+        return COMPILE_ERROR_EXIT_CODE;
+    }
+
+    let outcome = run(&ast, Env::cli(input).with_limits(execution_limits(options)));
+    if let Some(diagnostic) = outcome.diagnostic() {
+        report_runtime_error(&ast, diagnostic, options);
+    }
+    outcome.exit_code()
+}
+
+fn check_file(options: &Options) -> i32 {
+    let path = &options.path;
+    let Some(code) = read_file(path) else {
+        return IO_ERROR_EXIT_CODE;
+    };
+
+    match compile_with_options(&code, Some(path), &compile_options(options)) {
+        Ok(ast) => {
+            if !report_warnings(&ast, options) {
+                return COMPILE_ERROR_EXIT_CODE;
+            }
+            if options.message_format == MessageFormat::Human {
+                println!("{}: no errors found", path.display());
+            }
+            0
+        }
+        Err(errors) => {
+            report_compile_errors(&errors, options);
+            COMPILE_ERROR_EXIT_CODE
+        }
+    }
+}
+
+fn format_file(options: &Options) -> i32 {
+    let path = &options.path;
+    let Some(code) = read_file(path) else {
+        return IO_ERROR_EXIT_CODE;
+    };
+
+    let formatted = match format_code(&code) {
+        Ok(formatted) => formatted,
+        Err(FormatError::InvalidSyntax) => {
+            match compile_with_path(&code, path) {
+                Err(errors) => report_compile_errors(&errors, options),
+                Ok(_) => eprintln!("error: cannot parse `{}`", path.display()),
+            }
+            return COMPILE_ERROR_EXIT_CODE;
+        }
+        Err(FormatError::ChangedProgram) => {
+            eprintln!(
+                "error: formatting `{}` would change the program, the file was left unchanged",
+                path.display()
+            );
+            return UNFORMATTED_EXIT_CODE;
+        }
+    };
+
+    if formatted == code {
+        println!("{}: already formatted", path.display());
+        return 0;
+    }
+    if options.check {
+        eprintln!("{}: not formatted", path.display());
+        return UNFORMATTED_EXIT_CODE;
+    }
+
+    match fs::write(path, formatted) {
+        Ok(()) => {
+            println!("{}: formatted", path.display());
+            0
+        }
+        Err(err) => {
+            eprintln!("error: cannot write `{}`: {}", path.display(), err);
+            IO_ERROR_EXIT_CODE
+        }
+    }
+}
+
+fn print_ast(options: &Options) -> i32 {
+    let Some(code) = read_file(&options.path) else {
+        return IO_ERROR_EXIT_CODE;
+    };
+
+    match compile_with_path(&code, &options.path) {
+        Ok(ast) => {
+            println!("{}", ast);
+            0
+        }
+        Err(errors) => {
+            report_compile_errors(&errors, options);
+            COMPILE_ERROR_EXIT_CODE
+        }
+    }
+}
+
+fn report_compile_errors(errors: &CompileErrors, options: &Options) {
+    report_diagnostics(&errors.source_map, &errors.diagnostics, options);
+}
+
+fn compile_options(options: &Options) -> CompileOptions {
+    CompileOptions {
+        style: options.style.then(StyleProfile::ib),
+        scoping: options.scoping.clone(),
+    }
+}
+
+fn execution_limits(options: &Options) -> ExecutionLimits {
+    ExecutionLimits {
+        max_call_depth: Some(options.max_call_depth),
+        max_nesting_depth: Some(options.max_call_depth.saturating_mul(NESTING_PER_CALL)),
+        ..ExecutionLimits::default()
+    }
+}
+
+/// Reports the warnings found while compiling `ast` and its lints at the levels of `options`,
+/// returns whether none of the lints is denied.
+fn report_warnings(ast: &AST, options: &Options) -> bool {
+    let mut diagnostics = ast.warnings.clone();
+    diagnostics.extend(ast.lint(&options.lints));
+    report_diagnostics(&ast.source_map, &diagnostics, options);
+    diagnostics
+        .iter()
+        .all(|diagnostic| diagnostic.severity == Severity::Warning)
+}
+
+fn report_diagnostics(source_map: &SourceMap, diagnostics: &[Diagnostic], options: &Options) {
+    for diagnostic in diagnostics {
+        let error_category = CompileErrors::error_category(diagnostic);
+        match options.message_format {
+            MessageFormat::Human => print_diagnostic_error(source_map, error_category, diagnostic),
+            MessageFormat::Json => print_json(
+                diagnostic_to_json(source_map, error_category, diagnostic),
+                options,
+            ),
+        }
+    }
+}
+
+fn report_runtime_error(ast: &AST, diagnostic: &Diagnostic, options: &Options) {
+    match options.message_format {
+        MessageFormat::Human => print_runtime_error(ast, diagnostic),
+        MessageFormat::Json => print_json(
+            diagnostic_to_json(&ast.source_map, RUNTIME_ERROR_CATEGORY, diagnostic),
+            options,
+        ),
+    }
+}
+
+/// JSON messages go to stdout, except with `run`, where they would be mixed up with the output
+/// of the program.
+fn print_json(json: String, options: &Options) {
+    if options.command == Command::Run {
+        eprintln!("{}", json);
+    } else {
+        println!("{}", json);
+    }
+}
+
+/// Runs every program in `dir`. A program passes when it compiles, finishes without a runtime error
+/// and, if `<name>.out` exists, prints exactly its lines. Inputs are taken from `<name>.in`.
+fn test_dir(options: &Options) -> i32 {
+    let dir = &options.path;
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) => {
+            eprintln!("error: cannot read directory `{}`: {}", dir.display(), err);
+            return IO_ERROR_EXIT_CODE;
+        }
+    };
+
+    let mut programs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && !is_test_data(path))
+        .collect();
+    programs.sort();
+
+    let mut failed = 0;
+    for program in &programs {
+        match test_program(program, options) {
+            Ok(()) => println!("test {} ... ok", program.display()),
+            Err(reason) => {
+                println!("test {} ... FAILED\n{}", program.display(), reason);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "\ntest result: {} passed; {} failed",
+        programs.len() - failed,
+        failed
+    );
+
+    if failed > 0 {
+        TEST_FAILURE_EXIT_CODE
+    } else {
+        0
+    }
+}
+
+fn is_test_data(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("in") | Some("out")
+    )
+}
+
+fn test_program(program: &Path, options: &Options) -> Result<(), String> {
+    let code = fs::read_to_string(program).map_err(|err| err.to_string())?;
+    let inputs: VecDeque<String> = fs::read_to_string(program.with_extension("in"))
+        .map(|inputs| inputs.lines().map(str::to_string).collect())
+        .unwrap_or_default();
+
+    let ast = match compile_with_options(&code, Some(program), &compile_options(options)) {
+        Ok(ast) => ast,
+        Err(errors) => {
+            report_compile_errors(&errors, options);
+            return Err("compilation failed".to_string());
+        }
+    };
+    if !report_warnings(&ast, options) {
+        return Err("denied lint".to_string());
+    }
+
+    let outcome = run(
+        &ast,
+        Env::test(inputs).with_limits(execution_limits(options)),
+    );
+    if let Some(diagnostic) = outcome.diagnostic() {
+        report_runtime_error(&ast, diagnostic, options);
+        return Err("runtime error".to_string());
+    }
+
+    let Ok(expected) = fs::read_to_string(program.with_extension("out")) else {
+        return Ok(());
+    };
+    let EnvMode::Test { logs, .. } = &outcome.env.mode else {
+        unreachable!()
+    };
+
+    let expected: Vec<&str> = expected.trim().lines().collect();
+    let actual: Vec<&str> = logs.iter().map(String::as_str).collect();
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected output:\n{}\nactual output:\n{}",
+            expected.join("\n"),
+            actual.join("\n")
+        ))
+    }
+}
+
+fn explain_code(code: Option<&String>) -> i32 {
+    let Some(code) = code else {
+        for code in ErrorCode::ALL {
+            println!("{}  {}", code.as_str(), explanation(*code).title);
+        }
+        return 0;
+    };
+
+    match explain(code) {
+        Some(explanation) => {
+            println!("{}", explanation);
+            0
+        }
+        None => {
+            eprintln!("error: unknown error code `{}`", code);
+            USAGE_EXIT_CODE
+        }
+    }
+}
+
+fn watch(options: &Options) -> i32 {
+    let mut last_modified = None;
+
+    loop {
+        let modified = Some(modified_times(&options.path));
+        if modified != last_modified {
+            last_modified = modified;
+            println!("[watch] {}", options.path.display());
+            execute(options);
+        }
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// The modification times of `path`, or of every file in it when it is a directory, and of the
+/// files they import. The list also changes when a file is added or removed.
+pub fn modified_times(path: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let programs: Vec<PathBuf> = match fs::read_dir(path) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => vec![path.to_path_buf()],
+    };
+
+    let mut files = Vec::new();
+    for program in programs {
+        if let Ok(code) = fs::read_to_string(&program) {
+            let imports = ImportResolver::resolve(&code, Some(&program));
+            files.extend(imports.files.into_iter().map(|file| file.path));
+        }
+        files.push(program);
+    }
+    files.sort();
+    files.dedup();
+
+    files
+        .into_iter()
+        .map(|path| {
+            let time = modified_time(&path);
+            (path, time)
+        })
+        .collect()
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn read_file(path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Some(contents),
+        Err(err) => {
+            eprintln!("error: cannot read `{}`: {}", path.display(), err);
+            None
+        }
+    }
+}
@@ -6,12 +6,12 @@ pub fn fix_quotes_plain(s: &str) -> String {
 
     while let Some(c) = chars.next() {
         if c == '\\' {
-            if let Some(&next) = chars.peek() {
-                if next == '"' {
-                    chars.next();
-                    out.push('"');
-                    continue;
-                }
+            if let Some(&next) = chars.peek()
+                && next == '"'
+            {
+                chars.next();
+                out.push('"');
+                continue;
             }
             out.push(c);
         } else if c == '"' {
@@ -23,15 +23,15 @@ pub fn fix_quotes_plain(s: &str) -> String {
     out
 }
 
-pub fn combine_all_paths_at(dir: &Dir, contents_combined: &mut String) {
+/// Collects `(path, contents)` of every file in `dir`, files of subdirectories first.
+pub fn collect_all_files_at<'a>(dir: &'a Dir, files: &mut Vec<(String, &'a str)>) {
     for dir in dir.dirs() {
-        combine_all_paths_at(dir, contents_combined)
+        collect_all_files_at(dir, files)
     }
     for file in dir.files() {
         let contents =
             std::str::from_utf8(file.contents()).expect("Should have been able to read the file");
-        contents_combined.push_str(contents);
-        contents_combined.push('\n');
+        files.push((file.path().display().to_string(), contents));
     }
 }
 
@@ -44,3 +44,36 @@ pub fn to_bool_num(num: f64) -> bool {
 pub fn to_num_bool(bool: bool) -> f64 {
     if bool { 1.0 } else { 0.0 }
 }
+
+/// Levenshtein distance between `a` and `b`, ignoring case.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0; b.len() + 1];
+
+    for i in 1..=a.len() {
+        curr[0] = i;
+        for j in 1..=b.len() {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Returns up to 3 `candidates` close enough to `name` to likely be a typo, closest first.
+pub fn similar_names<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 3).max(1);
+
+    let mut similar: Vec<(usize, &str)> = candidates
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    similar.sort();
+    similar.dedup();
+    similar.into_iter().take(3).map(|(_, name)| name).collect()
+}
@@ -1,18 +1,31 @@
 use crate::ast::AST;
-use crate::common::combine_all_paths_at;
-use crate::compiler::error_print::{print_diagnostic_error, print_parsing_error};
-use crate::data::Validator;
-use crate::data::diagnostic::Diagnostic;
+use crate::common::collect_all_files_at;
+use crate::compiler::error_json::diagnostics_to_json;
+use crate::compiler::error_print::print_diagnostic_error;
+use crate::compiler::imports::{ImportResolver, ImportedFile};
+use crate::compiler::syntax_errors::{MAX_SYNTAX_ERRORS, SyntaxError};
+use crate::data::diagnostic::{Diagnostic, ErrorType, Severity};
 use crate::data::name_hash::with_name_map;
+use crate::data::scoping::Scoping;
+use crate::data::source_map::FileId;
+use crate::data::style::StyleProfile;
+use crate::data::{SourceMap, Validator};
 use crate::env::Env;
 use include_dir::{Dir, include_dir};
 use pest::Parser;
+use pest::error::Error;
 use pest::iterators::Pair;
 use pest_derive::Parser;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
+pub mod error_json;
 pub mod error_print;
 pub mod errors;
+pub mod explain;
+pub mod formatter;
+pub mod imports;
+pub mod syntax_errors;
 
 static INCLUDE_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/include");
 
@@ -20,70 +33,265 @@ static INCLUDE_DIR: Dir = include_dir!("$CARGO_MANIFEST_DIR/include");
 #[grammar = "grammar.pest"]
 struct DSLParser;
 
-pub fn compile(code: &str, should_panic: bool) -> AST {
-    let (program, user_code_start_line) = construct_program_string(code);
+/// Every error found while compiling a program, left for the caller to report.
+#[derive(Debug)]
+pub struct CompileErrors {
+    pub source_map: SourceMap,
+    /// Syntax errors first, followed by the errors found while validating the rest of the program
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CompileErrors {
+    pub fn print(&self) {
+        for diagnostic in &self.diagnostics {
+            print_diagnostic_error(
+                &self.source_map,
+                Self::error_category(diagnostic),
+                diagnostic,
+            );
+        }
+    }
+
+    /// Every error as a JSON array, see `error_json::diagnostic_to_json` for the format.
+    pub fn to_json(&self) -> String {
+        diagnostics_to_json(
+            &self.source_map,
+            self.diagnostics
+                .iter()
+                .map(|diagnostic| (Self::error_category(diagnostic), diagnostic)),
+        )
+    }
+
+    pub fn error_category(diagnostic: &Diagnostic) -> &'static str {
+        match diagnostic.error_type {
+            ErrorType::Syntax => "Parsing",
+            ErrorType::Lint => "Lint",
+            ErrorType::Style => "Style",
+            _ => "Compilation",
+        }
+    }
+}
+
+/// Name of the user code file when it is compiled without a path
+pub const MAIN_FILE_NAME: &str = "main";
+
+/// Checks that are off unless they are asked for, and the rules the program is compiled with.
+#[derive(Debug, Clone, Default)]
+pub struct CompileOptions {
+    /// Naming conventions reported as warnings
+    pub style: Option<StyleProfile>,
+    pub scoping: Scoping,
+}
+
+pub fn compile(code: &str) -> Result<AST, CompileErrors> {
+    compile_with_options(code, None, &CompileOptions::default())
+}
 
-    let parsed_result = parse(&program, user_code_start_line, should_panic);
+/// Compiles `code` read from `path`, so that its imports are resolved relative to that file.
+pub fn compile_with_path(code: &str, path: &Path) -> Result<AST, CompileErrors> {
+    compile_with_options(code, Some(path), &CompileOptions::default())
+}
+
+/// Compiles `code` with the checks of `options`. Warnings do not stop the program from compiling,
+/// they are kept in `AST::warnings`.
+pub fn compile_with_options(
+    code: &str,
+    path: Option<&Path>,
+    options: &CompileOptions,
+) -> Result<AST, CompileErrors> {
+    let analysis = analyze_with_options(code, path, options);
+    let has_errors = analysis
+        .errors
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error);
+
+    match analysis.ast {
+        Some(mut ast) if !has_errors => {
+            ast.warnings = analysis.errors.diagnostics;
+            Ok(ast)
+        }
+        _ => Err(analysis.errors),
+    }
+}
+
+/// A program compiled as far as its errors allow, for tools that need the AST of a program with errors.
+pub struct Analysis {
+    /// Missing when the program could not be parsed, even after repairing its syntax errors
+    pub ast: Option<AST>,
+    pub errors: CompileErrors,
+}
+
+/// Compiles `code` like `compile_with_path`, but keeps the AST built from the repaired program
+/// alongside all errors instead of discarding it.
+pub fn analyze(code: &str, path: Option<&Path>) -> Analysis {
+    analyze_with_options(code, path, &CompileOptions::default())
+}
+
+pub fn analyze_with_options(code: &str, path: Option<&Path>, options: &CompileOptions) -> Analysis {
+    let imports = ImportResolver::resolve(code, path);
+    let (program, mut source_map, import_file_ids) =
+        construct_program_string(code, path, &imports.files);
+
+    if !imports.errors.is_empty() {
+        let diagnostics = imports
+            .errors
+            .into_iter()
+            .map(|error| {
+                // Errors in the main program are the only ones without an imported file entry
+                let mut diagnostic = error.diagnostic;
+                diagnostic.line_info.file_id = error
+                    .file
+                    .as_ref()
+                    .and_then(|file| import_file_ids.get(file).copied())
+                    .unwrap_or(source_map.main_file);
+                diagnostic
+            })
+            .collect();
+
+        return Analysis {
+            ast: None,
+            errors: CompileErrors {
+                source_map,
+                diagnostics,
+            },
+        };
+    }
+
+    let (program, mut diagnostics) = recover_syntax_errors(program, &mut source_map);
+    let Ok(parsed_result) = parse(&program) else {
+        return Analysis {
+            ast: None,
+            errors: CompileErrors {
+                source_map,
+                diagnostics,
+            },
+        };
+    };
 
     let mut validator = Validator {
         validated_functions: HashMap::new(),
         errors: Vec::new(),
         added_errors: 0,
+        style: options.style.clone(),
+        unassigned: HashMap::new(),
+        method_scopes: Vec::new(),
+        caller_reads: HashMap::new(),
     };
 
-    let ast = build_ast(
-        &program,
-        user_code_start_line,
-        parsed_result,
-        &mut validator,
-    );
+    let mut ast = build_ast(source_map, parsed_result, &mut validator);
+    ast.scoping = options.scoping.clone();
     validate_ast(&ast, &mut validator);
 
-    show_compiler_errors(&ast, should_panic, &validator.errors);
-
-    ast
+    diagnostics.append(&mut validator.errors);
+    Analysis {
+        errors: CompileErrors {
+            source_map: ast.source_map.clone(),
+            diagnostics,
+        },
+        ast: Some(ast),
+    }
 }
 
-fn construct_program_string(code: &str) -> (String, u32) {
-    let includes = load_includes();
-    let user_code_start_line = includes.lines().count() as u32;
+/// Parses `program` until it has no syntax errors left, repairing it after every error,
+/// so that one compilation reports all of them. The repaired program is returned with the errors.
+fn recover_syntax_errors(
+    mut program: String,
+    source_map: &mut SourceMap,
+) -> (String, Vec<Diagnostic>) {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
 
-    let mut program = includes.clone();
-    program.push_str(code);
-    program.push('\n');
-    (program, user_code_start_line)
-}
+    while diagnostics.len() < MAX_SYNTAX_ERRORS {
+        let Err(err) = parse(&program) else {
+            break;
+        };
 
-fn load_includes() -> String {
-    let mut output = "".to_string();
-    combine_all_paths_at(&INCLUDE_DIR, &mut output);
-    output
+        let error = SyntaxError::new(source_map, &program, &err);
+        let repeated = diagnostics.last().is_some_and(|last| {
+            last.message == error.diagnostic.message
+                && last.line_info.file_id == error.diagnostic.line_info.file_id
+                && last.line_info.start_line == error.diagnostic.line_info.start_line
+        });
+        if repeated {
+            break;
+        }
+
+        let repaired = error.repair(&mut program, source_map);
+        diagnostics.push(error.diagnostic);
+        if !repaired {
+            break;
+        }
+    }
+    (program, diagnostics)
 }
 
-fn parse(program: &str, user_code_start_line: u32, should_panic: bool) -> Pair<'_, Rule> {
-    match DSLParser::parse(Rule::program, program) {
-        Ok(parsed) => parsed,
-        Err(err) => {
-            print_parsing_error(program, user_code_start_line, err);
+/// Compiles `code`, printing any errors and then either panicking or exiting the process.
+pub fn compile_or_exit(code: &str, should_panic: bool) -> AST {
+    match compile(code) {
+        Ok(ast) => ast,
+        Err(errors) => {
+            errors.print();
 
             if should_panic {
                 panic!()
             } else {
-                std::process::exit(0)
+                std::process::exit(crate::COMPILE_ERROR_EXIT_CODE)
             }
         }
     }
-    .next()
-    .unwrap()
 }
 
-fn build_ast(
-    program: &str,
-    user_code_start_line: u32,
-    parsed_result: Pair<Rule>,
-    validator: &mut Validator,
-) -> AST {
-    let mut ast = AST::new(program.to_string(), user_code_start_line);
+/// Joins the includes, the imported files and the user code into one program,
+/// registering each of them in the source map.
+fn construct_program_string(
+    code: &str,
+    path: Option<&Path>,
+    imported_files: &[ImportedFile],
+) -> (String, SourceMap, HashMap<PathBuf, FileId>) {
+    let mut program = String::new();
+    let mut source_map = SourceMap::default();
+
+    let mut includes = Vec::new();
+    collect_all_files_at(&INCLUDE_DIR, &mut includes);
+    for (name, contents) in includes {
+        source_map.push_file(&mut program, name, contents);
+    }
+
+    let main_dir = path
+        .map(|path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf()))
+        .and_then(|path| path.parent().map(Path::to_path_buf));
+
+    let mut import_file_ids = HashMap::new();
+    for file in imported_files {
+        let name = main_dir
+            .as_ref()
+            .and_then(|dir| file.path.strip_prefix(dir).ok())
+            .unwrap_or(&file.path)
+            .display()
+            .to_string();
+
+        let id = source_map.push_file(&mut program, name, &file.code);
+        import_file_ids.insert(file.path.clone(), id);
+    }
+
+    let main_name = match path {
+        Some(path) => path.display().to_string(),
+        None => MAIN_FILE_NAME.to_string(),
+    };
+    source_map.push_main_file(&mut program, main_name, code);
+
+    (program, source_map, import_file_ids)
+}
+
+fn parse(program: &str) -> Result<Pair<'_, Rule>, Box<Error<Rule>>> {
+    match DSLParser::parse(Rule::program, program) {
+        Ok(mut parsed) => Ok(parsed.next().unwrap()),
+        Err(err) => Err(Box::new(err)),
+    }
+}
+
+fn build_ast(source_map: SourceMap, parsed_result: Pair<Rule>, validator: &mut Validator) -> AST {
+    let mut ast = AST::new(source_map);
     ast.build_ast(parsed_result, validator);
     ast
 }
@@ -95,17 +303,3 @@ fn validate_ast(ast: &AST, validator: &mut Validator) {
         ast.validate(&mut env, validator);
     });
 }
-
-fn show_compiler_errors(ast: &AST, should_panic: bool, compile_errors: &Vec<Diagnostic>) {
-    if !compile_errors.is_empty() {
-        for error in compile_errors {
-            print_diagnostic_error(ast, "Compilation", error.clone());
-        }
-
-        if should_panic {
-            panic!()
-        } else {
-            std::process::exit(0)
-        }
-    }
-}
@@ -0,0 +1,88 @@
+use crate::data::SourceMap;
+use crate::data::diagnostic::{Diagnostic, LineInfo};
+
+/// Serializes `diagnostic` as one JSON object, with every location resolved to its file name.
+/// Lines and columns are 1-based, `end_col` is the column after the last character.
+pub fn diagnostic_to_json(
+    source_map: &SourceMap,
+    error_category: &str,
+    diagnostic: &Diagnostic,
+) -> String {
+    let labels: Vec<String> = diagnostic
+        .labels
+        .iter()
+        .map(|label| {
+            format!(
+                "{{\"file\":{},\"span\":{},\"message\":{}}}",
+                json_string(file_name(source_map, &label.line_info)),
+                span_to_json(&label.line_info),
+                json_string(&label.message)
+            )
+        })
+        .collect();
+
+    let help = match &diagnostic.help {
+        Some(help) => json_string(help),
+        None => "null".to_string(),
+    };
+
+    format!(
+        "{{\"category\":{},\"severity\":{},\"code\":{},\"error_type\":{},\"file\":{},\"span\":{},\"message\":{},\"note\":{},\"help\":{},\"labels\":[{}]}}",
+        json_string(&error_category.to_lowercase()),
+        json_string(diagnostic.severity.as_str()),
+        json_string(diagnostic.code.as_str()),
+        json_string(&format!("{:?}", diagnostic.error_type)),
+        json_string(file_name(source_map, &diagnostic.line_info)),
+        span_to_json(&diagnostic.line_info),
+        json_string(&diagnostic.message),
+        json_string(&diagnostic.note),
+        help,
+        labels.join(",")
+    )
+}
+
+/// Serializes `diagnostics` as a JSON array.
+pub fn diagnostics_to_json<'a>(
+    source_map: &SourceMap,
+    diagnostics: impl IntoIterator<Item = (&'a str, &'a Diagnostic)>,
+) -> String {
+    let objects: Vec<String> = diagnostics
+        .into_iter()
+        .map(|(error_category, diagnostic)| {
+            diagnostic_to_json(source_map, error_category, diagnostic)
+        })
+        .collect();
+    format!("[{}]", objects.join(","))
+}
+
+fn file_name<'a>(source_map: &'a SourceMap, line_info: &LineInfo) -> &'a str {
+    source_map
+        .files
+        .get(line_info.file_id as usize)
+        .map_or("", |file| file.name.as_str())
+}
+
+fn span_to_json(line_info: &LineInfo) -> String {
+    format!(
+        "{{\"start_line\":{},\"start_col\":{},\"end_line\":{},\"end_col\":{}}}",
+        line_info.start_line, line_info.start_col, line_info.end_line, line_info.end_col
+    )
+}
+
+pub fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
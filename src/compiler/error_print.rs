@@ -1,132 +1,323 @@
-use crate::ast::AST;
-use crate::compiler::Rule;
-use crate::data::diagnostic::Diagnostic;
-use pest::error::{Error, ErrorVariant, InputLocation};
+use crate::data::SourceMap;
+use crate::data::diagnostic::{Diagnostic, LineInfo};
+use std::borrow::Cow;
 use std::cmp::max;
+use std::sync::atomic::{AtomicU8, AtomicU32, Ordering};
 
-#[cfg(target_arch = "wasm32")]
-const RED: &str = "<span style=\"color:red;\">";
-#[cfg(not(target_arch = "wasm32"))]
-const RED: &str = "\x1b[31m";
+/// Decides how an error is marked up: colours around it and escaping of the text inside it.
+pub trait Renderer {
+    /// Written before the error
+    fn start(&self) -> &'static str;
+    /// Written after the error
+    fn end(&self) -> &'static str;
+    /// Applied to every piece of source code, file name and message put into the error
+    fn escape<'a>(&self, text: &'a str) -> Cow<'a, str>;
+}
+
+/// Text without any markup.
+pub struct PlainRenderer;
 
-#[cfg(target_arch = "wasm32")]
-const RESET: &str = "</span>";
-#[cfg(not(target_arch = "wasm32"))]
-const RESET: &str = "\x1b[0m";
+impl Renderer for PlainRenderer {
+    fn start(&self) -> &'static str {
+        ""
+    }
+
+    fn end(&self) -> &'static str {
+        ""
+    }
 
-struct ErrorLine {
-    pub user_start_line: isize,
-    pub start_line: usize,
-    pub start_col: usize,
-    pub end_col: usize,
+    fn escape<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(text)
+    }
 }
 
-pub fn print_diagnostic_error(ast: &AST, error_category: &str, diagnostic: Diagnostic) {
-    let start_line = diagnostic.line_info.start_line as usize;
+/// Text coloured red with ANSI escape codes, for terminals.
+pub struct AnsiRenderer;
 
-    let error_line = ErrorLine {
-        user_start_line: start_line as isize - ast.user_code_start_line as isize,
-        start_line,
-        start_col: diagnostic.line_info.start_col as usize,
-        end_col: diagnostic.line_info.end_col as usize,
-    };
+impl Renderer for AnsiRenderer {
+    fn start(&self) -> &'static str {
+        "\x1b[31m"
+    }
 
-    let msg = &mut String::new();
+    fn end(&self) -> &'static str {
+        "\x1b[0m"
+    }
 
-    msg.push_str(RED);
-    msg.push_str(format!("{} error: {}\n", error_category, diagnostic.message).as_str());
-    push_line_info(&ast.source, diagnostic.note.as_str(), &error_line, msg);
-    msg.push_str(RESET);
-    print_to_console(msg);
+    fn escape<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        Cow::Borrowed(text)
+    }
 }
 
-pub fn print_parsing_error(program: &str, user_code_start_line: u32, err: Error<Rule>) {
-    let (start_byte, end_byte) = match &err.location {
-        InputLocation::Pos(p) => (*p, *p),
-        InputLocation::Span((s, e)) => (*s, *e),
-    };
+/// HTML safe to insert into a page, optionally wrapped in a red `<span>`.
+pub struct HtmlRenderer {
+    pub colored: bool,
+}
 
-    // clamp to source bounds to be defensive
-    let src_len = program.len();
-    let start_byte = start_byte.min(src_len);
-    let end_byte = end_byte.min(src_len);
+impl Renderer for HtmlRenderer {
+    fn start(&self) -> &'static str {
+        if self.colored {
+            "<span style=\"color:red;\">"
+        } else {
+            ""
+        }
+    }
 
-    // helper to compute (line, col) 1-based from a byte index
-    let line_col_of = |byte_idx: usize| -> (usize, usize) {
-        let prefix = &program[..byte_idx];
-        let line = prefix.lines().count() + 1;
+    fn end(&self) -> &'static str {
+        if self.colored { "</span>" } else { "" }
+    }
 
-        let last_nl_pos = prefix.rfind('\n').map(|p| p + 1).unwrap_or(0);
-        let col = program[last_nl_pos..byte_idx].chars().count() + 1;
+    fn escape<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        if !text.contains(['&', '<', '>', '"', '\'']) {
+            return Cow::Borrowed(text);
+        }
 
-        (line, col)
-    };
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&#39;"),
+                c => escaped.push(c),
+            }
+        }
+        Cow::Owned(escaped)
+    }
+}
+
+/// Layout settings shared by every renderer.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderOptions {
+    /// Number of unmarked source lines shown before and after each location
+    pub context_lines: u32,
+}
 
-    let (start_line, start_col) = line_col_of(start_byte);
-    let (_end_line, end_col) = line_col_of(end_byte);
+const COLOR_AUTO: u8 = 0;
+const COLOR_ALWAYS: u8 = 1;
+const COLOR_NEVER: u8 = 2;
 
-    let positives = match &err.variant {
-        ErrorVariant::ParsingError {
-            positives,
-            negatives: _,
-        } => positives.clone(),
-        _ => Vec::new(),
-    };
+static COLORED_OUTPUT: AtomicU8 = AtomicU8::new(COLOR_AUTO);
+static CONTEXT_LINES: AtomicU32 = AtomicU32::new(0);
+
+/// Turns the colour markup around printed errors on or off, overriding `NO_COLOR`.
+pub fn set_colored_output(enabled: bool) {
+    let setting = if enabled { COLOR_ALWAYS } else { COLOR_NEVER };
+    COLORED_OUTPUT.store(setting, Ordering::Relaxed);
+}
 
-    let user_start_line = start_line as isize - user_code_start_line as isize;
+/// Sets how many lines around each error location are printed.
+pub fn set_context_lines(lines: u32) {
+    CONTEXT_LINES.store(lines, Ordering::Relaxed);
+}
 
-    let error_line = ErrorLine {
-        user_start_line: user_start_line - 1,
-        start_line: start_line - 1,
-        start_col,
-        end_col: end_col + 1,
+/// Whether the user asked for no colours with a non-empty `NO_COLOR` variable, see https://no-color.org
+pub fn no_color_requested() -> bool {
+    #[cfg(target_arch = "wasm32")]
+    {
+        false
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+}
+
+/// The renderer used for printed errors: HTML in the browser, ANSI or plain text elsewhere.
+pub fn default_renderer() -> &'static dyn Renderer {
+    let colored = match COLORED_OUTPUT.load(Ordering::Relaxed) {
+        COLOR_ALWAYS => true,
+        COLOR_NEVER => false,
+        _ => !no_color_requested(),
     };
 
-    let msg = &mut String::new();
+    #[cfg(target_arch = "wasm32")]
+    {
+        if colored {
+            &HtmlRenderer { colored: true }
+        } else {
+            &HtmlRenderer { colored: false }
+        }
+    }
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        if colored {
+            &AnsiRenderer
+        } else {
+            &PlainRenderer
+        }
+    }
+}
+
+pub fn default_render_options() -> RenderOptions {
+    RenderOptions {
+        context_lines: CONTEXT_LINES.load(Ordering::Relaxed),
+    }
+}
+
+/// Multi-line spans longer than this only show their first and last lines
+const MAX_SPAN_LINES: u32 = 4;
+
+pub fn print_diagnostic_error(
+    source_map: &SourceMap,
+    error_category: &str,
+    diagnostic: &Diagnostic,
+) {
+    let msg = render_diagnostic(
+        default_renderer(),
+        &default_render_options(),
+        source_map,
+        error_category,
+        diagnostic,
+    );
+    print_to_console(&msg);
+}
+
+pub fn render_diagnostic(
+    renderer: &dyn Renderer,
+    options: &RenderOptions,
+    source_map: &SourceMap,
+    error_category: &str,
+    diagnostic: &Diagnostic,
+) -> String {
+    let mut msg = String::new();
 
-    msg.push_str(RED);
-    msg.push_str("Parsing error\n");
-    push_line_info(program, "", &error_line, msg);
-    msg.push_str(format!("Expected grammar: {:?}\n", positives).as_str());
-    msg.push_str(RESET);
-    print_to_console(msg);
+    msg.push_str(renderer.start());
+    msg.push_str(
+        format!(
+            "{} {}[{}]: {}\n",
+            error_category,
+            diagnostic.severity.as_str(),
+            diagnostic.code.as_str(),
+            renderer.escape(&diagnostic.message)
+        )
+        .as_str(),
+    );
+    push_line_info(
+        renderer,
+        options,
+        source_map,
+        diagnostic.note.as_str(),
+        &diagnostic.line_info,
+        &mut msg,
+    );
+    for label in &diagnostic.labels {
+        push_line_info(
+            renderer,
+            options,
+            source_map,
+            label.message.as_str(),
+            &label.line_info,
+            &mut msg,
+        );
+    }
+    if let Some(help) = &diagnostic.help {
+        msg.push_str(format!("help: {}\n", renderer.escape(help)).as_str());
+    }
+    msg.push_str(renderer.end());
+    msg
 }
 
-fn push_line_info(source: &str, note: &str, info: &ErrorLine, msg: &mut String) {
-    let lines: Vec<&str> = source.lines().collect();
+fn push_line_info(
+    renderer: &dyn Renderer,
+    options: &RenderOptions,
+    source_map: &SourceMap,
+    note: &str,
+    info: &LineInfo,
+    msg: &mut String,
+) {
+    let file = source_map.file(info.file_id);
+
+    msg.push_str(
+        format!(
+            "At line: {} in {}\n",
+            info.start_line,
+            renderer.escape(&file.name)
+        )
+        .as_str(),
+    );
 
-    msg.push_str(format!("At line: {}\n", info.user_start_line).as_str());
+    let end_line = max(info.start_line, info.end_line);
+    let context_start = info.start_line.saturating_sub(options.context_lines).max(1);
+    let context_end = end_line + options.context_lines;
+    let width = context_end.to_string().chars().count();
+    let ident = " ".repeat(width);
 
-    if let Some(line_text) = lines.get(info.start_line - 1) {
-        let indent_len = info.user_start_line.to_string().chars().count();
+    if file.line(info.start_line).is_none() {
+        return;
+    }
+    msg.push_str(format!("{} | \n", ident).as_str());
+
+    for line in context_start..info.start_line {
+        push_source_line(renderer, file.line(line).unwrap_or(""), line, width, msg);
+    }
 
-        let mut ident = String::new();
-        for _ in 0..indent_len {
-            ident.push(' ');
+    for line in info.start_line..=end_line {
+        let span_lines = end_line - info.start_line + 1;
+        if span_lines > MAX_SPAN_LINES && line > info.start_line + 1 && line < end_line {
+            if line == info.start_line + 2 {
+                msg.push_str(format!("{} | ...\n", ident).as_str());
+            }
+            continue;
         }
 
-        msg.push_str(format!("{} | \n", ident).as_str());
-        msg.push_str(format!("{} | {}\n", info.user_start_line, line_text).as_str());
+        let Some(line_text) = file.line(line) else {
+            break;
+        };
+
+        // Continued lines are underlined from their first to their last character
+        let start_col = if line == info.start_line {
+            info.start_col as usize
+        } else {
+            line_text.len() - line_text.trim_start().len() + 1
+        };
+        let end_col = if line == end_line {
+            info.end_col as usize
+        } else {
+            line_text.trim_end().chars().count() + 1
+        };
+
+        push_source_line(renderer, line_text, line, width, msg);
 
         let mut underline = String::new();
-        for _ in 1..info.start_col {
+        for _ in 1..start_col {
             underline.push(' ');
         }
 
-        let width = max(1, info.end_col.saturating_sub(info.start_col));
-
-        msg.push_str(format!("{} | ", ident).as_str());
-        for _ in 0..width {
+        let underline_width = max(1, end_col.saturating_sub(start_col));
+        for _ in 0..underline_width {
             underline.push('^');
         }
-        msg.push_str(format!("{} {}\n", underline, note).as_str());
+
+        if line == end_line {
+            msg.push_str(format!("{} | {} {}\n", ident, underline, renderer.escape(note)).as_str());
+        } else {
+            msg.push_str(format!("{} | {}\n", ident, underline).as_str());
+        }
     }
+
+    for line in end_line + 1..=context_end {
+        let Some(line_text) = file.line(line) else {
+            break;
+        };
+        push_source_line(renderer, line_text, line, width, msg);
+    }
+}
+
+fn push_source_line(
+    renderer: &dyn Renderer,
+    line_text: &str,
+    line: u32,
+    width: usize,
+    msg: &mut String,
+) {
+    msg.push_str(format!("{:>width$} | {}\n", line, renderer.escape(line_text)).as_str());
 }
 
 fn print_to_console(msg: &str) {
     #[cfg(target_arch = "wasm32")]
     {
-        crate::ast::write_output(msg);
+        crate::ast::write_diagnostic(msg);
     }
     #[cfg(not(target_arch = "wasm32"))]
     {
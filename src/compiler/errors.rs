@@ -1,6 +1,8 @@
-use crate::data::ast_nodes::Operand;
-use crate::data::diagnostic::{Diagnostic, ErrorType, LineInfo};
+use crate::data::ast_nodes::{Function, Operand};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo, Severity};
+use crate::data::types::TypeSet;
 use crate::data::{NameHash, Validator, Value};
+use std::fmt::Display;
 
 pub fn compile_error(diagnostic: Diagnostic, validator: &mut Validator) {
     validator.errors.push(diagnostic.clone());
@@ -9,15 +11,19 @@ pub fn compile_error(diagnostic: Diagnostic, validator: &mut Validator) {
 
 pub fn diagnostic(
     line_info: &LineInfo,
-    error_type: ErrorType,
+    code: ErrorCode,
     message: String,
     note: &str,
 ) -> Diagnostic {
     Diagnostic {
         line_info: line_info.clone(),
-        error_type,
+        code,
+        severity: Severity::Error,
+        error_type: code.error_type(),
         message,
         note: note.to_string(),
+        labels: Vec::new(),
+        help: None,
     }
 }
 
@@ -25,15 +31,21 @@ pub fn no_return_error(
     line_info: &LineInfo,
     fn_name: &NameHash,
     class_name: &NameHash,
+    fn_def: &Function,
 ) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::NoReturn,
-        message: format!(
+    let error = diagnostic(
+        line_info,
+        ErrorCode::MissingReturn,
+        format!(
             "not all code paths return for function `{}` in class `{}`",
             fn_name, class_name
         ),
-        note: "expected to return a value".to_string(),
+        "expected to return a value",
+    );
+
+    match &fn_def.missing_return {
+        Some(path) => error.with_label(&path.line_info, &path.message),
+        None => error,
     }
 }
 
@@ -42,15 +54,15 @@ pub fn no_public_var_error(
     var_name: &NameHash,
     class_name: &NameHash,
 ) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::NoReturn,
-        message: format!(
+    diagnostic(
+        line_info,
+        ErrorCode::UndefinedPublicVariable,
+        format!(
             "public variable `{}` was not found in class `{}` ",
             var_name, class_name
         ),
-        note: "undefined public variable".to_string(),
-    }
+        "undefined public variable",
+    )
 }
 
 pub fn undefined_fn_in_class_error(
@@ -58,21 +70,21 @@ pub fn undefined_fn_in_class_error(
     class_name: &NameHash,
     fn_name: &NameHash,
 ) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::Uninitialized,
-        message: format!("undefined function `{}` in class `{}`", fn_name, class_name),
-        note: "undefined function".to_string(),
-    }
+    diagnostic(
+        line_info,
+        ErrorCode::UndefinedClassFunction,
+        format!("undefined function `{}` in class `{}`", fn_name, class_name),
+        "undefined function",
+    )
 }
 
 pub fn out_of_bounds_error(line_info: &LineInfo, index: i64, length: usize) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::OutOfBounds,
-        message: format!("index `{}` is out of bounds `{}`", index, length),
-        note: "tries to access invalid memory".to_string(),
-    }
+    diagnostic(
+        line_info,
+        ErrorCode::IndexOutOfBounds,
+        format!("index `{}` is out of bounds `{}`", index, length),
+        "tries to access invalid memory",
+    )
 }
 
 pub fn invalid_number_of_params_error(
@@ -80,33 +92,53 @@ pub fn invalid_number_of_params_error(
     provided_number: usize,
     expected: String,
 ) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::OutOfBounds,
-        message: format!(
+    diagnostic(
+        line_info,
+        ErrorCode::WrongArgumentCount,
+        format!(
             "provided number of parameters `{}` is not the same as requested `{}`",
             provided_number, expected
         ),
-        note: "incorrect number of params".to_string(),
-    }
+        "incorrect number of params",
+    )
 }
 
 pub fn invalid_type_call_error(
     line_info: &LineInfo,
     method: &str,
-    val: &Value,
+    val: &impl Display,
     supported: &str,
     note: &str,
 ) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::InvalidType,
-        message: format!(
+    diagnostic(
+        line_info,
+        ErrorCode::InvalidMethodTarget,
+        format!(
             "{} used on `{}`. Only {} are supported",
             method, val, supported
         ),
-        note: note.to_string(),
-    }
+        note,
+    )
+}
+
+/// Operation that fails for every type its operands can have, found before running.
+pub fn unsupported_operand_types_error(
+    line_info: &LineInfo,
+    left: &TypeSet,
+    op: &Operand,
+    right: &TypeSet,
+) -> Diagnostic {
+    diagnostic(
+        line_info,
+        ErrorCode::UnsupportedOperand,
+        format!(
+            "unsupported operand `{}` for types `{}` and `{}`",
+            op.error_fmt(),
+            left,
+            right
+        ),
+        "fails whenever this line runs",
+    )
 }
 
 pub fn unsupported_operand_error(
@@ -115,15 +147,15 @@ pub fn unsupported_operand_error(
     op: &Operand,
     right: &Value,
 ) -> Diagnostic {
-    Diagnostic {
-        line_info: line_info.clone(),
-        error_type: ErrorType::Unsupported,
-        message: format!(
+    diagnostic(
+        line_info,
+        ErrorCode::UnsupportedOperand,
+        format!(
             "unsupported operand `{}` for types `{}` and `{}`",
             op.error_fmt(),
             left.error_fmt(),
             right.error_fmt()
         ),
-        note: "results in undefined behavior".to_string(),
-    }
+        "results in undefined behavior",
+    )
 }
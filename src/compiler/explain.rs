@@ -0,0 +1,396 @@
+use crate::data::diagnostic::ErrorCode;
+use std::fmt::{Display, Formatter};
+
+/// Long form description of an error code, with a program that causes it and the same program fixed.
+pub struct Explanation {
+    pub code: ErrorCode,
+    pub title: &'static str,
+    pub description: &'static str,
+    pub wrong: &'static str,
+    pub fixed: &'static str,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}: {}\n", self.code.as_str(), self.title)?;
+        writeln!(f, "{}\n", self.description)?;
+        writeln!(f, "Wrong:\n{}\n", indent(self.wrong))?;
+        write!(f, "Fixed:\n{}", indent(self.fixed))
+    }
+}
+
+fn indent(code: &str) -> String {
+    code.lines()
+        .map(|line| format!("    {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Looks up the explanation of a code written like `P0301`.
+pub fn explain(code: &str) -> Option<Explanation> {
+    ErrorCode::parse(code).map(explanation)
+}
+
+pub fn explanation(code: ErrorCode) -> Explanation {
+    let (title, description, wrong, fixed) = match code {
+        ErrorCode::UnexpectedToken => (
+            "Unexpected text",
+            "The line does not follow the rules of the language. The error shows what was expected \
+             at that point, for example an expression after `=` or an operator between two values.",
+            "X = 5 +",
+            "X = 5 + 1",
+        ),
+        ErrorCode::UnclosedBlock => (
+            "Block is never closed",
+            "Every `if`, `loop`, `method` and `Class` starts a block that has to be closed with \
+             the matching `end if`, `end loop`, `end method` or `end Class`. The error points to \
+             the line that opened the block.",
+            "X = 3\nif X > 1 then\n    output X",
+            "X = 3\nif X > 1 then\n    output X\nend if",
+        ),
+        ErrorCode::UnmatchedEnd => (
+            "`end` without a block",
+            "An `end` line closes a block that was never opened, or that was already closed.",
+            "X = 3\noutput X\nend if",
+            "X = 3\nif X > 1 then\n    output X\nend if",
+        ),
+        ErrorCode::AssignWithDoubleEquals => (
+            "`==` used to assign a value",
+            "`==` checks whether two values are equal, it does not change a variable. \
+             Use a single `=` to store a value in a variable.",
+            "X == 5\noutput X",
+            "X = 5\noutput X",
+        ),
+        ErrorCode::MissingThen => (
+            "Missing `then`",
+            "The condition of an `if` or `else if` has to be followed by `then` on the same line.",
+            "X = 5\nif X > 1\n    output X\nend if",
+            "X = 5\nif X > 1 then\n    output X\nend if",
+        ),
+        ErrorCode::ImportNotFound => (
+            "Imported file not found",
+            "The file named in an `import` does not exist. Paths are relative to the file that \
+             contains the `import`.",
+            "import \"helpers.txt\" // the file is called helper.txt",
+            "import \"helper.txt\"",
+        ),
+        ErrorCode::ImportCycle => (
+            "Files import each other",
+            "A file imports another file that, directly or through other files, imports the \
+             first one again. Move the code both files need into a third file and import that one.",
+            "// a.txt\nimport \"b.txt\"\n\n// b.txt\nimport \"a.txt\"",
+            "// a.txt\nimport \"shared.txt\"\n\n// b.txt\nimport \"shared.txt\"",
+        ),
+        ErrorCode::ImportUnreadable => (
+            "Imported file cannot be read",
+            "The imported path exists, but it is not a text file that can be read, \
+             for example a folder.",
+            "import \"shapes\" // a folder",
+            "import \"shapes/circle.txt\"",
+        ),
+        ErrorCode::UndefinedVariable => (
+            "Variable not found",
+            "A variable is used before any value was assigned to it. Check the spelling: \
+             names are case sensitive, so `COUNT` and `Count` are different variables. A method only \
+             sees its parameters, its own variables and those of the main program.",
+            "COUNT = 1\noutput CONT",
+            "COUNT = 1\noutput COUNT",
+        ),
+        ErrorCode::UndefinedFunction => (
+            "Method not found",
+            "A method is called, but no method with that name is declared.",
+            "output square(3)",
+            "method square(X)\n    return X * X\nend method\n\noutput square(3)",
+        ),
+        ErrorCode::UndefinedClass => (
+            "Class not found",
+            "A class is used, but no class with that name is declared.",
+            "P = new Point(1, 2)",
+            "Class Point(X, Y)\n    public this.x = X\n    public this.y = Y\nend Class\n\nP = new Point(1, 2)",
+        ),
+        ErrorCode::UndefinedClassFunction => (
+            "Class function not found",
+            "A function is called on an object or a static class that does not declare it.",
+            "Q = new Queue()\nQ.enqeue(1)",
+            "Q = new Queue()\nQ.enqueue(1)",
+        ),
+        ErrorCode::UndefinedPublicVariable => (
+            "Public variable not found",
+            "Only variables declared with `public this.name = ...` can be read from outside \
+             of a class.",
+            "output Math.pi",
+            "output Math.PI",
+        ),
+        ErrorCode::DuplicateClass => (
+            "Class declared twice",
+            "Two classes have the same name. Rename one of them.",
+            "Class Point()\nend Class\n\nClass Point()\nend Class",
+            "Class Point()\nend Class\n\nClass Vector()\nend Class",
+        ),
+        ErrorCode::DuplicateFunction => (
+            "Method declared twice",
+            "Two methods in the same place have the same name. Rename one of them.",
+            "method area(W)\n    return W * W\nend method\n\nmethod area(W, H)\n    return W * H\nend method",
+            "method square(W)\n    return W * W\nend method\n\nmethod area(W, H)\n    return W * H\nend method",
+        ),
+        ErrorCode::UnassignedVariable => (
+            "Variable may be used before assignment",
+            "A variable is read on a path where it has not been given a value yet, for example when it is \
+             only assigned inside an `if`, or when a method reads a variable of the main program that may \
+             not be assigned where the method is called.",
+            "method report()\n    output SCORE\nend method\n\nANSWER = \"yes\"\nif ANSWER == \"yes\" then\n    SCORE = 1\n    report()\nend if\nreport()",
+            "method report(SCORE)\n    output SCORE\nend method\n\nSCORE = 0\nANSWER = \"yes\"\nif ANSWER == \"yes\" then\n    SCORE = 1\nend if\nreport(SCORE)",
+        ),
+        ErrorCode::WrongArgumentCount => (
+            "Wrong number of arguments",
+            "A method is called with more or fewer values than it has parameters.",
+            "method add(A, B)\n    return A + B\nend method\n\noutput add(1)",
+            "method add(A, B)\n    return A + B\nend method\n\noutput add(1, 2)",
+        ),
+        ErrorCode::MissingReturn => (
+            "Method does not return a value",
+            "The result of a method is used, but some way through the method reaches its end without a \
+             `return`, for example when no branch of an `if` matches. Either return a value on every \
+             path, or call the method on its own line.",
+            "method greet(NAME)\n    output \"Hello \" + NAME\nend method\n\nX = greet(\"Sam\")",
+            "method greet(NAME)\n    output \"Hello \" + NAME\nend method\n\ngreet(\"Sam\")",
+        ),
+        ErrorCode::StaticClassInstance => (
+            "Object of a static class",
+            "A static class cannot be created with `new`. Call its functions on the class name instead.",
+            "M = new Math()",
+            "output Math.abs(-2)",
+        ),
+        ErrorCode::StaticClassParameters => (
+            "Static class with parameters",
+            "A static class is never created with `new`, so it cannot take constructor parameters.",
+            "static Class Tools(X)\nend Class",
+            "static Class Tools()\nend Class",
+        ),
+        ErrorCode::InvalidAssignTarget => (
+            "Cannot assign to this",
+            "The left side of `=` has to be a variable or an array element.",
+            "X = 1\n5 = X",
+            "X = 1\nY = X",
+        ),
+        ErrorCode::UnsupportedOperand => (
+            "Operator cannot be used on these values",
+            "The operator only works on numbers, but one of the values is a string, an array or \
+             an object. Only `+` works on strings, where it joins them.",
+            "output \"apples\" * 2",
+            "output \"apples\" + 2",
+        ),
+        ErrorCode::InvalidMethodTarget => (
+            "Wrong kind of value",
+            "A built in operation is used on a value it does not support, for example `.length` \
+             on a number or an index on a number.",
+            "X = 5\noutput X.length",
+            "X = \"hello\"\noutput X.length",
+        ),
+        ErrorCode::InvalidUnaryOperand => (
+            "`-` or `NOT` on the wrong kind of value",
+            "`-` in front of a value and `NOT` only work on numbers and booleans.",
+            "X = \"five\"\noutput -X",
+            "X = 5\noutput -X",
+        ),
+        ErrorCode::NotANumber => (
+            "Value is not a number",
+            "A number is needed here, for example as a loop bound or an array index, \
+             but the value is something else.",
+            "X = \"ten\"\nloop I from 1 to X\n    output I\nend loop",
+            "X = 10\nloop I from 1 to X\n    output I\nend loop",
+        ),
+        ErrorCode::NotABoolean => (
+            "Value is not true or false",
+            "A condition has to be a boolean, a number or a string, but the value is an array \
+             or an object.",
+            "A = [1, 2]\nif A then\n    output 1\nend if",
+            "A = [1, 2]\nif A.length > 0 then\n    output 1\nend if",
+        ),
+        ErrorCode::CallOnNonInstance => (
+            "Function call on a value that is not an object",
+            "Functions can only be called on objects created with `new`.",
+            "X = 5\nX.add(1)",
+            "X = new Queue()\nX.enqueue(1)",
+        ),
+        ErrorCode::AccessOnNonInstance => (
+            "Variable read on a value that is not an object",
+            "`.name` can only read variables of objects created with `new`.",
+            "X = 5\noutput X.size",
+            "Class Box(SIZE)\n    public this.size = SIZE\nend Class\n\nX = new Box(5)\noutput X.size",
+        ),
+        ErrorCode::LoopVariableNotNumber => (
+            "Loop variable changed to a value that is not a number",
+            "The variable of a `loop I from ... to ...` is counted up after every pass, \
+             so it has to stay a number.",
+            "loop I from 1 to 3\n    I = \"done\"\nend loop",
+            "loop I from 1 to 3\n    output I\nend loop",
+        ),
+        ErrorCode::IndexOutOfBounds => (
+            "Index out of bounds",
+            "Arrays start at index 0, so the last element of an array of length N is at index N - 1.",
+            "A = [1, 2, 3]\noutput A[3]",
+            "A = [1, 2, 3]\noutput A[2]",
+        ),
+        ErrorCode::NegativeIndex => (
+            "Negative index",
+            "Array indexes start at 0 and cannot be negative.",
+            "A = [1, 2, 3]\nA[-1] = 5",
+            "A = [1, 2, 3]\nA[2] = 5",
+        ),
+        ErrorCode::AssertionFailed => (
+            "Assertion failed",
+            "`assert(value, expected)` stops the program when the two values are not equal.",
+            "assert(1 + 1, 3)",
+            "assert(1 + 1, 2)",
+        ),
+        ErrorCode::MissingInput => (
+            "No input left",
+            "The program asked for more `input` values than it was given, such as by the `.in` file \
+             of a program run with `test <dir>`. Add the missing values, one on each line.",
+            "NAME = input(\"Name\")\noutput \"Hello \" + NAME",
+            "NAME = \"Ada\"\noutput \"Hello \" + NAME",
+        ),
+        ErrorCode::UnusedVariable => (
+            "Unused variable",
+            "A variable or parameter is declared but never used afterwards. It is either left over \
+             from an earlier version of the program, or another name was used by mistake.",
+            "TOTAL = 0\nSUM = 5\noutput SUM",
+            "SUM = 5\noutput SUM",
+        ),
+        ErrorCode::UnusedMethod => (
+            "Unused method",
+            "A method is declared but never called, so its code never runs.",
+            "method double(N)\n    return N * 2\nend method\noutput 4",
+            "method double(N)\n    return N * 2\nend method\noutput double(2)",
+        ),
+        ErrorCode::UnusedClass => (
+            "Unused class",
+            "A class is declared but no object of it is ever created or used.",
+            "Class Box(SIZE)\n    public this.size = SIZE\nend Class\noutput 5",
+            "Class Box(SIZE)\n    public this.size = SIZE\nend Class\nB = new Box(5)\noutput B.size",
+        ),
+        ErrorCode::UnreadVariable => (
+            "Variable assigned but never read",
+            "A variable is given values that are never read, so the work done to compute them is lost.",
+            "NAME = \"Ann\"\nNAME = \"Bob\"\noutput \"Hello\"",
+            "NAME = \"Ann\"\noutput \"Hello \" + NAME",
+        ),
+        ErrorCode::LoopVariableWritten => (
+            "Loop variable changed inside the loop",
+            "The variable of a `loop I from ... to ...` is counted up by the loop itself. Changing it \
+             in the loop skips or repeats passes, which makes the loop hard to follow. Use a \
+             `loop while` when the loop needs its own way of counting.",
+            "loop I from 1 to 10\n    output I\n    I = I + 1\nend loop",
+            "I = 1\nloop while I <= 10\n    output I\n    I = I + 2\nend loop",
+        ),
+        ErrorCode::AssignmentAsEquality => (
+            "`=` used to compare values",
+            "In a condition, a single `=` compares two values like `==`. It reads like an \
+             assignment though, so `==` makes the intent clear.",
+            "X = 5\nif X = 5 then\n    output X\nend if",
+            "X = 5\nif X == 5 then\n    output X\nend if",
+        ),
+        ErrorCode::ChainedComparison => (
+            "Chained comparison",
+            "`1 < X < 5` does not check that X is between 1 and 5. The first comparison gives \
+             true or false, which is then compared with 5. Join two comparisons with `AND` instead.",
+            "X = 3\nif 1 < X < 5 then\n    output X\nend if",
+            "X = 3\nif 1 < X AND X < 5 then\n    output X\nend if",
+        ),
+        ErrorCode::FloatEquality => (
+            "Equality of numbers with a fractional part",
+            "Numbers with a fractional part are stored with rounding errors, so `0.1 + 0.2 == 0.3` \
+             is false. Check that the difference between the numbers is small instead.",
+            "X = 0.1 + 0.2\nif X == 0.3 then\n    output X\nend if",
+            "X = 0.1 + 0.2\nif X - 0.3 < 0.000001 AND 0.3 - X < 0.000001 then\n    output X\nend if",
+        ),
+        ErrorCode::UnreachableCode => (
+            "Unreachable code",
+            "A `return` leaves the method immediately, so the statements after it never run.",
+            "method half(N)\n    return N / 2\n    output N\nend method\noutput half(4)",
+            "method half(N)\n    output N\n    return N / 2\nend method\noutput half(4)",
+        ),
+        ErrorCode::VariableNameStyle => (
+            "Variable name not in UPPERCASE",
+            "IB pseudocode writes the names of variables and parameters in capitals, with \
+             underscores between words. Only reported when the naming style is checked.",
+            "totalScore = 5\noutput totalScore",
+            "TOTAL_SCORE = 5\noutput TOTAL_SCORE",
+        ),
+        ErrorCode::MethodNameStyle => (
+            "Method name not in camelCase",
+            "IB pseudocode writes the names of methods starting with a small letter, with a capital \
+             at the start of every other word. Only reported when the naming style is checked.",
+            "method Double(NUMBER)\n    return NUMBER * 2\nend method\noutput Double(2)",
+            "method double(NUMBER)\n    return NUMBER * 2\nend method\noutput double(2)",
+        ),
+        ErrorCode::ClassNameStyle => (
+            "Class name not in PascalCase",
+            "IB pseudocode writes the names of classes with a capital at the start of every word. \
+             Only reported when the naming style is checked.",
+            "Class bank_account(BALANCE)\n    public this.balance = BALANCE\nend Class\n\nACCOUNT = new bank_account(5)\noutput ACCOUNT.balance",
+            "Class BankAccount(BALANCE)\n    public this.balance = BALANCE\nend Class\n\nACCOUNT = new BankAccount(5)\noutput ACCOUNT.balance",
+        ),
+        ErrorCode::MeaninglessName => (
+            "Variable name is not meaningful",
+            "A single letter or a placeholder such as `TEMP` does not say what a variable holds, \
+             which makes the program harder to follow. Loop counters such as `I` are fine. Only \
+             reported when the naming style is checked.",
+            "X = 5\noutput X * 2",
+            "PRICE = 5\noutput PRICE * 2",
+        ),
+        ErrorCode::StepLimitExceeded => (
+            "Program ran for too long",
+            "The program ran more statements than the runner allows, usually because a loop never \
+             ends. Only reported when the program is run with execution limits, such as by a grader.",
+            "COUNT = 10\nTOTAL = 0\nloop while COUNT > 0\n    TOTAL = TOTAL + COUNT\nend loop\noutput TOTAL",
+            "COUNT = 10\nTOTAL = 0\nloop while COUNT > 0\n    TOTAL = TOTAL + COUNT\n    COUNT = COUNT - 1\nend loop\noutput TOTAL",
+        ),
+        ErrorCode::CallDepthExceeded => (
+            "Maximum recursion depth exceeded",
+            "Methods called each other more deeply than the runner allows, 1000 calls by default and 200 \
+             on the web, usually because a recursive method has no case where it stops calling itself. \
+             Statements and expressions nested inside the running calls are limited too, 10 for \
+             each call allowed. The error shows every place of the chain of calls still running.",
+            "method countDown(COUNT)\n    output COUNT\n    countDown(COUNT - 1)\nend method\n\ncountDown(3)",
+            "method countDown(COUNT)\n    if COUNT > 0 then\n        output COUNT\n        countDown(COUNT - 1)\n    end if\nend method\n\ncountDown(3)",
+        ),
+        ErrorCode::ArrayLimitExceeded => (
+            "Arrays are too large",
+            "The arrays of the program hold more elements together than the runner allows. Assigning \
+             to an index past the end of an array makes it longer, up to that index.",
+            "SCORES = [0]\nSCORES[1000000] = 5\noutput SCORES.length",
+            "SCORES = [0]\nSCORES[1] = 5\noutput SCORES.length",
+        ),
+        ErrorCode::OutputBytesExceeded => (
+            "Output is too long",
+            "The program wrote more text than the runner allows. Only reported when the program is \
+             run with execution limits.",
+            "TEXT = \"ab\"\nloop I from 1 to 20\n    TEXT = TEXT + TEXT\nend loop\noutput TEXT",
+            "TEXT = \"ab\"\nloop I from 1 to 5\n    TEXT = TEXT + TEXT\nend loop\noutput TEXT",
+        ),
+        ErrorCode::OutputLinesExceeded => (
+            "Too many lines of output",
+            "The program wrote more lines than the runner allows. Only reported when the program is \
+             run with execution limits.",
+            "loop I from 1 to 100000\n    output I\nend loop",
+            "loop I from 1 to 10\n    output I\nend loop",
+        ),
+        ErrorCode::ExecutionCancelled => (
+            "Program was stopped",
+            "The program was stopped from outside before it finished, for example by the stop button \
+             or the time limit of a grader.",
+            "loop while true\n    output \"still running\"\nend loop",
+            "loop I from 1 to 3\n    output \"still running\"\nend loop",
+        ),
+    };
+
+    Explanation {
+        code,
+        title,
+        description,
+        wrong,
+        fixed,
+    }
+}
@@ -0,0 +1,475 @@
+use crate::compiler::{DSLParser, Rule};
+use pest::Parser;
+use pest::iterators::Pair;
+
+const INDENT: &str = "    ";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatError {
+    /// The program does not parse, compiling it explains why
+    InvalidSyntax,
+    /// The formatted program would not parse to the same syntax tree, the original is left untouched
+    ChangedProgram,
+}
+
+/// Re-emits `code` with the standard layout: four spaces of indentation for every block, single spaces
+/// around operators, `end loop` for every loop and class functions with their braces on separate lines.
+/// Comments and single blank lines between statements are kept.
+pub fn format_code(code: &str) -> Result<String, FormatError> {
+    // Like imported files, the last statement may end without a line break
+    let code = match code.ends_with('\n') {
+        true => code.to_string(),
+        false => format!("{}\n", code),
+    };
+    let original = parse(&code).ok_or(FormatError::InvalidSyntax)?;
+
+    let mut formatter = Formatter::new(&code);
+    formatter.block(original.clone().into_inner());
+    formatter.comments_before(usize::MAX);
+    let formatted = formatter.finish();
+
+    // Only whitespace, comments and keywords that parse to the same tree may change
+    let reparsed = parse(&formatted).ok_or(FormatError::ChangedProgram)?;
+    if tokens(original) != tokens(reparsed) {
+        return Err(FormatError::ChangedProgram);
+    }
+    Ok(formatted)
+}
+
+/// Whether `code` is already laid out the way `format_code` would lay it out.
+pub fn is_formatted(code: &str) -> Result<bool, FormatError> {
+    Ok(format_code(code)? == code)
+}
+
+fn parse(code: &str) -> Option<Pair<'_, Rule>> {
+    DSLParser::parse(Rule::program, code).ok()?.next()
+}
+
+/// The leaves of the syntax tree, with the whitespace that cannot change their meaning removed.
+fn tokens(program: Pair<Rule>) -> Vec<(Rule, String)> {
+    program
+        .into_inner()
+        .flatten()
+        .filter(|pair| pair.clone().into_inner().next().is_none())
+        .map(|pair| {
+            let text = match pair.as_rule() {
+                Rule::string => pair.as_str().to_string(),
+                _ => pair.as_str().split_whitespace().collect(),
+            };
+            (pair.as_rule(), text)
+        })
+        .collect()
+}
+
+struct Comment {
+    /// 0-based line of the code
+    line: usize,
+    text: String,
+    /// Alone on its line, instead of following code
+    own_line: bool,
+}
+
+struct Formatter<'a> {
+    source_lines: Vec<&'a str>,
+    comments: Vec<Comment>,
+    next_comment: usize,
+    lines: Vec<String>,
+    indent: usize,
+    /// Last line of the code taken by the latest statement or comment
+    last_line: Option<usize>,
+    /// Nothing was written in the current block yet, so a blank line would only push its body down
+    block_start: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(code: &'a str) -> Self {
+        Self {
+            source_lines: code.split('\n').collect(),
+            comments: find_comments(code),
+            next_comment: 0,
+            lines: Vec::new(),
+            indent: 0,
+            last_line: None,
+            block_start: true,
+        }
+    }
+
+    fn finish(self) -> String {
+        let mut code = self.lines.join("\n");
+        if !code.is_empty() {
+            code.push('\n');
+        }
+        code
+    }
+
+    /// Writes the comments found on the lines before `line`, trailing comments stay on the line they follow.
+    fn comments_before(&mut self, line: usize) {
+        while let Some(comment) = self.comments.get(self.next_comment) {
+            if comment.line >= line {
+                break;
+            }
+            let (comment_line, text, own_line) =
+                (comment.line, comment.text.clone(), comment.own_line);
+            self.next_comment += 1;
+
+            match self.lines.last_mut() {
+                Some(last) if !own_line => {
+                    last.push(' ');
+                    last.push_str(&text);
+                }
+                _ => self.push_line(comment_line, comment_line, text),
+            }
+        }
+    }
+
+    /// Writes a statement or a comment that starts and ends on the given lines of the code.
+    fn push_line(&mut self, start_line: usize, end_line: usize, text: String) {
+        if !self.block_start && self.blank_line_between(start_line) {
+            self.lines.push(String::new());
+        }
+        self.lines
+            .push(format!("{}{}", INDENT.repeat(self.indent), text));
+        self.last_line = Some(end_line);
+        self.block_start = false;
+    }
+
+    fn blank_line_between(&self, line: usize) -> bool {
+        let Some(last_line) = self.last_line else {
+            return false;
+        };
+        (last_line + 1..line).any(|line| {
+            self.source_lines
+                .get(line)
+                .is_some_and(|text| text.trim().is_empty())
+        })
+    }
+
+    fn statement(&mut self, pair: Pair<Rule>, text: String) {
+        let (start_line, end_line) = lines_of(&pair);
+        self.comments_before(start_line);
+        self.push_line(start_line, end_line, text);
+    }
+
+    /// Writes the first line of a block and indents the statements after it.
+    fn open(&mut self, line: usize, text: String) {
+        self.comments_before(line);
+        self.push_line(line, line, text);
+        self.indent += 1;
+        self.block_start = true;
+    }
+
+    /// Writes the last line of a block, after the comments left inside it.
+    fn close(&mut self, line: usize, text: &str) {
+        self.comments_before(line);
+        self.indent -= 1;
+        self.lines
+            .push(format!("{}{}", INDENT.repeat(self.indent), text));
+        self.last_line = Some(line);
+        self.block_start = false;
+    }
+
+    fn block<'i>(&mut self, pairs: impl Iterator<Item = Pair<'i, Rule>>) {
+        for pair in pairs.filter(|pair| pair.as_rule() != Rule::EOI) {
+            self.stmt(pair);
+        }
+    }
+
+    fn stmt(&mut self, pair: Pair<Rule>) {
+        let (start_line, end_line) = lines_of(&pair);
+        let rule = pair.as_rule();
+        let mut inner = pair.clone().into_inner();
+
+        match rule {
+            Rule::if_stmt => {
+                let condition = expr(inner.next().unwrap());
+                self.open(start_line, format!("if {} then", condition));
+                for pair in inner {
+                    match pair.as_rule() {
+                        Rule::elif_clause => {
+                            let line = lines_of(&pair).0;
+                            let mut clause = pair.into_inner();
+                            let condition = expr(clause.next().unwrap());
+                            self.close(line, &format!("else if {} then", condition));
+                            self.indent += 1;
+                            self.block_start = true;
+                            self.block(clause);
+                        }
+                        Rule::else_clause => {
+                            self.close(lines_of(&pair).0, "else");
+                            self.indent += 1;
+                            self.block_start = true;
+                            self.block(pair.into_inner());
+                        }
+                        _ => self.stmt(pair),
+                    }
+                }
+                self.close(end_line, "end if");
+            }
+            Rule::while_loop_stmt => {
+                let condition = expr(inner.next().unwrap());
+                self.open(start_line, format!("loop while {}", condition));
+                self.block(inner);
+                self.close(end_line, "end loop");
+            }
+            Rule::for_loop_stmt => {
+                let counter = inner.next().unwrap().as_str();
+                let from = expr(inner.next().unwrap());
+                let to = expr(inner.next().unwrap());
+                self.open(
+                    start_line,
+                    format!("loop {} from {} to {}", counter, from, to),
+                );
+                self.block(inner);
+                self.close(end_line, "end loop");
+            }
+            Rule::loop_until_stmt => {
+                let condition = expr(inner.next().unwrap());
+                self.open(start_line, format!("loop until {}", condition));
+                self.block(inner);
+                self.close(end_line, "end loop");
+            }
+            Rule::method_decl => {
+                let name = inner.next().unwrap().as_str();
+                let params = params(inner.next().unwrap());
+                self.open(start_line, format!("method {}{}", name, params));
+                self.block(inner);
+                self.close(end_line, "end method");
+            }
+            Rule::class_decl => {
+                let mut keyword = "Class";
+                if inner.peek().unwrap().as_rule() == Rule::static_keyword {
+                    inner.next();
+                    keyword = "static Class";
+                }
+                let name = inner.next().unwrap().as_str();
+                let params = params(inner.next().unwrap());
+                self.open(start_line, format!("{} {}{}", keyword, name, params));
+                self.block(inner);
+                self.close(end_line, "end Class");
+            }
+            Rule::class_function => {
+                let name = class_ident(inner.next().unwrap());
+                let params = params(inner.next().unwrap());
+                self.comments_before(start_line);
+                self.push_line(
+                    start_line,
+                    start_line,
+                    format!("{} = function{}", name, params),
+                );
+                let brace_line = self.brace_line(start_line);
+                self.open(brace_line, "{".to_string());
+                self.block(inner);
+                self.close(self.closing_brace_line(end_line), "}");
+            }
+            _ => self.statement(pair, simple_stmt(rule, inner)),
+        }
+    }
+
+    /// The line of the `{` opening a class function, which may be written after its parameters.
+    fn brace_line(&self, start_line: usize) -> usize {
+        (start_line..self.source_lines.len())
+            .find(|&line| code_without_comment(self.source_lines[line]).contains('{'))
+            .unwrap_or(start_line)
+    }
+
+    /// The line of the `}` closing a class function, before the comments that follow it up to
+    /// `end_line`, which the grammar counts as part of the function.
+    fn closing_brace_line(&self, end_line: usize) -> usize {
+        (0..=end_line)
+            .rev()
+            .find(|&line| code_without_comment(self.source_lines[line]).contains('}'))
+            .unwrap_or(end_line)
+    }
+}
+
+fn simple_stmt(rule: Rule, mut inner: pest::iterators::Pairs<Rule>) -> String {
+    match rule {
+        Rule::import_stmt => format!("import {}", inner.next().unwrap().as_str()),
+        Rule::assign_stmt => {
+            let target = term(inner.next().unwrap());
+            let operation = inner.next().unwrap().as_str();
+            format!("{} {} {}", target, operation, expr(inner.next().unwrap()))
+        }
+        Rule::increment_stmt => format!("{}++", term(inner.next().unwrap())),
+        Rule::decrement_stmt => format!("{}--", term(inner.next().unwrap())),
+        Rule::method_return => format!("return {}", expr(inner.next().unwrap())),
+        Rule::expr_stmt => term(inner.next().unwrap()),
+        Rule::input_stmt => format!("input {}", name(inner.next().unwrap())),
+        Rule::output_stmt => format!("output {}", list(inner.map(expr))),
+        Rule::assert_stmt => {
+            let actual = expr(inner.next().unwrap());
+            format!("assert({}, {})", actual, expr(inner.next().unwrap()))
+        }
+        Rule::class_constructor_stmt => {
+            let mut public = "";
+            if inner.peek().unwrap().as_rule() == Rule::public_keyword {
+                inner.next();
+                public = "public ";
+            }
+            let name = class_ident(inner.next().unwrap());
+            format!("{}{} = {}", public, name, expr(inner.next().unwrap()))
+        }
+        _ => unreachable!("{:?} is not a statement", rule),
+    }
+}
+
+fn expr(pair: Pair<Rule>) -> String {
+    match pair.as_rule() {
+        Rule::expr => expr(pair.into_inner().next().unwrap()),
+        Rule::logical_or | Rule::logical_and | Rule::comparison | Rule::add_sub | Rule::mul_div => {
+            let mut text = String::new();
+            for (i, pair) in pair.into_inner().enumerate() {
+                if i > 0 {
+                    text.push(' ');
+                }
+                // Operands and operators alternate, operators are kept as they are written
+                if i % 2 == 0 {
+                    text.push_str(&expr(pair));
+                } else {
+                    text.push_str(pair.as_str());
+                }
+            }
+            text
+        }
+        Rule::pow => {
+            let mut inner = pair.into_inner();
+            let base = expr(inner.next().unwrap());
+            match inner.nth(1) {
+                Some(exponent) => format!("{} ^ {}", base, expr(exponent)),
+                None => base,
+            }
+        }
+        Rule::unary => {
+            let mut text = String::new();
+            for pair in pair.into_inner() {
+                match pair.as_rule() {
+                    Rule::term => text.push_str(&term(pair)),
+                    _ if pair.as_str() == "NOT" => text.push_str("NOT "),
+                    _ => text.push_str(pair.as_str()),
+                }
+            }
+            text
+        }
+        _ => term(pair),
+    }
+}
+
+fn term(pair: Pair<Rule>) -> String {
+    let mut inner = pair.into_inner();
+    let mut text = primary(inner.next().unwrap());
+
+    for postfix in inner {
+        let postfix = postfix.into_inner().next().unwrap();
+        match postfix.as_rule() {
+            Rule::class_call => {
+                let mut inner = postfix.into_inner();
+                let name = inner.next().unwrap().as_str();
+                text.push_str(&format!(".{}{}", name, args(inner.next().unwrap())));
+            }
+            Rule::class_var => {
+                text.push('.');
+                text.push_str(postfix.into_inner().next().unwrap().as_str());
+            }
+            _ => text.push_str(&format!("[{}]", expr(postfix.into_inner().next().unwrap()))),
+        }
+    }
+    text
+}
+
+fn primary(pair: Pair<Rule>) -> String {
+    let inner = pair.into_inner().next().unwrap();
+    match inner.as_rule() {
+        Rule::expr => format!("({})", expr(inner)),
+        Rule::array if inner.as_str().starts_with("new") => "new Array()".to_string(),
+        Rule::array => format!("[{}]", list(inner.into_inner().map(expr))),
+        Rule::class_new => {
+            let mut inner = inner.into_inner();
+            let name = inner.next().unwrap().as_str();
+            format!("new {}{}", name, args(inner.next().unwrap()))
+        }
+        Rule::method_call => {
+            let mut inner = inner.into_inner();
+            let name = name(inner.next().unwrap());
+            format!("{}{}", name, args(inner.next().unwrap()))
+        }
+        Rule::class_ident | Rule::ident => name(inner),
+        _ => inner.as_str().to_string(),
+    }
+}
+
+fn name(pair: Pair<Rule>) -> String {
+    match pair.as_rule() {
+        Rule::class_ident => class_ident(pair),
+        _ => pair.as_str().to_string(),
+    }
+}
+
+fn class_ident(pair: Pair<Rule>) -> String {
+    format!("this.{}", pair.into_inner().next().unwrap().as_str())
+}
+
+fn params(pair: Pair<Rule>) -> String {
+    format!(
+        "({})",
+        list(pair.into_inner().map(|ident| ident.as_str().to_string()))
+    )
+}
+
+fn args(pair: Pair<Rule>) -> String {
+    format!("({})", list(pair.into_inner().map(expr)))
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+    items.collect::<Vec<_>>().join(", ")
+}
+
+/// First and last 0-based lines of the code of `pair`, without the line breaks ending it.
+fn code_without_comment(line: &str) -> &str {
+    line.find("//").map_or(line, |comment| &line[..comment])
+}
+
+fn lines_of(pair: &Pair<Rule>) -> (usize, usize) {
+    let start_line = pair.as_span().start_pos().line_col().0 - 1;
+    let text = pair.as_str().trim_end();
+    (start_line, start_line + text.matches('\n').count())
+}
+
+/// Every `//` comment outside of strings, which the grammar skips.
+fn find_comments(code: &str) -> Vec<Comment> {
+    let mut comments = Vec::new();
+    let mut in_string = false;
+    let mut own_line = true;
+    let mut line = 0;
+    let mut chars = code.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\n' => {
+                line += 1;
+                own_line = true;
+                continue;
+            }
+            '\\' if in_string && chars.peek().is_some_and(|(_, c)| *c == '"') => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '/' if !in_string && chars.peek().is_some_and(|(_, c)| *c == '/') => {
+                let end = code[i..].find('\n').map_or(code.len(), |end| i + end);
+                comments.push(Comment {
+                    line,
+                    text: code[i..end].trim_end().to_string(),
+                    own_line,
+                });
+                while chars.peek().is_some_and(|(i, _)| *i < end) {
+                    chars.next();
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if !c.is_whitespace() {
+            own_line = false;
+        }
+    }
+    comments
+}
@@ -0,0 +1,174 @@
+use crate::ast::line_info_of;
+use crate::common::fix_quotes_plain;
+use crate::compiler::errors::diagnostic;
+use crate::compiler::{DSLParser, Rule};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo};
+use pest::Parser;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file pulled in by an `import` statement.
+pub struct ImportedFile {
+    pub path: PathBuf,
+    pub code: String,
+}
+
+/// An import problem, with `line_info` relative to the file that contains the `import`.
+pub struct ImportError {
+    pub file: Option<PathBuf>,
+    pub diagnostic: Diagnostic,
+}
+
+/// Follows every `import` reachable from the main program.
+/// Files are returned in dependency order and each file appears only once.
+pub struct ImportResolver {
+    pub files: Vec<ImportedFile>,
+    pub errors: Vec<ImportError>,
+    stack: Vec<PathBuf>,
+}
+
+impl ImportResolver {
+    pub fn resolve(code: &str, main_path: Option<&Path>) -> Self {
+        let mut resolver = Self {
+            files: Vec::new(),
+            errors: Vec::new(),
+            stack: Vec::new(),
+        };
+
+        let main_path =
+            main_path.map(|path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf()));
+        if let Some(path) = &main_path {
+            resolver.stack.push(path.clone());
+        }
+        resolver.visit(code, main_path.as_deref());
+        resolver
+    }
+
+    fn visit(&mut self, code: &str, file: Option<&Path>) {
+        let base_dir = file
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .unwrap_or_else(|| PathBuf::from("."));
+
+        for (line_info, import_path) in find_imports(code) {
+            let Ok(path) = base_dir.join(&import_path).canonicalize() else {
+                self.error(
+                    file,
+                    &line_info,
+                    ErrorCode::ImportNotFound,
+                    format!("cannot find imported file `{}`", import_path),
+                    "file does not exist",
+                );
+                continue;
+            };
+
+            if let Some(cycle_start) = self.stack.iter().position(|p| *p == path) {
+                let cycle: Vec<String> = self.stack[cycle_start..]
+                    .iter()
+                    .chain([&path])
+                    .map(|p| file_name(p))
+                    .collect();
+                self.error(
+                    file,
+                    &line_info,
+                    ErrorCode::ImportCycle,
+                    format!("import cycle detected: {}", cycle.join(" -> ")),
+                    "imports a file that imports this one",
+                );
+                continue;
+            }
+
+            if self.files.iter().any(|imported| imported.path == path) {
+                continue;
+            }
+
+            let Ok(imported_code) = fs::read_to_string(&path) else {
+                self.error(
+                    file,
+                    &line_info,
+                    ErrorCode::ImportUnreadable,
+                    format!("cannot read imported file `{}`", import_path),
+                    "file cannot be read",
+                );
+                continue;
+            };
+
+            self.stack.push(path.clone());
+            self.visit(&imported_code, Some(&path));
+            self.stack.pop();
+
+            self.files.push(ImportedFile {
+                path,
+                code: imported_code,
+            });
+        }
+    }
+
+    fn error(
+        &mut self,
+        file: Option<&Path>,
+        line_info: &LineInfo,
+        code: ErrorCode,
+        message: String,
+        note: &str,
+    ) {
+        self.errors.push(ImportError {
+            file: file.map(Path::to_path_buf),
+            diagnostic: diagnostic(line_info, code, message, note),
+        });
+    }
+}
+
+/// Collects the paths of all `import` statements in `code`. Files that fail to parse have no imports,
+/// their syntax errors are reported when the whole program is parsed.
+fn find_imports(code: &str) -> Vec<(LineInfo, String)> {
+    // Files are joined into the program with a line break after each, which the last statement needs
+    let code = format!("{}\n", code);
+    let Ok(pairs) = DSLParser::parse(Rule::program, &code) else {
+        return find_import_lines(&code);
+    };
+
+    pairs
+        .flatten()
+        .filter(|pair| pair.as_rule() == Rule::import_stmt)
+        .map(|pair| {
+            let path = pair.clone().into_inner().next().unwrap();
+            (line_info_of(&pair), fix_quotes_plain(path.as_str()))
+        })
+        .collect()
+}
+
+/// Finds the imports of a file with syntax errors line by line,
+/// so that the imported classes and methods are still known while the errors are reported.
+fn find_import_lines(code: &str) -> Vec<(LineInfo, String)> {
+    let mut imports = Vec::new();
+    for (i, line) in code.lines().enumerate() {
+        let statement = line.trim_start();
+        let Ok(mut pairs) = DSLParser::parse(Rule::import_stmt, statement) else {
+            continue;
+        };
+
+        let pair = pairs.next().unwrap();
+        let rest = statement[pair.as_str().len()..].trim();
+        if !rest.is_empty() && !rest.starts_with("//") {
+            continue;
+        }
+
+        let indent = (line.len() - statement.len()) as u16;
+        let mut line_info = line_info_of(&pair);
+        line_info.start_line = i as u32 + 1;
+        line_info.end_line = i as u32 + 1;
+        line_info.start_col += indent;
+        line_info.end_col += indent;
+
+        let path = pair.into_inner().next().unwrap();
+        imports.push((line_info, fix_quotes_plain(path.as_str())));
+    }
+    imports
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
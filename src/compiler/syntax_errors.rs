@@ -0,0 +1,533 @@
+use crate::compiler::Rule;
+use crate::compiler::errors::diagnostic;
+use crate::data::SourceMap;
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo};
+use crate::data::source_map::FileId;
+use pest::error::{Error, ErrorVariant, LineColLocation};
+
+/// Most syntax errors reported for one program, later ones are usually caused by the earlier ones
+pub const MAX_SYNTAX_ERRORS: usize = 20;
+
+/// A syntax error, together with the edit of the program that lets parsing continue after it.
+pub struct SyntaxError {
+    pub diagnostic: Diagnostic,
+    repair: Option<Repair>,
+}
+
+/// Edits of the program. Only `Append` adds a line, and it moves the files after it down in the source map.
+/// Lines are 0-based indexes into the whole program.
+enum Repair {
+    /// Adds a line after the last line of a file
+    Append(FileId, &'static str),
+    Replace(usize, String),
+    /// Empties every line from the first to the second, both included
+    Blank(usize, usize),
+}
+
+impl SyntaxError {
+    /// Explains `err` in words instead of grammar rules.
+    pub fn new(source_map: &SourceMap, program: &str, err: &Error<Rule>) -> Self {
+        let ((start_line, start_col), (end_line, end_col)) = match &err.line_col {
+            LineColLocation::Pos(pos) => (*pos, (pos.0, pos.1 + 1)),
+            LineColLocation::Span(start, end) => (*start, *end),
+        };
+
+        let (file_id, start_line) = source_map.locate(start_line as u32);
+        let (_, end_line) = source_map.locate(end_line as u32);
+        let line_info = LineInfo {
+            file_id,
+            start_line,
+            start_col: start_col as u16,
+            end_line,
+            end_col: end_col as u16,
+        };
+
+        // A block left open at the end of a file takes in the files after it,
+        // so the parser only stumbles over it in a later file
+        for earlier_file in 0..file_id {
+            let (_, lines) = file_lines(source_map, program, earlier_file);
+            if let Some((diagnostic, repair @ Repair::Append(..))) =
+                unclosed_block_error(&lines, earlier_file, u32::MAX)
+            {
+                return Self::recoverable(diagnostic, repair);
+            }
+        }
+
+        let (first_line, lines) = file_lines(source_map, program, file_id);
+        let program_line = |line: usize| first_line + line;
+
+        if let Some((diagnostic, repair)) = unclosed_block_error(&lines, file_id, start_line) {
+            let repair = match repair {
+                Repair::Replace(line, text) => Repair::Replace(program_line(line), text),
+                Repair::Blank(start, end) => Repair::Blank(program_line(start), program_line(end)),
+                append => append,
+            };
+            return Self::recoverable(diagnostic, repair);
+        }
+
+        let error_line = start_line as usize - 1;
+        let Some(line) = lines
+            .get(error_line)
+            .map(|line| strip_strings_and_comments(line))
+        else {
+            return Self {
+                diagnostic: expected_error(
+                    &line_info,
+                    err,
+                    "the end of the file".to_string(),
+                    None,
+                ),
+                repair: None,
+            };
+        };
+
+        let col = start_col.saturating_sub(1);
+        let before = line.get(..col).unwrap_or(&line);
+        let after = line.get(col..).unwrap_or("");
+
+        if after.starts_with('=') && before.trim_end().ends_with('=') {
+            let diagnostic = diagnostic(
+                &line_info,
+                ErrorCode::AssignWithDoubleEquals,
+                "`==` compares two values, it cannot assign one".to_string(),
+                "unexpected `=`",
+            )
+            .with_help(Some("use a single `=` to assign a value".to_string()));
+            return Self::recoverable(diagnostic, skip_statement(&lines, error_line, first_line));
+        }
+
+        let statement = line.trim_start();
+        if (starts_with_keyword(statement, "if") || statement.starts_with("else if "))
+            && !has_word(statement, "then")
+            && after.trim().is_empty()
+        {
+            let diagnostic = diagnostic(
+                &line_info,
+                ErrorCode::MissingThen,
+                "missing `then` after the condition".to_string(),
+                "expected `then`",
+            )
+            .with_help(Some("write `then` at the end of the line".to_string()));
+            return Self::recoverable(diagnostic, skip_statement(&lines, error_line, first_line));
+        }
+
+        let diagnostic = expected_error(
+            &line_info,
+            err,
+            describe_found(after),
+            unclosed_bracket(&line),
+        );
+        Self::recoverable(diagnostic, skip_statement(&lines, error_line, first_line))
+    }
+
+    fn recoverable(diagnostic: Diagnostic, repair: Repair) -> Self {
+        Self {
+            diagnostic,
+            repair: Some(repair),
+        }
+    }
+
+    /// Edits `program` so that parsing can continue after this error, keeping `source_map` in line with it.
+    /// Returns `false` when there is no way to recover from it.
+    pub fn repair(&self, program: &mut String, source_map: &mut SourceMap) -> bool {
+        let Some(repair) = &self.repair else {
+            return false;
+        };
+
+        let mut lines: Vec<&str> = program.split('\n').collect();
+        match repair {
+            Repair::Append(file_id, line) => {
+                let next_files = &mut source_map.files[*file_id as usize + 1..];
+                let Some(next_file) = next_files.first() else {
+                    program.push_str(line);
+                    program.push('\n');
+                    return true;
+                };
+
+                lines.insert(next_file.start_line as usize, line);
+                for file in next_files {
+                    file.start_line += 1;
+                }
+            }
+            Repair::Replace(line, text) => lines[*line] = text.as_str(),
+            Repair::Blank(start, end) => lines[*start..=*end].fill(""),
+        }
+        *program = lines.join("\n");
+        true
+    }
+}
+
+/// The first program line of `file_id` and the lines of the file as they are in `program`.
+/// The program is read instead of the original file, so that earlier repairs are taken into account.
+fn file_lines<'a>(
+    source_map: &SourceMap,
+    program: &'a str,
+    file_id: FileId,
+) -> (usize, Vec<&'a str>) {
+    let first_line = source_map.file(file_id).start_line as usize;
+    let line_count = source_map
+        .files
+        .get(file_id as usize + 1)
+        .map_or(usize::MAX, |next| next.start_line as usize - first_line);
+    let lines = program.lines().skip(first_line).take(line_count).collect();
+    (first_line, lines)
+}
+
+/// Removes the broken statement on `line`. Block openers are replaced by an opener of the same kind
+/// when possible, so that the rest of the block is still parsed.
+fn skip_statement(lines: &[&str], line: usize, first_line: usize) -> Repair {
+    let stripped = strip_strings_and_comments(lines[line]);
+    let statement = stripped.trim();
+
+    let replacement = match block_opener(statement) {
+        Some(("if", _)) => "if true then".to_string(),
+        Some(("loop", _)) => "loop while false".to_string(),
+        Some(_) => {
+            let last_line = matching_closer(lines, line).unwrap_or(line);
+            return Repair::Blank(first_line + line, first_line + last_line);
+        }
+        None if statement.starts_with("else if") => "else if true then".to_string(),
+        // Keeping the assigned variable avoids reporting it as missing further down
+        None => match assigned_variable(statement) {
+            Some(name) => format!("{} = 0", name),
+            None => return Repair::Blank(first_line + line, first_line + line),
+        },
+    };
+    Repair::Replace(first_line + line, replacement)
+}
+
+/// Returns `X` for statements starting like `X = ` or `X == `.
+fn assigned_variable(statement: &str) -> Option<&str> {
+    let end = statement
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(statement.len());
+    let (name, rest) = statement.split_at(end);
+
+    let starts_with_letter = name.starts_with(|c: char| c.is_ascii_alphabetic());
+    (starts_with_letter && rest.trim_start().starts_with('=')).then_some(name)
+}
+
+/// Finds the line closing the block opened on `line`.
+fn matching_closer(lines: &[&str], line: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (i, text) in lines.iter().enumerate().skip(line) {
+        let stripped = strip_strings_and_comments(text);
+        let statement = stripped.trim();
+
+        if block_closer(statement).is_some() {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        } else if block_opener(statement).is_some() {
+            depth += 1;
+        }
+    }
+    None
+}
+
+fn expected_error(
+    line_info: &LineInfo,
+    err: &Error<Rule>,
+    found: String,
+    help: Option<String>,
+) -> Diagnostic {
+    let ErrorVariant::ParsingError { positives, .. } = &err.variant else {
+        return diagnostic(
+            line_info,
+            ErrorCode::UnexpectedToken,
+            err.variant.message().to_string(),
+            "",
+        );
+    };
+
+    let mut expected: Vec<String> = Vec::new();
+    for phrase in positives.iter().map(describe_rule) {
+        if !expected.contains(&phrase) {
+            expected.push(phrase);
+        }
+    }
+
+    let message = match expected.split_last() {
+        None => format!("unexpected {}", found),
+        Some((last, [])) => format!("expected {}, found {}", last, found),
+        Some((last, rest)) => format!("expected {} or {}, found {}", rest.join(", "), last, found),
+    };
+    diagnostic(
+        line_info,
+        ErrorCode::UnexpectedToken,
+        message,
+        "unexpected here",
+    )
+    .with_help(help)
+}
+
+/// Describes a grammar rule the way it would be explained in class.
+fn describe_rule(rule: &Rule) -> String {
+    let phrase = match rule {
+        Rule::equal
+        | Rule::not_equal
+        | Rule::greater_equal
+        | Rule::less_equal
+        | Rule::greater
+        | Rule::less
+        | Rule::add
+        | Rule::subtract
+        | Rule::multiply
+        | Rule::divide
+        | Rule::int_divide
+        | Rule::power
+        | Rule::modulo
+        | Rule::and
+        | Rule::or
+        | Rule::postfix
+        | Rule::class_call
+        | Rule::class_var
+        | Rule::index => "an operator",
+        Rule::expr
+        | Rule::logical_or
+        | Rule::logical_and
+        | Rule::comparison
+        | Rule::add_sub
+        | Rule::mul_div
+        | Rule::pow
+        | Rule::unary
+        | Rule::not
+        | Rule::term
+        | Rule::primary
+        | Rule::method_call
+        | Rule::class_new
+        | Rule::array
+        | Rule::undefined => "an expression",
+        Rule::import_stmt
+        | Rule::class_decl
+        | Rule::assign_stmt
+        | Rule::increment_stmt
+        | Rule::decrement_stmt
+        | Rule::if_stmt
+        | Rule::while_loop_stmt
+        | Rule::for_loop_stmt
+        | Rule::loop_until_stmt
+        | Rule::method_decl
+        | Rule::method_return
+        | Rule::input_stmt
+        | Rule::output_stmt
+        | Rule::assert_stmt
+        | Rule::expr_stmt
+        | Rule::static_keyword => "a statement",
+        Rule::elif_clause => "`else if`",
+        Rule::else_clause => "`else`",
+        Rule::call_param_list => "`(`",
+        Rule::decl_param_list => "a parameter list like `(A, B)`",
+        Rule::ident => "a name",
+        Rule::class_ident => "`this.` followed by a name",
+        Rule::class_constructor_stmt | Rule::public_keyword => "a class variable",
+        Rule::class_function => "a class function",
+        Rule::string => "a string",
+        Rule::number | Rule::int => "a number",
+        Rule::bool => "`true` or `false`",
+        Rule::EOI => "the end of the file",
+        other => return format!("{:?}", other).replace('_', " "),
+    };
+    phrase.to_string()
+}
+
+fn describe_found(rest: &str) -> String {
+    let rest = rest.trim_start();
+    let Some(first) = rest.chars().next() else {
+        return "the end of the line".to_string();
+    };
+
+    let token: String = if first.is_alphanumeric() || first == '_' {
+        rest.chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '.')
+            .collect()
+    } else if first == '"' {
+        return "a string".to_string();
+    } else {
+        first.to_string()
+    };
+    format!("`{}`", token)
+}
+
+fn unclosed_bracket(line: &str) -> Option<String> {
+    ["(", "["]
+        .into_iter()
+        .zip([")", "]"])
+        .find(|(open, close)| line.matches(open).count() > line.matches(close).count())
+        .map(|(open, close)| format!("a `{}` on this line is never closed with `{}`", open, close))
+}
+
+struct OpenBlock {
+    keyword: &'static str,
+    closer: &'static str,
+    line_info: LineInfo,
+}
+
+/// Matches block openers (`if`, `loop`, `method`, `Class`, class functions) with their `end` lines.
+/// Only problems that start on or before `error_line` are reported, others are not the cause of the error.
+fn unclosed_block_error(
+    lines: &[&str],
+    file_id: FileId,
+    error_line: u32,
+) -> Option<(Diagnostic, Repair)> {
+    let mut open_blocks: Vec<OpenBlock> = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        let line_number = i as u32 + 1;
+        if line_number > error_line {
+            break;
+        }
+
+        let line = strip_strings_and_comments(line);
+        let statement = line.trim();
+        let indent = line.len() - line.trim_start().len();
+        let line_info = LineInfo {
+            file_id,
+            start_line: line_number,
+            start_col: indent as u16 + 1,
+            end_line: line_number,
+            end_col: (indent + statement.len()) as u16 + 1,
+        };
+
+        if let Some(closer) = block_closer(statement) {
+            let Some(position) = open_blocks.iter().rposition(|block| block.closer == closer)
+            else {
+                let diagnostic = diagnostic(
+                    &line_info,
+                    ErrorCode::UnmatchedEnd,
+                    format!("`{}` without a matching opening line", closer),
+                    "nothing to close here",
+                )
+                .with_help(open_blocks.last().map(|block| {
+                    format!(
+                        "the innermost open block must be closed with `{}`",
+                        block.closer
+                    )
+                }));
+                return Some((diagnostic, Repair::Blank(i, i)));
+            };
+
+            if position + 1 < open_blocks.len() {
+                // The block contents now belong to the enclosing block
+                let block = open_blocks.pop().unwrap();
+                let opener = block.line_info.start_line as usize - 1;
+                let diagnostic = unclosed_diagnostic(&block).with_label(
+                    &line_info,
+                    &format!("expected `{}` before this line", block.closer),
+                );
+                return Some((diagnostic, Repair::Blank(opener, opener)));
+            }
+            open_blocks.pop();
+        } else if let Some((keyword, closer)) = block_opener(statement) {
+            open_blocks.push(OpenBlock {
+                keyword,
+                closer,
+                line_info,
+            });
+        }
+    }
+
+    let block = open_blocks.pop()?;
+    if lines.len() as u32 >= error_line {
+        return None;
+    }
+    Some((
+        unclosed_diagnostic(&block),
+        Repair::Append(file_id, block.closer),
+    ))
+}
+
+fn unclosed_diagnostic(block: &OpenBlock) -> Diagnostic {
+    diagnostic(
+        &block.line_info,
+        ErrorCode::UnclosedBlock,
+        format!("this `{}` block is never closed", block.keyword),
+        &format!("`{}` opened here", block.keyword),
+    )
+    .with_help(Some(format!(
+        "add `{}` after the last line of the block",
+        block.closer
+    )))
+}
+
+fn block_opener(statement: &str) -> Option<(&'static str, &'static str)> {
+    [
+        ("if", "end if"),
+        ("loop", "end loop"),
+        ("method", "end method"),
+        ("Class", "end Class"),
+        ("static", "end Class"),
+    ]
+    .into_iter()
+    .find(|(keyword, _)| starts_with_keyword(statement, keyword))
+    .map(|(keyword, closer)| match keyword {
+        "static" => ("Class", closer),
+        _ => (keyword, closer),
+    })
+    .or_else(|| {
+        statement
+            .contains("= function")
+            .then_some(("function", "}"))
+    })
+}
+
+fn block_closer(statement: &str) -> Option<&'static str> {
+    if statement.starts_with('}') {
+        return Some("}");
+    }
+
+    let mut words = statement.split_whitespace();
+    if words.next()? != "end" {
+        return None;
+    }
+    match words.next()? {
+        "if" => Some("end if"),
+        // `end while` is accepted for `loop while`
+        "loop" | "while" => Some("end loop"),
+        "method" => Some("end method"),
+        "Class" => Some("end Class"),
+        _ => None,
+    }
+}
+
+fn starts_with_keyword(statement: &str, keyword: &str) -> bool {
+    statement
+        .strip_prefix(keyword)
+        .is_some_and(|rest| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_'))
+}
+
+fn has_word(statement: &str, word: &str) -> bool {
+    statement.split_whitespace().any(|w| w == word)
+}
+
+/// Blanks out string contents and removes comments, keeping the columns of the remaining code.
+fn strip_strings_and_comments(line: &str) -> String {
+    let mut stripped = String::new();
+    let mut in_string = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            if c == '\\' && chars.peek() == Some(&'"') {
+                chars.next();
+                stripped.push_str("  ");
+                continue;
+            }
+            if c == '"' {
+                in_string = false;
+                stripped.push(c);
+            } else {
+                stripped.push(' ');
+            }
+        } else if c == '"' {
+            in_string = true;
+            stripped.push(c);
+        } else if c == '/' && chars.peek() == Some(&'/') {
+            break;
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
@@ -1,9 +1,17 @@
 pub mod ast_nodes;
 pub mod diagnostic;
+pub mod lint;
 pub mod name_hash;
+pub mod scoping;
+pub mod semantic_index;
+pub mod source_map;
+pub mod style;
+pub mod types;
 pub mod validator;
 pub mod value;
 
 pub use name_hash::NameHash;
+pub use semantic_index::SemanticIndex;
+pub use source_map::SourceMap;
 pub use validator::Validator;
 pub use value::Value;
@@ -1,5 +1,5 @@
 use crate::ast::AST;
-use crate::data::diagnostic::{Diagnostic, LineInfo};
+use crate::data::diagnostic::{Diagnostic, Label, LineInfo};
 use crate::data::{NameHash, Value};
 use crate::env::Env;
 use std::collections::{HashMap, HashSet};
@@ -20,6 +20,17 @@ impl ExprNode {
     pub fn eval_as_bool(&self, ast: &AST, env: &mut Env) -> Result<bool, Diagnostic> {
         ast.eval_expr(self, env)?.as_bool(&self.line_info)
     }
+
+    /// Where errors about the returned value point to, when this is a call of a user defined method.
+    pub fn call_line(&self) -> Option<&LineInfo> {
+        match &self.expr {
+            Expr::LocalFunctionCall(..) => Some(&self.line_info),
+            Expr::StaticFunctionCall(fn_line, ..) | Expr::ClassFunctionCall { fn_line, .. } => {
+                Some(fn_line)
+            }
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -34,9 +45,11 @@ pub enum Stmt {
         else_branch: Option<Vec<StmtNode>>,
     },
     While(ExprNode, Vec<StmtNode>),
-    For(NameHash, ExprNode, ExprNode, Vec<StmtNode>),
+    /// Location of the loop counter, its name, start, end and body
+    For(LineInfo, NameHash, ExprNode, ExprNode, Vec<StmtNode>),
     Until(ExprNode, Vec<StmtNode>),
-    Input(NameHash),
+    Import(String),
+    Input(LineInfo, NameHash),
     Output(Vec<ExprNode>),
     Assert(ExprNode, ExprNode),
     FunctionDeclaration(NameHash),
@@ -52,7 +65,8 @@ pub enum Expr {
     Data(Value),
     Index(Box<ExprNode>, Box<ExprNode>),
     ArrayNew(Vec<ExprNode>),
-    ClassNew(NameHash, Vec<ExprNode>),
+    /// Location of the class name, the class name and the constructor parameters
+    ClassNew(LineInfo, NameHash, Vec<ExprNode>),
     Unary(UnaryOp, Box<ExprNode>),
     BinOp(Box<ExprNode>, Operand, Box<ExprNode>),
     NativeFunctionCall(NativeMethod, Option<Box<ExprNode>>, LineInfo, Vec<ExprNode>),
@@ -141,13 +155,25 @@ pub enum AssignOperator {
 
 #[derive(Debug)]
 pub struct Function {
+    /// Location of the method name
+    pub line_info: LineInfo,
     pub args: Vec<NameHash>,
+    /// Location of every argument, in the same order as `args`
+    pub arg_lines: Vec<LineInfo>,
     pub body: Vec<StmtNode>,
-    pub returns: bool,
+    /// A way through the body that ends without a `return`, nothing when every way returns
+    pub missing_return: Option<Label>,
+}
+
+impl Function {
+    pub fn returns(&self) -> bool {
+        self.missing_return.is_none()
+    }
 }
 
 #[derive(Debug)]
 pub struct Class {
+    /// Location of the class name
     pub line_info: LineInfo,
     pub functions: HashMap<NameHash, Function>,
     pub public_vars: HashSet<NameHash>,
@@ -159,5 +185,9 @@ pub struct Class {
 pub struct Constructor {
     pub line_info: LineInfo,
     pub constructors: Vec<(NameHash, ExprNode)>,
+    /// Location of the variable name of every constructor statement, in the same order as `constructors`
+    pub var_lines: Vec<LineInfo>,
     pub args: Vec<NameHash>,
+    /// Location of every argument, in the same order as `args`
+    pub arg_lines: Vec<LineInfo>,
 }
@@ -1,11 +1,55 @@
+use crate::data::source_map::FileId;
 use std::fmt::{Debug, Formatter};
 
 #[derive(Debug, Clone)]
 pub struct Diagnostic {
+    pub code: ErrorCode,
+    pub severity: Severity,
     pub error_type: ErrorType,
     pub line_info: LineInfo,
     pub message: String,
     pub note: String,
+    /// Secondary locations related to the error, shown after the main one
+    pub labels: Vec<Label>,
+    /// Hint on how to fix the error, shown at the end
+    pub help: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn with_label(mut self, line_info: &LineInfo, message: &str) -> Self {
+        self.labels.push(Label {
+            line_info: line_info.clone(),
+            message: message.to_string(),
+        });
+        self
+    }
+
+    pub fn with_help(mut self, help: Option<String>) -> Self {
+        self.help = help;
+        self
+    }
+}
+
+/// Errors stop the program from compiling, warnings only point out code that is likely a mistake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub line_info: LineInfo,
+    pub message: String,
 }
 
 #[derive(Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -17,15 +61,276 @@ pub enum ErrorType {
     Unsupported,
     DuplicateName,
     AssertionFailed,
+    MissingInput,
+    InvalidImport,
+    Syntax,
+    InvalidArguments,
+    Lint,
+    Style,
+    LimitExceeded,
+    Cancelled,
 }
 
-#[derive(Clone, Default)]
+/// Stable identifier of every distinct diagnostic, printed with the error and looked up by `--explain`.
+/// Codes are grouped by hundreds: syntax, imports, names, calls and classes, types, indexing, lints, naming style, then execution limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    UnexpectedToken,
+    UnclosedBlock,
+    UnmatchedEnd,
+    AssignWithDoubleEquals,
+    MissingThen,
+
+    ImportNotFound,
+    ImportCycle,
+    ImportUnreadable,
+
+    UndefinedVariable,
+    UndefinedFunction,
+    UndefinedClass,
+    UndefinedClassFunction,
+    UndefinedPublicVariable,
+    DuplicateClass,
+    DuplicateFunction,
+    UnassignedVariable,
+
+    WrongArgumentCount,
+    MissingReturn,
+    StaticClassInstance,
+    StaticClassParameters,
+    InvalidAssignTarget,
+
+    UnsupportedOperand,
+    InvalidMethodTarget,
+    InvalidUnaryOperand,
+    NotANumber,
+    NotABoolean,
+    CallOnNonInstance,
+    AccessOnNonInstance,
+    LoopVariableNotNumber,
+
+    IndexOutOfBounds,
+    NegativeIndex,
+    AssertionFailed,
+    MissingInput,
+
+    UnusedVariable,
+    UnusedMethod,
+    UnusedClass,
+    UnreadVariable,
+    LoopVariableWritten,
+    AssignmentAsEquality,
+    ChainedComparison,
+    FloatEquality,
+    UnreachableCode,
+
+    VariableNameStyle,
+    MethodNameStyle,
+    ClassNameStyle,
+    MeaninglessName,
+
+    StepLimitExceeded,
+    CallDepthExceeded,
+    ArrayLimitExceeded,
+    OutputBytesExceeded,
+    OutputLinesExceeded,
+    ExecutionCancelled,
+}
+
+impl ErrorCode {
+    pub const ALL: &'static [ErrorCode] = &[
+        ErrorCode::UnexpectedToken,
+        ErrorCode::UnclosedBlock,
+        ErrorCode::UnmatchedEnd,
+        ErrorCode::AssignWithDoubleEquals,
+        ErrorCode::MissingThen,
+        ErrorCode::ImportNotFound,
+        ErrorCode::ImportCycle,
+        ErrorCode::ImportUnreadable,
+        ErrorCode::UndefinedVariable,
+        ErrorCode::UndefinedFunction,
+        ErrorCode::UndefinedClass,
+        ErrorCode::UndefinedClassFunction,
+        ErrorCode::UndefinedPublicVariable,
+        ErrorCode::DuplicateClass,
+        ErrorCode::DuplicateFunction,
+        ErrorCode::UnassignedVariable,
+        ErrorCode::WrongArgumentCount,
+        ErrorCode::MissingReturn,
+        ErrorCode::StaticClassInstance,
+        ErrorCode::StaticClassParameters,
+        ErrorCode::InvalidAssignTarget,
+        ErrorCode::UnsupportedOperand,
+        ErrorCode::InvalidMethodTarget,
+        ErrorCode::InvalidUnaryOperand,
+        ErrorCode::NotANumber,
+        ErrorCode::NotABoolean,
+        ErrorCode::CallOnNonInstance,
+        ErrorCode::AccessOnNonInstance,
+        ErrorCode::LoopVariableNotNumber,
+        ErrorCode::IndexOutOfBounds,
+        ErrorCode::NegativeIndex,
+        ErrorCode::AssertionFailed,
+        ErrorCode::MissingInput,
+        ErrorCode::UnusedVariable,
+        ErrorCode::UnusedMethod,
+        ErrorCode::UnusedClass,
+        ErrorCode::UnreadVariable,
+        ErrorCode::LoopVariableWritten,
+        ErrorCode::AssignmentAsEquality,
+        ErrorCode::ChainedComparison,
+        ErrorCode::FloatEquality,
+        ErrorCode::UnreachableCode,
+        ErrorCode::VariableNameStyle,
+        ErrorCode::MethodNameStyle,
+        ErrorCode::ClassNameStyle,
+        ErrorCode::MeaninglessName,
+        ErrorCode::StepLimitExceeded,
+        ErrorCode::CallDepthExceeded,
+        ErrorCode::ArrayLimitExceeded,
+        ErrorCode::OutputBytesExceeded,
+        ErrorCode::OutputLinesExceeded,
+        ErrorCode::ExecutionCancelled,
+    ];
+
+    /// The code shown to users. Codes are never reused once published.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::UnexpectedToken => "P0101",
+            ErrorCode::UnclosedBlock => "P0102",
+            ErrorCode::UnmatchedEnd => "P0103",
+            ErrorCode::AssignWithDoubleEquals => "P0104",
+            ErrorCode::MissingThen => "P0105",
+            ErrorCode::ImportNotFound => "P0201",
+            ErrorCode::ImportCycle => "P0202",
+            ErrorCode::ImportUnreadable => "P0203",
+            ErrorCode::UndefinedVariable => "P0301",
+            ErrorCode::UndefinedFunction => "P0302",
+            ErrorCode::UndefinedClass => "P0303",
+            ErrorCode::UndefinedClassFunction => "P0304",
+            ErrorCode::UndefinedPublicVariable => "P0305",
+            ErrorCode::DuplicateClass => "P0306",
+            ErrorCode::DuplicateFunction => "P0307",
+            ErrorCode::UnassignedVariable => "P0308",
+            ErrorCode::WrongArgumentCount => "P0401",
+            ErrorCode::MissingReturn => "P0402",
+            ErrorCode::StaticClassInstance => "P0403",
+            ErrorCode::StaticClassParameters => "P0404",
+            ErrorCode::InvalidAssignTarget => "P0405",
+            ErrorCode::UnsupportedOperand => "P0501",
+            ErrorCode::InvalidMethodTarget => "P0502",
+            ErrorCode::InvalidUnaryOperand => "P0503",
+            ErrorCode::NotANumber => "P0504",
+            ErrorCode::NotABoolean => "P0505",
+            ErrorCode::CallOnNonInstance => "P0506",
+            ErrorCode::AccessOnNonInstance => "P0507",
+            ErrorCode::LoopVariableNotNumber => "P0508",
+            ErrorCode::IndexOutOfBounds => "P0601",
+            ErrorCode::NegativeIndex => "P0602",
+            ErrorCode::AssertionFailed => "P0603",
+            ErrorCode::MissingInput => "P0604",
+            ErrorCode::UnusedVariable => "P0701",
+            ErrorCode::UnusedMethod => "P0702",
+            ErrorCode::UnusedClass => "P0703",
+            ErrorCode::UnreadVariable => "P0704",
+            ErrorCode::LoopVariableWritten => "P0705",
+            ErrorCode::AssignmentAsEquality => "P0706",
+            ErrorCode::ChainedComparison => "P0707",
+            ErrorCode::FloatEquality => "P0708",
+            ErrorCode::UnreachableCode => "P0709",
+            ErrorCode::VariableNameStyle => "P0801",
+            ErrorCode::MethodNameStyle => "P0802",
+            ErrorCode::ClassNameStyle => "P0803",
+            ErrorCode::MeaninglessName => "P0804",
+            ErrorCode::StepLimitExceeded => "P0901",
+            ErrorCode::CallDepthExceeded => "P0902",
+            ErrorCode::ArrayLimitExceeded => "P0903",
+            ErrorCode::OutputBytesExceeded => "P0904",
+            ErrorCode::OutputLinesExceeded => "P0905",
+            ErrorCode::ExecutionCancelled => "P0906",
+        }
+    }
+
+    pub fn error_type(&self) -> ErrorType {
+        match self {
+            ErrorCode::UnexpectedToken
+            | ErrorCode::UnclosedBlock
+            | ErrorCode::UnmatchedEnd
+            | ErrorCode::AssignWithDoubleEquals
+            | ErrorCode::MissingThen => ErrorType::Syntax,
+            ErrorCode::ImportNotFound | ErrorCode::ImportCycle | ErrorCode::ImportUnreadable => {
+                ErrorType::InvalidImport
+            }
+            ErrorCode::UndefinedVariable
+            | ErrorCode::UndefinedFunction
+            | ErrorCode::UndefinedClass
+            | ErrorCode::UndefinedClassFunction
+            | ErrorCode::UndefinedPublicVariable
+            | ErrorCode::UnassignedVariable => ErrorType::Uninitialized,
+            ErrorCode::DuplicateClass | ErrorCode::DuplicateFunction => ErrorType::DuplicateName,
+            ErrorCode::WrongArgumentCount => ErrorType::InvalidArguments,
+            ErrorCode::MissingReturn => ErrorType::NoReturn,
+            ErrorCode::StaticClassInstance
+            | ErrorCode::StaticClassParameters
+            | ErrorCode::InvalidAssignTarget
+            | ErrorCode::UnsupportedOperand => ErrorType::Unsupported,
+            ErrorCode::InvalidMethodTarget
+            | ErrorCode::InvalidUnaryOperand
+            | ErrorCode::NotANumber
+            | ErrorCode::NotABoolean
+            | ErrorCode::CallOnNonInstance
+            | ErrorCode::AccessOnNonInstance
+            | ErrorCode::LoopVariableNotNumber => ErrorType::InvalidType,
+            ErrorCode::IndexOutOfBounds | ErrorCode::NegativeIndex => ErrorType::OutOfBounds,
+            ErrorCode::AssertionFailed => ErrorType::AssertionFailed,
+            ErrorCode::MissingInput => ErrorType::MissingInput,
+            ErrorCode::UnusedVariable
+            | ErrorCode::UnusedMethod
+            | ErrorCode::UnusedClass
+            | ErrorCode::UnreadVariable
+            | ErrorCode::LoopVariableWritten
+            | ErrorCode::AssignmentAsEquality
+            | ErrorCode::ChainedComparison
+            | ErrorCode::FloatEquality
+            | ErrorCode::UnreachableCode => ErrorType::Lint,
+            ErrorCode::VariableNameStyle
+            | ErrorCode::MethodNameStyle
+            | ErrorCode::ClassNameStyle
+            | ErrorCode::MeaninglessName => ErrorType::Style,
+            ErrorCode::StepLimitExceeded
+            | ErrorCode::CallDepthExceeded
+            | ErrorCode::ArrayLimitExceeded
+            | ErrorCode::OutputBytesExceeded
+            | ErrorCode::OutputLinesExceeded => ErrorType::LimitExceeded,
+            ErrorCode::ExecutionCancelled => ErrorType::Cancelled,
+        }
+    }
+
+    /// Finds the code written as `P0101`, ignoring case.
+    pub fn parse(code: &str) -> Option<ErrorCode> {
+        ErrorCode::ALL
+            .iter()
+            .find(|error_code| error_code.as_str().eq_ignore_ascii_case(code.trim()))
+            .copied()
+    }
+}
+
+#[derive(Clone, Default, PartialEq)]
 pub struct LineInfo {
+    pub file_id: FileId,
     pub start_line: u32,
     pub start_col: u16,
+    pub end_line: u32,
+    /// Column after the last character, on `end_line`
     pub end_col: u16,
 }
 
+impl LineInfo {
+    pub fn is_multi_line(&self) -> bool {
+        self.end_line > self.start_line
+    }
+}
+
 impl Debug for ErrorType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let raw = match self {
@@ -36,6 +341,14 @@ impl Debug for ErrorType {
             ErrorType::Unsupported => "Unsupported",
             ErrorType::DuplicateName => "Duplicate Name",
             ErrorType::AssertionFailed => "Assertion Failed",
+            ErrorType::MissingInput => "Missing Input",
+            ErrorType::InvalidImport => "Invalid Import",
+            ErrorType::Syntax => "Syntax",
+            ErrorType::InvalidArguments => "Invalid Arguments",
+            ErrorType::Lint => "Lint",
+            ErrorType::Style => "Style",
+            ErrorType::LimitExceeded => "Limit Exceeded",
+            ErrorType::Cancelled => "Cancelled",
         };
         write!(f, "{}", raw)
     }
@@ -43,6 +356,10 @@ impl Debug for ErrorType {
 
 impl Debug for LineInfo {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "start line: {}", self.start_line)
+        write!(
+            f,
+            "file: {}, lines: {}-{}",
+            self.file_id, self.start_line, self.end_line
+        )
     }
 }
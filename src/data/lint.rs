@@ -0,0 +1,115 @@
+use crate::data::diagnostic::{ErrorCode, Severity};
+use std::collections::HashMap;
+
+/// Checks for code that compiles but is likely a mistake, reported as warnings by `AST::lint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Lint {
+    /// Variables and parameters that are never used after their declaration
+    UnusedVariable,
+    UnusedMethod,
+    UnusedClass,
+    /// Variables that are written but never read
+    UnreadVariable,
+    /// The counter of a `loop I from ... to ...` written inside the loop
+    LoopVariableWritten,
+    /// `=` used to compare values in a condition, where `==` is clearer
+    AssignmentAsEquality,
+    /// Comparisons such as `1 < X < 5`, which compare a boolean with a number
+    ChainedComparison,
+    /// Equality of numbers with a fractional part, which rounding errors make unreliable
+    FloatEquality,
+    /// Statements after a `return`, which are never run
+    UnreachableCode,
+}
+
+impl Lint {
+    pub const ALL: &'static [Lint] = &[
+        Lint::UnusedVariable,
+        Lint::UnusedMethod,
+        Lint::UnusedClass,
+        Lint::UnreadVariable,
+        Lint::LoopVariableWritten,
+        Lint::AssignmentAsEquality,
+        Lint::ChainedComparison,
+        Lint::FloatEquality,
+        Lint::UnreachableCode,
+    ];
+
+    /// The name used to allow or deny the lint on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused_variable",
+            Lint::UnusedMethod => "unused_method",
+            Lint::UnusedClass => "unused_class",
+            Lint::UnreadVariable => "unread_variable",
+            Lint::LoopVariableWritten => "loop_variable_written",
+            Lint::AssignmentAsEquality => "assignment_as_equality",
+            Lint::ChainedComparison => "chained_comparison",
+            Lint::FloatEquality => "float_equality",
+            Lint::UnreachableCode => "unreachable_code",
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Lint::UnusedVariable => ErrorCode::UnusedVariable,
+            Lint::UnusedMethod => ErrorCode::UnusedMethod,
+            Lint::UnusedClass => ErrorCode::UnusedClass,
+            Lint::UnreadVariable => ErrorCode::UnreadVariable,
+            Lint::LoopVariableWritten => ErrorCode::LoopVariableWritten,
+            Lint::AssignmentAsEquality => ErrorCode::AssignmentAsEquality,
+            Lint::ChainedComparison => ErrorCode::ChainedComparison,
+            Lint::FloatEquality => ErrorCode::FloatEquality,
+            Lint::UnreachableCode => ErrorCode::UnreachableCode,
+        }
+    }
+
+    /// Finds the lint written as `unused_variable`, or by its code such as `P0701`.
+    pub fn parse(name: &str) -> Option<Lint> {
+        let name = name.trim();
+        Lint::ALL.iter().copied().find(|lint| {
+            lint.name() == name.replace('-', "_") || lint.code().as_str().eq_ignore_ascii_case(name)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintLevel {
+    /// Not reported
+    Allow,
+    /// Reported as a warning
+    Warn,
+    /// Reported as an error, which stops the program from running
+    Deny,
+}
+
+/// Level of every lint, all of them are warnings unless they are changed.
+#[derive(Debug, Clone, Default)]
+pub struct LintLevels {
+    levels: HashMap<Lint, LintLevel>,
+}
+
+impl LintLevels {
+    pub fn set(&mut self, lint: Lint, level: LintLevel) {
+        self.levels.insert(lint, level);
+    }
+
+    pub fn set_all(&mut self, level: LintLevel) {
+        for lint in Lint::ALL {
+            self.set(*lint, level);
+        }
+    }
+
+    pub fn level(&self, lint: Lint) -> LintLevel {
+        self.levels.get(&lint).copied().unwrap_or(LintLevel::Warn)
+    }
+
+    /// The severity `lint` is reported with, nothing when it is allowed.
+    pub fn severity(&self, lint: Lint) -> Option<Severity> {
+        match self.level(lint) {
+            LintLevel::Allow => None,
+            LintLevel::Warn => Some(Severity::Warning),
+            LintLevel::Deny => Some(Severity::Error),
+        }
+    }
+}
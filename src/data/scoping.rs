@@ -0,0 +1,10 @@
+/// Rules for which variables a statement can see, shared by the validator and the evaluator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scoping {
+    /// Methods run in the variables of their caller, as in older versions of the compiler,
+    /// instead of a call frame that only shares the variables of the main program
+    pub dynamic_calls: bool,
+    /// Variables first assigned in the body of an `if` or a loop are gone after the body,
+    /// instead of living until the end of the method or the main program
+    pub block_scoped: bool,
+}
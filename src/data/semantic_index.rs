@@ -0,0 +1,175 @@
+use crate::data::NameHash;
+use crate::data::diagnostic::LineInfo;
+use crate::data::source_map::FileId;
+
+pub type ScopeId = usize;
+pub type SymbolId = usize;
+
+/// Every declaration of a program and every place it is used, resolved with the scoping rules of the evaluator.
+/// Built once from an AST with `AST::semantic_index`, so that tools do not have to run the program
+/// to know what a name refers to.
+#[derive(Debug, Default)]
+pub struct SemanticIndex {
+    /// The program scope is always the first one, children are added after their parent
+    pub scopes: Vec<Scope>,
+    pub symbols: Vec<Symbol>,
+    /// Uses of the symbols in the order they are written, including their declarations
+    pub references: Vec<Reference>,
+    /// Names used without a declaration the index could find, such as members of unknown objects
+    pub unresolved: Vec<UnresolvedReference>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScopeKind {
+    /// Variables of the main program, main methods and classes
+    Program,
+    /// Members of a class, only reachable through `this.`
+    Class,
+    /// Parameters and variables of a method, a class function or a constructor
+    Method,
+    /// Variables of the body of an `if`, a loop or one of their branches
+    Block,
+}
+
+#[derive(Debug)]
+pub struct Scope {
+    pub kind: ScopeKind,
+    pub parent: Option<ScopeId>,
+    /// Class the scope belongs to, `MAIN_CLASS` outside classes
+    pub class_name: NameHash,
+    /// Method the scope belongs to, nothing outside methods
+    pub fn_name: Option<NameHash>,
+    /// Code covered by the scope, from its first to its last line
+    pub line_info: LineInfo,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymbolKind {
+    Class,
+    /// Method of the main program
+    Method,
+    ClassFunction,
+    ClassVariable,
+    Parameter,
+    Variable,
+    LoopCounter,
+}
+
+#[derive(Debug)]
+pub struct Symbol {
+    /// Name as written in the code, without the `this.` of class members
+    pub name: String,
+    pub name_hash: NameHash,
+    pub kind: SymbolKind,
+    /// Location of the name where it is declared, or first assigned for variables
+    pub line_info: LineInfo,
+    pub scope: ScopeId,
+    /// Class of the object assigned to a variable or class variable, when it is known without running the program
+    pub instance_of: Option<NameHash>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReferenceKind {
+    Declaration,
+    Read,
+    Write,
+    /// Compound assignments, `++` and `--`
+    ReadWrite,
+    /// Method calls and `new` objects
+    Call,
+}
+
+#[derive(Debug)]
+pub struct Reference {
+    pub symbol: SymbolId,
+    pub kind: ReferenceKind,
+    /// Location of the name only, without the `this.` or the receiver of members
+    pub line_info: LineInfo,
+    /// Innermost scope the reference is written in
+    pub scope: ScopeId,
+}
+
+#[derive(Debug)]
+pub struct UnresolvedReference {
+    pub name: String,
+    pub kind: ReferenceKind,
+    pub line_info: LineInfo,
+    pub scope: ScopeId,
+}
+
+impl SemanticIndex {
+    /// The symbol whose declaration or reference covers the 1-based `line` and `col`.
+    pub fn symbol_at(&self, file_id: FileId, line: u32, col: u16) -> Option<SymbolId> {
+        self.reference_at(file_id, line, col)
+            .map(|reference| reference.symbol)
+    }
+
+    pub fn reference_at(&self, file_id: FileId, line: u32, col: u16) -> Option<&Reference> {
+        self.references
+            .iter()
+            .find(|reference| contains(&reference.line_info, file_id, line, col))
+    }
+
+    /// Every use of `symbol`, starting with its declaration.
+    pub fn references(&self, symbol: SymbolId) -> impl Iterator<Item = &Reference> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.symbol == symbol)
+    }
+
+    /// The innermost scope containing the 1-based `line` and `col`, the program scope when there is none.
+    pub fn scope_at(&self, file_id: FileId, line: u32, col: u16) -> ScopeId {
+        // Nested scopes are added after the scope containing them, and siblings do not overlap
+        self.scopes
+            .iter()
+            .rposition(|scope| {
+                scope.kind != ScopeKind::Program && contains(&scope.line_info, file_id, line, col)
+            })
+            .unwrap_or(0)
+    }
+
+    /// The symbol `name` refers to when written in `scope`, without the `this.` of class members.
+    pub fn lookup(&self, scope: ScopeId, name: &str) -> Option<SymbolId> {
+        self.visible_symbols(scope)
+            .find(|&symbol| self.symbols[symbol].name == name)
+    }
+
+    /// Symbols that can be named in `scope`, innermost first. Class functions cannot see the variables
+    /// and methods of the main program, only its classes.
+    pub fn visible_symbols(&self, scope: ScopeId) -> impl Iterator<Item = SymbolId> + '_ {
+        let mut chain = Vec::new();
+        let mut in_class = false;
+        let mut current = Some(scope);
+        while let Some(scope) = current {
+            chain.push((scope, in_class));
+            in_class |= self.scopes[scope].kind == ScopeKind::Class;
+            current = self.scopes[scope].parent;
+        }
+
+        chain.into_iter().flat_map(move |(scope, in_class)| {
+            self.symbols
+                .iter()
+                .enumerate()
+                .filter(move |(_, symbol)| {
+                    symbol.scope == scope && (!in_class || symbol.kind == SymbolKind::Class)
+                })
+                .map(|(id, _)| id)
+        })
+    }
+
+    /// Symbols declared directly in `scope`, in the order they are declared.
+    pub fn declared_in(&self, scope: ScopeId) -> impl Iterator<Item = SymbolId> + '_ {
+        self.symbols
+            .iter()
+            .enumerate()
+            .filter(move |(_, symbol)| symbol.scope == scope)
+            .map(|(id, _)| id)
+    }
+}
+
+fn contains(line_info: &LineInfo, file_id: FileId, line: u32, col: u16) -> bool {
+    let end_line = line_info.end_line.max(line_info.start_line);
+    line_info.file_id == file_id
+        && (line_info.start_line, line_info.start_col) <= (line, col)
+        && (line, col) <= (end_line, line_info.end_col)
+}
@@ -0,0 +1,68 @@
+pub type FileId = u16;
+
+/// One of the files joined into the compiled program.
+#[derive(Debug, Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub code: String,
+    /// Number of program lines before this file
+    pub start_line: u32,
+}
+
+impl SourceFile {
+    pub fn line(&self, line: u32) -> Option<&str> {
+        self.code.lines().nth(line.checked_sub(1)? as usize)
+    }
+}
+
+/// Keeps track of where every file (includes, imports and the user code) is placed in the program,
+/// so that program lines can be turned back into file lines.
+#[derive(Debug, Default, Clone)]
+pub struct SourceMap {
+    pub files: Vec<SourceFile>,
+    pub main_file: FileId,
+}
+
+impl SourceMap {
+    /// Appends `code` to `program` and registers it under `name`.
+    pub fn push_file(&mut self, program: &mut String, name: String, code: &str) -> FileId {
+        let id = self.files.len() as FileId;
+        self.files.push(SourceFile {
+            name,
+            code: code.to_string(),
+            start_line: program.lines().count() as u32,
+        });
+
+        program.push_str(code);
+        program.push('\n');
+        id
+    }
+
+    pub fn push_main_file(&mut self, program: &mut String, name: String, code: &str) -> FileId {
+        self.main_file = self.push_file(program, name, code);
+        self.main_file
+    }
+
+    pub fn file(&self, id: FileId) -> &SourceFile {
+        &self.files[id as usize]
+    }
+
+    pub fn file_id(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(|id| id as FileId)
+    }
+
+    /// Converts a 1-based program line into the file containing it and the 1-based line inside that file.
+    pub fn locate(&self, program_line: u32) -> (FileId, u32) {
+        let id = self
+            .files
+            .iter()
+            .rposition(|file| file.start_line < program_line)
+            .unwrap_or(0);
+
+        let start_line = self.files.get(id).map_or(0, |file| file.start_line);
+        (id as FileId, program_line - start_line)
+    }
+}
@@ -0,0 +1,31 @@
+/// Naming conventions checked while validating a program, as expected by IB mark schemes.
+/// Checks are off unless a profile is given to the `Validator`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleProfile {
+    /// Variables, parameters and loop counters are written in UPPERCASE, such as `TOTAL_SCORE`
+    pub uppercase_variables: bool,
+    /// Methods and class functions are written in camelCase, such as `findMax`
+    pub camel_case_methods: bool,
+    /// Classes are written in PascalCase, such as `BankAccount`
+    pub pascal_case_classes: bool,
+    /// Variables have names that say what they hold, instead of a single letter or a placeholder
+    pub meaningful_names: bool,
+}
+
+impl StyleProfile {
+    /// Every convention of the IB mark schemes.
+    pub fn ib() -> Self {
+        Self {
+            uppercase_variables: true,
+            camel_case_methods: true,
+            pascal_case_classes: true,
+            meaningful_names: true,
+        }
+    }
+}
+
+impl Default for StyleProfile {
+    fn default() -> Self {
+        Self::ib()
+    }
+}
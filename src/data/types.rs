@@ -0,0 +1,112 @@
+use crate::data::{NameHash, Value};
+use std::fmt;
+use std::fmt::{Display, Formatter};
+
+/// Type of a value, as far as it can be known before running the program
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Type {
+    Number,
+    Bool,
+    String,
+    Array,
+    /// Object made with `new` of the class
+    Instance(NameHash),
+    Undefined,
+}
+
+impl Type {
+    /// Numbers, booleans and strings, the values that can be compared and converted to a condition
+    pub fn is_scalar(&self) -> bool {
+        matches!(self, Type::Number | Type::Bool | Type::String)
+    }
+
+    /// Numbers and booleans, the values that convert to a number
+    pub fn is_numeric(&self) -> bool {
+        matches!(self, Type::Number | Type::Bool)
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Number => write!(f, "Number"),
+            Type::Bool => write!(f, "Boolean"),
+            Type::String => write!(f, "String"),
+            Type::Array => write!(f, "Array"),
+            Type::Instance(class_name) => write!(f, "ClassInstance({})", class_name),
+            Type::Undefined => write!(f, "Undefined"),
+        }
+    }
+}
+
+/// Every type a value may have depending on the way through the program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeSet {
+    /// Not known before running, such as a parameter or an element of an array
+    Unknown,
+    /// One of the types, never empty
+    OneOf(Vec<Type>),
+}
+
+impl TypeSet {
+    pub fn of(ty: Type) -> Self {
+        TypeSet::OneOf(vec![ty])
+    }
+
+    pub fn of_value(value: &Value) -> Self {
+        match value {
+            Value::Number(_) => TypeSet::of(Type::Number),
+            Value::Bool(_) => TypeSet::of(Type::Bool),
+            Value::String(_) => TypeSet::of(Type::String),
+            Value::ArrayId(_) => TypeSet::of(Type::Array),
+            Value::InstanceId(_) => TypeSet::Unknown,
+            Value::Undefined => TypeSet::of(Type::Undefined),
+        }
+    }
+
+    /// Types a value may have, nothing when it can be anything.
+    pub fn types(&self) -> Option<&[Type]> {
+        match self {
+            TypeSet::Unknown => None,
+            TypeSet::OneOf(types) => Some(types),
+        }
+    }
+
+    /// A value that may have either the types of `self` or of `other`.
+    pub fn union(&self, other: &TypeSet) -> TypeSet {
+        match (self, other) {
+            (TypeSet::OneOf(left), TypeSet::OneOf(right)) => {
+                let mut types = left.clone();
+                for ty in right {
+                    if !types.contains(ty) {
+                        types.push(ty.clone());
+                    }
+                }
+                TypeSet::OneOf(types)
+            }
+            _ => TypeSet::Unknown,
+        }
+    }
+
+    /// Whether the value is known to never satisfy `allowed`, so using it always fails.
+    pub fn never(&self, allowed: fn(&Type) -> bool) -> bool {
+        self.types().is_some_and(|types| !types.iter().any(allowed))
+    }
+}
+
+impl Display for TypeSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeSet::Unknown => write!(f, "Unknown"),
+            TypeSet::OneOf(types) => {
+                for (i, ty) in types.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " or ")?;
+                    }
+                    write!(f, "{}", ty)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,11 +1,29 @@
 use crate::data::NameHash;
-use crate::data::diagnostic::{Diagnostic, ErrorType};
+use crate::data::diagnostic::{Diagnostic, ErrorType, Label, LineInfo};
+use crate::data::style::StyleProfile;
 use std::collections::{HashMap, HashSet};
 
 pub struct Validator {
     pub validated_functions: HashMap<NameHash, HashSet<NameHash>>,
     pub errors: Vec<Diagnostic>,
     pub added_errors: u32,
+    /// Naming conventions to check, reported as warnings
+    pub style: Option<StyleProfile>,
+    /// The `if` or loop that can leave each possibly unassigned variable without a value
+    pub unassigned: HashMap<NameHash, Label>,
+    /// Main methods being validated, innermost last
+    pub method_scopes: Vec<MethodScope>,
+    /// Variables every main method reads from the scopes of its caller, with where they are read
+    pub caller_reads: HashMap<NameHash, Vec<(NameHash, LineInfo)>>,
+}
+
+/// A method of the main program being validated, which can read the variables of its caller
+pub struct MethodScope {
+    pub name: NameHash,
+    /// Number of scopes that belong to the caller
+    pub caller_scopes: usize,
+    /// Variables the method has assigned so far
+    pub assigned: HashSet<NameHash>,
 }
 
 impl Validator {
@@ -13,17 +31,19 @@ impl Validator {
         self.added_errors = 0;
     }
 
-    pub fn is_last_recorded_expr_error(&self, error_type: ErrorType) -> bool {
+    /// Whether the last error recorded since `start_record` has `error_type` and points to `line_info`.
+    pub fn is_last_recorded_expr_error(
+        &self,
+        error_type: ErrorType,
+        line_info: Option<&LineInfo>,
+    ) -> bool {
         if self.added_errors == 0 {
             return false;
         }
 
         match self.errors.last() {
             Some(last_error) => {
-                if last_error.error_type == error_type {
-                    return true;
-                }
-                false
+                last_error.error_type == error_type && Some(&last_error.line_info) == line_info
             }
             None => false,
         }
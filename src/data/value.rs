@@ -1,7 +1,7 @@
 use crate::common::{to_bool_str, to_num_bool};
 use crate::compiler::errors::{diagnostic, unsupported_operand_error};
 use crate::data::ast_nodes::Operand;
-use crate::data::diagnostic::{Diagnostic, ErrorType, LineInfo};
+use crate::data::diagnostic::{Diagnostic, ErrorCode, LineInfo};
 use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::{Display, Formatter};
@@ -45,7 +45,7 @@ impl Value {
             Value::Bool(b) => Ok(Value::Number(if b { -1.0 } else { 0.0 })),
             _ => Err(diagnostic(
                 line_info,
-                ErrorType::InvalidType,
+                ErrorCode::InvalidUnaryOperand,
                 format!("cannot apply `negate` operand on `{}`", self.error_fmt()),
                 "",
             )),
@@ -58,7 +58,7 @@ impl Value {
             Value::Bool(b) => Ok(Value::Bool(!b)),
             _ => Err(diagnostic(
                 line_info,
-                ErrorType::InvalidType,
+                ErrorCode::InvalidUnaryOperand,
                 format!("cannot apply `not` operand on `{}`", self.error_fmt()),
                 "",
             )),
@@ -121,7 +121,7 @@ impl Value {
             }
             _ => Err(diagnostic(
                 line_info,
-                ErrorType::InvalidType,
+                ErrorCode::NotANumber,
                 format!("cannot convert `{}` to a number", self.error_fmt()),
                 "",
             )),
@@ -151,7 +151,7 @@ impl Value {
             Value::String(s) => Ok(to_bool_str(s)),
             _ => Err(diagnostic(
                 line_info,
-                ErrorType::InvalidType,
+                ErrorCode::NotABoolean,
                 format!("cannot convert `{}` to a boolean", self.error_fmt()),
                 "",
             )),
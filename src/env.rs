@@ -1,12 +1,14 @@
 use crate::ast::MAIN_CLASS;
 use crate::data::{NameHash, Value};
 use crate::env::allocated_lookup_map::AllocatedLookupMap;
+use crate::env::limits::{ExecutionLimits, ExecutionUsage};
 use crate::env::local_env::LocalEnv;
 use std::collections::{HashMap, VecDeque};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
 mod allocated_lookup_map;
+pub mod limits;
 mod local_env;
 
 #[derive(Debug)]
@@ -16,6 +18,8 @@ pub struct Env {
     pub static_envs: HashMap<NameHash, usize>,
     pub local_ids_stack: Vec<usize>,
     pub mode: EnvMode,
+    pub limits: ExecutionLimits,
+    pub usage: ExecutionUsage,
 }
 
 impl Display for Env {
@@ -27,18 +31,39 @@ impl Display for Env {
 #[derive(Debug)]
 pub enum EnvMode {
     Release,
+    #[cfg(not(target_arch = "wasm32"))]
+    Cli {
+        input: InputSource,
+    },
     Test {
         mock_inputs: VecDeque<String>,
         logs: VecDeque<String>,
     },
 }
 
+/// Where the command line runner takes `input` values from
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug)]
+pub enum InputSource {
+    /// Ask for every value with a prompt, like [`EnvMode::Release`]
+    Prompt,
+    /// Read every value from stdin without printing a prompt
+    Stdin,
+    /// Take values from the list in order, prompting once it runs out
+    Queue(VecDeque<String>),
+}
+
 impl Env {
     pub fn release() -> Self {
         let mode = EnvMode::Release;
         Env::new(mode)
     }
 
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn cli(input: InputSource) -> Self {
+        Env::new(EnvMode::Cli { input })
+    }
+
     pub fn test(mock_inputs: VecDeque<String>) -> Self {
         let mode = EnvMode::Test {
             mock_inputs,
@@ -54,12 +79,20 @@ impl Env {
             static_envs: HashMap::new(),
             local_ids_stack: Vec::new(),
             mode,
+            limits: ExecutionLimits::default(),
+            usage: ExecutionUsage::default(),
         };
         e.create_local_env(MAIN_CLASS); // global env
         e.push_local_env(0);
         e
     }
 
+    /// Runs the program within `limits`, a runtime error is returned when one of them is reached.
+    pub fn with_limits(mut self, limits: ExecutionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     pub fn record_log(logs: &mut VecDeque<String>, log: String) {
         logs.push_back(log);
     }
@@ -96,6 +129,14 @@ impl Env {
         self.get_local_env_mut().pop_scope();
     }
 
+    pub fn push_frame(&mut self) {
+        self.get_local_env_mut().push_frame();
+    }
+
+    pub fn pop_frame(&mut self) {
+        self.get_local_env_mut().pop_frame();
+    }
+
     pub fn assign(&mut self, name_hash: &NameHash, val: Value) {
         self.get_local_env_mut().assign(name_hash, val);
     }
@@ -112,6 +153,32 @@ impl Env {
         self.get_local_env().get(name_hash)
     }
 
+    /// Variable of the first call frame of the current local env, like the main program.
+    pub fn get_global(&self, name_hash: &NameHash) -> Option<Value> {
+        self.get_local_env().get_global(name_hash)
+    }
+
+    /// Variables of every scope of the current local env, to be put back with `set_scopes`.
+    pub fn scopes(&self) -> Vec<HashMap<NameHash, Value>> {
+        self.get_local_env().scopes.clone()
+    }
+
+    pub fn set_scopes(&mut self, scopes: Vec<HashMap<NameHash, Value>>) {
+        self.get_local_env_mut().scopes = scopes;
+    }
+
+    pub fn scope_count(&self) -> usize {
+        self.get_local_env().scopes.len()
+    }
+
+    /// Index of the innermost visible scope of the current local env holding `name_hash`.
+    pub fn scope_index(&self, name_hash: &NameHash) -> Option<usize> {
+        let local_env = self.get_local_env();
+        local_env
+            .visible_scopes()
+            .find(|index| local_env.scopes[*index].contains_key(name_hash))
+    }
+
     pub fn get_class_name_hash(&self, id: &usize) -> &NameHash {
         &self.get_local_env_at(id).class_name
     }
@@ -0,0 +1,81 @@
+use crate::data::NameHash;
+use crate::data::diagnostic::LineInfo;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Calls a program may nest by default, deeper than the recursion of any pseudocode exercise
+#[cfg(not(target_arch = "wasm32"))]
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// On the web, the engine running the compiler stops at about 1MB of its own stack, which holds
+/// about 450 calls with long expressions
+#[cfg(target_arch = "wasm32")]
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 200;
+
+/// Statements and expressions a call may nest on average, such as an `if` in a loop returning a
+/// sum in parentheses
+pub const NESTING_PER_CALL: usize = 10;
+
+/// Bounds on what a running program may do, so that untrusted programs cannot hang or exhaust
+/// the process running them. Every limit is off unless it is set, except the call and nesting
+/// depths, which stop endless recursion before it overflows the stack of the runner.
+#[derive(Debug, Clone)]
+pub struct ExecutionLimits {
+    /// Statements run in total, where every pass of a loop counts as one more
+    pub max_steps: Option<u64>,
+    /// Method and class function calls running at the same time
+    pub max_call_depth: Option<usize>,
+    /// Statements and expressions evaluated inside one another, counting those of every running
+    /// call. They take the stack of the runner, so `run` lowers it to what the stack holds.
+    pub max_nesting_depth: Option<usize>,
+    /// Elements of all arrays together
+    pub max_array_elements: Option<usize>,
+    /// Bytes of output, including the end of every line
+    pub max_output_bytes: Option<usize>,
+    pub max_output_lines: Option<usize>,
+    /// Set from outside the program, for example by another thread, to stop it at its next statement
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for ExecutionLimits {
+    fn default() -> Self {
+        Self {
+            max_steps: None,
+            max_call_depth: Some(DEFAULT_MAX_CALL_DEPTH),
+            max_nesting_depth: Some(DEFAULT_MAX_CALL_DEPTH * NESTING_PER_CALL),
+            max_array_elements: None,
+            max_output_bytes: None,
+            max_output_lines: None,
+            cancel: None,
+        }
+    }
+}
+
+impl ExecutionLimits {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
+/// What a program has used of its `ExecutionLimits` so far.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionUsage {
+    pub steps: u64,
+    /// Calls running at the same time, from the first one made by the main program
+    pub calls: Vec<Call>,
+    /// Statements and expressions being evaluated inside one another
+    pub nesting: usize,
+    pub array_elements: usize,
+    pub output_bytes: usize,
+    pub output_lines: usize,
+}
+
+/// A running call of a method or class function
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub fn_name: NameHash,
+    /// Where it is called
+    pub line_info: LineInfo,
+}
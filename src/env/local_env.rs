@@ -6,6 +6,8 @@ use std::collections::hash_map::Entry::Occupied;
 pub struct LocalEnv {
     pub class_name: NameHash,
     pub scopes: Vec<HashMap<NameHash, Value>>,
+    /// Index of the first scope of every call frame after the first one
+    pub frames: Vec<usize>,
 }
 
 impl LocalEnv {
@@ -13,6 +15,7 @@ impl LocalEnv {
         let mut e = Self {
             class_name: class_name_hash,
             scopes: Vec::new(),
+            frames: Vec::new(),
         };
         e.push_scope(); // top scope
         e
@@ -26,6 +29,26 @@ impl LocalEnv {
         self.scopes.pop().expect("popping empty scope stack");
     }
 
+    /// Starts the variables of a call, which only share the scopes of the first frame
+    pub fn push_frame(&mut self) {
+        self.frames.push(self.scopes.len());
+        self.push_scope();
+    }
+
+    pub fn pop_frame(&mut self) {
+        let start = self.frames.pop().expect("popping empty frame stack");
+        self.scopes.truncate(start);
+    }
+
+    /// Indexes of the scopes that can be seen from the current frame, innermost first
+    pub fn visible_scopes(&self) -> impl Iterator<Item = usize> + use<> {
+        let (frame, first_frame) = match (self.frames.first(), self.frames.last()) {
+            (Some(first), Some(last)) => (*last..self.scopes.len(), 0..*first),
+            _ => (0..self.scopes.len(), 0..0),
+        };
+        frame.rev().chain(first_frame.rev())
+    }
+
     /// Define in current (top) scope
     pub fn define(&mut self, name_hash: &NameHash, val: Value) {
         if let Some(top) = self.scopes.last_mut() {
@@ -44,7 +67,7 @@ impl LocalEnv {
         }
     }
 
-    /// Assign to nearest existing scope containing the var, or create in current scope
+    /// Assign to nearest visible scope containing the var, or create in current scope
     pub fn assign(&mut self, name_hash: &NameHash, val: Value) {
         if name_hash.this_keyword {
             self.scopes
@@ -54,8 +77,8 @@ impl LocalEnv {
             return;
         }
 
-        for scope in self.scopes.iter_mut().rev() {
-            if let Occupied(mut e) = scope.entry(name_hash.clone()) {
+        for index in self.visible_scopes() {
+            if let Occupied(mut e) = self.scopes[index].entry(name_hash.clone()) {
                 e.insert(val);
                 return;
             }
@@ -76,11 +99,20 @@ impl LocalEnv {
             return None;
         }
 
-        for scope in self.scopes.iter().rev() {
-            if let Some(v) = scope.get(name_hash) {
+        for index in self.visible_scopes() {
+            if let Some(v) = self.scopes[index].get(name_hash) {
                 return Some(v.clone());
             }
         }
         None
     }
+
+    /// Variable of the first frame, which every call can see
+    pub fn get_global(&self, name_hash: &NameHash) -> Option<Value> {
+        let end = self.frames.first().copied().unwrap_or(self.scopes.len());
+        self.scopes[..end]
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name_hash).cloned())
+    }
 }
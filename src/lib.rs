@@ -1,36 +1,182 @@
 extern crate core;
 
 use crate::ast::AST;
-use crate::compiler::compile;
+use crate::compiler::error_json::diagnostics_to_json;
 use crate::compiler::error_print::print_diagnostic_error;
+use crate::compiler::{CompileErrors, CompileOptions, compile, compile_with_options};
+use crate::data::diagnostic::Diagnostic;
+use crate::data::lint::LintLevels;
 use crate::data::name_hash::with_name_map;
-use crate::env::{Env, EnvMode};
+use crate::env::Env;
 
 pub mod ast;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod cli;
 pub mod common;
 pub mod compiler;
 pub mod data;
 pub mod env;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lsp;
 
-pub fn run_program_native(code: &str) {
-    let ast = compile(code, false);
-    let mut env = Env::release();
-    run(&ast, &mut env);
-}
-
-pub fn run(ast: &AST, env: &mut Env) {
-    with_name_map(&ast.hash_to_name_map, || {
-        match ast.traverse(env) {
-            Ok(_) => {}
-            Err(e) => {
-                print_diagnostic_error(ast, "Runtime", e);
-                match env.mode {
-                    EnvMode::Release => std::process::exit(0),
-                    EnvMode::Test { .. } => panic!(),
-                }
-            }
-        };
+/// Process exit code used when a program fails to compile.
+pub const COMPILE_ERROR_EXIT_CODE: i32 = 1;
+/// Process exit code used when a program stops on a runtime error.
+pub const RUNTIME_ERROR_EXIT_CODE: i32 = 2;
+
+/// How a program run came to an end.
+#[derive(Debug)]
+pub enum RunStatus {
+    Completed,
+    RuntimeError(Diagnostic),
+}
+
+/// The result of running a program: how it ended and the state it left behind.
+#[derive(Debug)]
+pub struct RunOutcome {
+    pub status: RunStatus,
+    pub env: Env,
+}
+
+impl RunOutcome {
+    pub fn is_success(&self) -> bool {
+        matches!(self.status, RunStatus::Completed)
+    }
+
+    pub fn diagnostic(&self) -> Option<&Diagnostic> {
+        match &self.status {
+            RunStatus::Completed => None,
+            RunStatus::RuntimeError(diagnostic) => Some(diagnostic),
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self.status {
+            RunStatus::Completed => 0,
+            RunStatus::RuntimeError(_) => RUNTIME_ERROR_EXIT_CODE,
+        }
+    }
+}
+
+/// Compiles and runs `code`, printing any errors. Returns the process exit code.
+pub fn run_program_native(code: &str) -> i32 {
+    let ast = match compile(code) {
+        Ok(ast) => ast,
+        Err(errors) => {
+            errors.print();
+            return COMPILE_ERROR_EXIT_CODE;
+        }
+    };
+
+    let outcome = run(&ast, Env::release());
+    if let Some(diagnostic) = outcome.diagnostic() {
+        print_runtime_error(&ast, diagnostic);
+    }
+    outcome.exit_code()
+}
+
+pub fn run(ast: &AST, mut env: Env) -> RunOutcome {
+    let status = with_nesting_stack(&mut env, |env| {
+        with_name_map(&ast.hash_to_name_map, || match ast.traverse(env) {
+            Ok(_) => RunStatus::Completed,
+            Err(e) => RunStatus::RuntimeError(e),
+        })
     });
+    RunOutcome { status, env }
+}
+
+/// Stack of a program thread before any statement, the default of the main thread
+#[cfg(not(target_arch = "wasm32"))]
+const BASE_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Largest stack asked for a program thread, so that a huge nesting depth lowers the depth
+/// instead of failing to start the thread
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_STACK_SIZE: usize = 512 * 1024 * 1024;
+
+/// Stack a statement or expression may take before the next one nested inside it, including a
+/// call between them. Measured at about 24KB in debug builds and 2.2KB in release builds.
+#[cfg(not(target_arch = "wasm32"))]
+const STACK_SIZE_PER_NESTING: usize = if cfg!(debug_assertions) {
+    40 * 1024
+} else {
+    4 * 1024
+};
+
+/// Runs `f` on a thread whose stack holds the nesting depth limit of `env`, lowered to what
+/// `MAX_STACK_SIZE` holds, so that a deep recursion stops with an error instead of overflowing the
+/// stack.
+#[cfg(not(target_arch = "wasm32"))]
+fn with_nesting_stack<R: Send>(env: &mut Env, f: impl FnOnce(&mut Env) -> R + Send) -> R {
+    let stack_size = match env.limits.max_nesting_depth {
+        Some(max) => {
+            let fitting = (MAX_STACK_SIZE - BASE_STACK_SIZE) / STACK_SIZE_PER_NESTING;
+            env.limits.max_nesting_depth = Some(max.min(fitting));
+            BASE_STACK_SIZE + max.min(fitting) * STACK_SIZE_PER_NESTING
+        }
+        None => BASE_STACK_SIZE,
+    };
+
+    std::thread::scope(|scope| {
+        let program = std::thread::Builder::new()
+            .stack_size(stack_size)
+            .spawn_scoped(scope, || f(env))
+            .expect("failed to start the program thread");
+        program
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    })
+}
+
+/// Threads cannot be started on the web, where the stack size is set when linking instead
+#[cfg(target_arch = "wasm32")]
+fn with_nesting_stack<R>(env: &mut Env, f: impl FnOnce(&mut Env) -> R) -> R {
+    f(env)
+}
+
+pub fn print_runtime_error(ast: &AST, diagnostic: &Diagnostic) {
+    print_diagnostic_error(&ast.source_map, RUNTIME_ERROR_CATEGORY, diagnostic);
+}
+
+pub const RUNTIME_ERROR_CATEGORY: &str = "Runtime";
+
+/// Compiles `code` and returns its errors and lint warnings as a JSON array, which is empty
+/// when it compiles without warnings.
+pub fn check_program_json(code: &str) -> String {
+    check_program_json_with_options(code, &CompileOptions::default())
+}
+
+/// Like `check_program_json`, with the checks of `options` such as the naming style.
+pub fn check_program_json_with_options(code: &str, options: &CompileOptions) -> String {
+    match compile_with_options(code, None, options) {
+        Ok(ast) => {
+            let mut warnings = ast.warnings.clone();
+            warnings.extend(ast.lint(&LintLevels::default()));
+            diagnostics_to_json(
+                &ast.source_map,
+                warnings
+                    .iter()
+                    .map(|diagnostic| (CompileErrors::error_category(diagnostic), diagnostic)),
+            )
+        }
+        Err(errors) => errors.to_json(),
+    }
+}
+
+/// Compiles and runs `code`, returning the compilation errors or the runtime error as a JSON array.
+pub fn run_program_json(code: &str, env: Env) -> String {
+    let ast = match compile(code) {
+        Ok(ast) => ast,
+        Err(errors) => return errors.to_json(),
+    };
+
+    let outcome = run(&ast, env);
+    diagnostics_to_json(
+        &ast.source_map,
+        outcome
+            .diagnostic()
+            .map(|diagnostic| (RUNTIME_ERROR_CATEGORY, diagnostic)),
+    )
 }
 
 #[cfg(target_arch = "wasm32")]
@@ -38,10 +184,50 @@ use wasm_bindgen::prelude::wasm_bindgen;
 
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
-pub fn run_program_wasm(source: &str) {
+pub fn run_program_wasm(source: &str) -> i32 {
     run_program_native(source)
 }
 
+/// Returns the compilation errors of `source` as a JSON array.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn check_program_wasm(source: &str) -> String {
+    check_program_json(source)
+}
+
+/// Returns the errors and warnings of `source` like `check_program_wasm`, checking the IB naming
+/// conventions as well.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn check_program_style_wasm(source: &str) -> String {
+    let options = CompileOptions {
+        style: Some(data::style::StyleProfile::ib()),
+        ..CompileOptions::default()
+    };
+    check_program_json_with_options(source, &options)
+}
+
+/// Runs `source` like `run_program_wasm`, but returns the errors as a JSON array instead of printing them.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn run_program_wasm_json(source: &str) -> String {
+    run_program_json(source, Env::release())
+}
+
+/// Returns `source` with the standard layout, or nothing when it does not parse.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn format_program_wasm(source: &str) -> Option<String> {
+    compiler::formatter::format_code(source).ok()
+}
+
+/// Returns the explanation of an error code such as `P0301`, or nothing for unknown codes.
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+pub fn explain_error_code(code: &str) -> Option<String> {
+    compiler::explain::explain(code).map(|explanation| explanation.to_string())
+}
+
 #[cfg(target_arch = "wasm32")]
 #[wasm_bindgen]
 pub fn setup_panic_hook() {
@@ -0,0 +1,41 @@
+use crate::lsp::transport::{read_message, write_message};
+use std::io;
+use std::io::{BufRead, Write};
+
+mod document;
+mod features;
+pub mod json;
+pub mod server;
+pub mod transport;
+
+pub use server::Server;
+
+/// Serves one client over `reader` and `writer` until it sends `exit` or closes the input.
+/// Returns the process exit code.
+pub fn run(mut reader: impl BufRead, mut writer: impl Write) -> io::Result<i32> {
+    let mut server = Server::new();
+
+    while let Some(message) = read_message(&mut reader)? {
+        for response in server.handle_text(&message) {
+            write_message(&mut writer, &response)?;
+        }
+
+        if let Some(exit_code) = server.exit_code() {
+            return Ok(exit_code);
+        }
+    }
+    // The client went away without shutting the server down
+    Ok(1)
+}
+
+/// Runs the language server over the standard input and output of the process.
+pub fn run_stdio() -> i32 {
+    let stdin = io::stdin();
+    match run(stdin.lock(), io::stdout().lock()) {
+        Ok(exit_code) => exit_code,
+        Err(error) => {
+            eprintln!("Error: {}", error);
+            1
+        }
+    }
+}
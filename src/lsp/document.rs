@@ -0,0 +1,241 @@
+use crate::ast::AST;
+use crate::compiler::{Analysis, analyze};
+use crate::data::SourceMap;
+use crate::data::diagnostic::{Diagnostic, LineInfo};
+use crate::data::lint::LintLevels;
+use crate::lsp::json::Json;
+use std::panic;
+use std::path::{Path, PathBuf};
+
+/// An open file and the result of compiling its latest text.
+pub struct Document {
+    pub uri: String,
+    pub text: String,
+    /// Errors of the latest text, in the main file or in the files it imports
+    pub diagnostics: Vec<Diagnostic>,
+    /// Files of the latest text: the included ones, the imported ones and this document
+    pub source_map: SourceMap,
+    /// AST of the latest text that could be parsed, kept while the current text cannot be
+    pub ast: Option<AST>,
+}
+
+impl Document {
+    pub fn new(uri: String, text: String) -> Self {
+        let mut document = Document {
+            uri,
+            text: String::new(),
+            diagnostics: Vec::new(),
+            source_map: SourceMap::default(),
+            ast: None,
+        };
+        document.update(text);
+        document
+    }
+
+    pub fn update(&mut self, text: String) {
+        let path = uri_to_path(&self.uri);
+
+        // A compiler bug should only cost the diagnostics of this edit, not the whole server
+        let analysis = panic::catch_unwind(|| {
+            let mut analysis = analyze(&text, path.as_deref());
+            // Lints of a program with errors would mostly point at code that is still being written
+            if let Some(ast) = &analysis.ast
+                && analysis.errors.diagnostics.is_empty()
+            {
+                analysis.errors.diagnostics = ast.lint(&LintLevels::default());
+            }
+            analysis
+        });
+        self.text = text;
+
+        match analysis {
+            Ok(Analysis { ast, errors }) => {
+                self.diagnostics = errors.diagnostics;
+                self.source_map = errors.source_map;
+                if ast.is_some() {
+                    self.ast = ast;
+                }
+            }
+            Err(_) => {
+                self.diagnostics.clear();
+                self.source_map = SourceMap::default();
+            }
+        }
+    }
+
+    /// Text of the 1-based `line` of the file with `file_id`, which is this document for its main file.
+    pub fn line_text(&self, file_id: u16, line: u32) -> &str {
+        if self.is_main_file(file_id) {
+            return text_line(&self.text, line);
+        }
+        self.source_map
+            .files
+            .get(file_id as usize)
+            .and_then(|file| file.line(line))
+            .unwrap_or("")
+    }
+
+    /// Converts `line_info` into an LSP range, counted from 0 in UTF-16 units.
+    pub fn range(&self, line_info: &LineInfo) -> Json {
+        let end_line = line_info.end_line.max(line_info.start_line);
+        Json::object([
+            (
+                "start",
+                position(
+                    self.line_text(line_info.file_id, line_info.start_line),
+                    line_info.start_line,
+                    line_info.start_col,
+                ),
+            ),
+            (
+                "end",
+                position(
+                    self.line_text(line_info.file_id, end_line),
+                    end_line,
+                    line_info.end_col,
+                ),
+            ),
+        ])
+    }
+
+    /// URI of the file with `file_id`, if it can be opened by the client.
+    /// The included standard library is compiled into the server, so it has none.
+    pub fn file_uri(&self, file_id: u16) -> Option<String> {
+        if self.is_main_file(file_id) {
+            return Some(self.uri.clone());
+        }
+
+        let main_dir = uri_to_path(&self.uri)?.parent()?.to_path_buf();
+        let path = main_dir.join(&self.source_map.files.get(file_id as usize)?.name);
+        path.is_file().then(|| path_to_uri(&path))
+    }
+
+    pub fn file_name(&self, file_id: u16) -> &str {
+        self.source_map
+            .files
+            .get(file_id as usize)
+            .map_or("", |file| file.name.as_str())
+    }
+
+    /// Range of the `import` line of this document that brings in `file_name`, directly or through other
+    /// imports. The first line is used when no line of the document names the file.
+    pub fn import_range(&self, file_name: &str) -> Json {
+        let base_name = Path::new(file_name)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let imports: Vec<(u32, &str)> = self
+            .text
+            .lines()
+            .zip(1..)
+            .filter(|(line, _)| line.trim_start().starts_with("import"))
+            .map(|(line, number)| (number, line))
+            .collect();
+        let (line, text) = imports
+            .iter()
+            .find(|(_, line)| !base_name.is_empty() && line.contains(&base_name))
+            .or(imports.first())
+            .copied()
+            .unwrap_or((1, text_line(&self.text, 1)));
+
+        let start = text.len() - text.trim_start().len();
+        let end = text.trim_end().chars().count();
+        Json::object([
+            ("start", position(text, line, start as u16 + 1)),
+            ("end", position(text, line, end as u16 + 1)),
+        ])
+    }
+
+    /// Whether `file_id` is this document, files are only known once the text was compiled.
+    pub fn is_main_file(&self, file_id: u16) -> bool {
+        self.source_map.files.is_empty() || file_id == self.source_map.main_file
+    }
+
+    /// Converts an LSP position into a 1-based line and the index of the character it points at.
+    pub fn offset(&self, position: &Json) -> Option<(u32, usize)> {
+        let line = position.get("line")?.as_u32()?;
+        let character = position.get("character")?.as_u32()? as usize;
+
+        let mut units = 0;
+        let mut index = 0;
+        for c in text_line(&self.text, line + 1).chars() {
+            if units >= character {
+                break;
+            }
+            units += c.len_utf16();
+            index += 1;
+        }
+        Some((line + 1, index))
+    }
+}
+
+fn text_line(text: &str, line: u32) -> &str {
+    line.checked_sub(1)
+        .and_then(|line| text.lines().nth(line as usize))
+        .unwrap_or("")
+}
+
+fn position(line_text: &str, line: u32, col: u16) -> Json {
+    let character: usize = line_text
+        .chars()
+        .take((col as usize).saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+
+    Json::object([
+        ("line", Json::Number(line.saturating_sub(1) as f64)),
+        ("character", Json::Number(character as f64)),
+    ])
+}
+
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = uri.strip_prefix("file://")?;
+    let bytes = path.as_bytes();
+
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    let path = String::from_utf8(decoded).ok()?;
+    // Windows paths are written as `file:///C:/...`
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.display().to_string().replace('\\', "/");
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
@@ -0,0 +1,635 @@
+use crate::ast::{AST, MAIN_CLASS, hash_const};
+use crate::data::NameHash;
+use crate::data::ast_nodes::{Class, Function};
+use crate::data::diagnostic::LineInfo;
+use crate::data::semantic_index::{Scope, ScopeId, SemanticIndex, SymbolId, SymbolKind};
+use crate::lsp::document::Document;
+use crate::lsp::json::Json;
+
+// Kinds from the LSP specification
+const COMPLETION_METHOD: f64 = 2.0;
+const COMPLETION_FUNCTION: f64 = 3.0;
+const COMPLETION_FIELD: f64 = 5.0;
+const COMPLETION_VARIABLE: f64 = 6.0;
+const COMPLETION_CLASS: f64 = 7.0;
+const COMPLETION_KEYWORD: f64 = 14.0;
+const SYMBOL_CLASS: f64 = 5.0;
+const SYMBOL_METHOD: f64 = 6.0;
+const SYMBOL_FUNCTION: f64 = 12.0;
+const SYMBOL_VARIABLE: f64 = 13.0;
+
+pub const KEYWORDS: [&str; 29] = [
+    "if",
+    "then",
+    "else",
+    "end",
+    "loop",
+    "while",
+    "from",
+    "to",
+    "until",
+    "method",
+    "return",
+    "Class",
+    "static",
+    "public",
+    "function",
+    "new",
+    "this",
+    "input",
+    "output",
+    "import",
+    "assert",
+    "true",
+    "false",
+    "undefined",
+    "AND",
+    "OR",
+    "NOT",
+    "div",
+    "mod",
+];
+
+/// Methods built into strings and arrays rather than declared in a class
+const NATIVE_METHODS: [(&str, &str); 2] = [
+    ("length", "length of a string or an array"),
+    ("substring", "substring(START, END) of a string"),
+];
+
+/// A name under the cursor, with the value it is read from when written as `RECEIVER.name`.
+struct Word {
+    name: String,
+    receiver: Option<String>,
+    line: u32,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+fn line_chars(text: &str, line: u32) -> Vec<char> {
+    text.lines()
+        .nth(line.saturating_sub(1) as usize)
+        .unwrap_or("")
+        .chars()
+        .collect()
+}
+
+/// The receiver written before the `.` ending at `dot`, including a leading `this.`.
+fn receiver_before(chars: &[char], dot: usize) -> Option<String> {
+    if chars.get(dot) != Some(&'.') {
+        return None;
+    }
+
+    let mut start = dot;
+    while start > 0 && is_ident_char(chars[start - 1]) {
+        start -= 1;
+    }
+    if start == dot {
+        return None;
+    }
+    if start >= 5 && chars[start - 5..start] == ['t', 'h', 'i', 's', '.'] {
+        start -= 5;
+    }
+    Some(chars[start..dot].iter().collect())
+}
+
+fn word_at(text: &str, line: u32, index: usize) -> Option<Word> {
+    let chars = line_chars(text, line);
+
+    let mut start = index.min(chars.len());
+    while start > 0 && is_ident_char(chars[start - 1]) {
+        start -= 1;
+    }
+    let mut end = index.min(chars.len());
+    while end < chars.len() && is_ident_char(chars[end]) {
+        end += 1;
+    }
+    if start == end {
+        return None;
+    }
+
+    let name: String = chars[start..end].iter().collect();
+    let receiver = match start.checked_sub(1) {
+        Some(dot) => receiver_before(&chars, dot),
+        None => None,
+    };
+    Some(Word {
+        name,
+        receiver,
+        line,
+    })
+}
+
+/// Name of a class member without its `this.` prefix.
+fn member_name(name: &str) -> &str {
+    name.strip_prefix("this.").unwrap_or(name)
+}
+
+fn args_list(ast: &AST, args: &[NameHash]) -> String {
+    args.iter()
+        .map(|arg| ast.get_name(arg))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn class_signature(ast: &AST, class_name: &NameHash, class: &Class) -> String {
+    format!(
+        "{}Class {}({})",
+        if class.is_static { "static " } else { "" },
+        ast.get_name(class_name),
+        args_list(ast, &class.constructor.args)
+    )
+}
+
+fn function_signature(
+    ast: &AST,
+    class_name: &NameHash,
+    fn_name: &NameHash,
+    function: &Function,
+) -> String {
+    let name = member_name(ast.get_name(fn_name));
+    let args = args_list(ast, &function.args);
+    if class_name == MAIN_CLASS {
+        format!("method {}({})", name, args)
+    } else {
+        format!("{}.{}({})", ast.get_name(class_name), name, args)
+    }
+}
+
+/// Name of the method a scope belongs to, or of the class for constructors.
+fn scope_owner(ast: &AST, scope: &Scope) -> String {
+    match &scope.fn_name {
+        Some(fn_name) => format!("`{}`", member_name(ast.get_name(fn_name))),
+        None => format!("the constructor of `{}`", ast.get_name(&scope.class_name)),
+    }
+}
+
+/// The class whose members `receiver.` refers to in `scope`, if it can be known without running the program.
+fn receiver_class(
+    ast: &AST,
+    index: &SemanticIndex,
+    scope: ScopeId,
+    receiver: &str,
+) -> Option<NameHash> {
+    let enclosing_class = &index.scopes[scope].class_name;
+    if receiver == "this" {
+        return (enclosing_class != MAIN_CLASS).then(|| enclosing_class.clone());
+    }
+
+    let class_name = hash_const(receiver);
+    if class_name != MAIN_CLASS && ast.class_map.contains_key(&class_name) {
+        return Some(class_name);
+    }
+    let symbol = index.lookup(scope, member_name(receiver))?;
+    index.symbols[symbol].instance_of.clone()
+}
+
+/// Functions named `name` that `receiver.name(...)` can call, every class is searched when its class is unknown.
+fn member_functions<'a>(
+    ast: &'a AST,
+    class_name: Option<&NameHash>,
+    name: &str,
+) -> Vec<(&'a NameHash, &'a NameHash, &'a Function)> {
+    let fn_name = hash_const(&format!("this.{}", name));
+    let mut functions: Vec<_> = ast
+        .class_map
+        .iter()
+        .filter(|(function_class, _)| match class_name {
+            Some(class_name) => *function_class == class_name,
+            None => *function_class != MAIN_CLASS,
+        })
+        .filter_map(|(class_name, class)| {
+            class
+                .functions
+                .get_key_value(&fn_name)
+                .map(|(fn_name, function)| (class_name, fn_name, function))
+        })
+        .collect();
+    functions.sort_by_key(|(class_name, _, _)| ast.get_name(class_name));
+    functions
+}
+
+fn is_variable(kind: SymbolKind) -> bool {
+    matches!(
+        kind,
+        SymbolKind::Variable | SymbolKind::Parameter | SymbolKind::LoopCounter
+    )
+}
+
+/// The symbol declared or used under the cursor, if the name there is still the one that was indexed.
+fn symbol_at(ast: &AST, index: &SemanticIndex, word: &Word, char_index: usize) -> Option<SymbolId> {
+    let symbol = index.symbol_at(ast.source_map.main_file, word.line, char_index as u16 + 1)?;
+    (index.symbols[symbol].name == word.name).then_some(symbol)
+}
+
+fn markdown(signature: &str, details: &[String]) -> Json {
+    let mut value = format!("```\n{}\n```", signature);
+    for detail in details {
+        value.push_str("\n\n");
+        value.push_str(detail);
+    }
+    Json::object([
+        ("kind", Json::string("markdown")),
+        ("value", Json::String(value)),
+    ])
+}
+
+fn declared_in(document: &Document, ast: &AST, line_info: &LineInfo) -> Vec<String> {
+    if document.is_main_file(line_info.file_id) {
+        return Vec::new();
+    }
+    vec![format!(
+        "Declared in `{}`",
+        ast.source_map.file(line_info.file_id).name
+    )]
+}
+
+fn function_details(
+    document: &Document,
+    ast: &AST,
+    function: &Function,
+    extra: Option<String>,
+) -> Vec<String> {
+    let mut details = Vec::new();
+    if function.returns() {
+        details.push("Returns a value".to_string());
+    }
+    details.extend(extra);
+    details.extend(declared_in(document, ast, &function.line_info));
+    details
+}
+
+fn symbol_hover(
+    document: &Document,
+    ast: &AST,
+    index: &SemanticIndex,
+    symbol: SymbolId,
+) -> Option<Json> {
+    let symbol = &index.symbols[symbol];
+    let scope = &index.scopes[symbol.scope];
+
+    let contents = match symbol.kind {
+        SymbolKind::Class => {
+            let class = ast.get_class(&symbol.name_hash)?;
+            markdown(
+                &class_signature(ast, &symbol.name_hash, class),
+                &declared_in(document, ast, &class.line_info),
+            )
+        }
+        SymbolKind::Method | SymbolKind::ClassFunction => {
+            let function = ast.get_function(&scope.class_name, &symbol.name_hash)?;
+            markdown(
+                &function_signature(ast, &scope.class_name, &symbol.name_hash, function),
+                &function_details(document, ast, function, None),
+            )
+        }
+        SymbolKind::ClassVariable => {
+            let class = ast.get_class(&scope.class_name)?;
+            let visibility = if class.public_vars.contains(&symbol.name_hash) {
+                "public "
+            } else {
+                ""
+            };
+            markdown(
+                &format!(
+                    "{}{}.{}",
+                    visibility,
+                    ast.get_name(&scope.class_name),
+                    symbol.name
+                ),
+                &declared_in(document, ast, &class.line_info),
+            )
+        }
+        SymbolKind::Parameter | SymbolKind::Variable | SymbolKind::LoopCounter => {
+            // Variables of blocks belong to the method the blocks are in
+            let description = match symbol.kind {
+                SymbolKind::Parameter => format!("Parameter of {}", scope_owner(ast, scope)),
+                SymbolKind::LoopCounter => "Loop counter".to_string(),
+                _ if scope.class_name == MAIN_CLASS && scope.fn_name.is_none() => {
+                    "Variable of the main program".to_string()
+                }
+                _ => format!("Local variable of {}", scope_owner(ast, scope)),
+            };
+            markdown(
+                &symbol.name,
+                &[format!(
+                    "{}, first assigned on line {}",
+                    description, symbol.line_info.start_line
+                )],
+            )
+        }
+    };
+    Some(contents)
+}
+
+pub fn hover(document: &Document, line: u32, index: usize) -> Option<Json> {
+    let ast = document.ast.as_ref()?;
+    let word = word_at(&document.text, line, index)?;
+    let semantic_index = ast.semantic_index();
+
+    let contents = if let Some(symbol) = symbol_at(ast, &semantic_index, &word, index) {
+        symbol_hover(document, ast, &semantic_index, symbol)?
+    } else if let Some(receiver) = &word.receiver {
+        // Members of objects whose class is unknown may belong to any class declaring them
+        let scope = semantic_index.scope_at(ast.source_map.main_file, line, index as u16 + 1);
+        let class_name = receiver_class(ast, &semantic_index, scope, receiver);
+        let functions = member_functions(ast, class_name.as_ref(), &word.name);
+        let (class_name, fn_name, function) = functions.first()?;
+        let others = (functions.len() > 1).then(|| {
+            let classes: Vec<_> = functions[1..]
+                .iter()
+                .map(|(class_name, _, _)| format!("`{}`", ast.get_name(class_name)))
+                .collect();
+            format!("Also declared in {}", classes.join(", "))
+        });
+        markdown(
+            &function_signature(ast, class_name, fn_name, function),
+            &function_details(document, ast, function, others),
+        )
+    } else {
+        let name = hash_const(&word.name);
+        if let Some(class) = ast.get_class(&name).filter(|_| name != MAIN_CLASS) {
+            markdown(
+                &class_signature(ast, &name, class),
+                &declared_in(document, ast, &class.line_info),
+            )
+        } else {
+            let function = ast.get_function(&MAIN_CLASS, &name)?;
+            markdown(
+                &function_signature(ast, &MAIN_CLASS, &name, function),
+                &function_details(document, ast, function, None),
+            )
+        }
+    };
+
+    Some(Json::object([("contents", contents)]))
+}
+
+fn location(document: &Document, line_info: &LineInfo) -> Option<Json> {
+    Some(Json::object([
+        ("uri", Json::String(document.file_uri(line_info.file_id)?)),
+        ("range", document.range(line_info)),
+    ]))
+}
+
+pub fn definition(document: &Document, line: u32, index: usize) -> Option<Json> {
+    let ast = document.ast.as_ref()?;
+    let word = word_at(&document.text, line, index)?;
+    let semantic_index = ast.semantic_index();
+
+    let locations: Vec<Json> = if let Some(symbol) = symbol_at(ast, &semantic_index, &word, index) {
+        let line_info = &semantic_index.symbols[symbol].line_info;
+        location(document, line_info).into_iter().collect()
+    } else if let Some(receiver) = &word.receiver {
+        let scope = semantic_index.scope_at(ast.source_map.main_file, line, index as u16 + 1);
+        let class_name = receiver_class(ast, &semantic_index, scope, receiver);
+        member_functions(ast, class_name.as_ref(), &word.name)
+            .into_iter()
+            .filter_map(|(_, _, function)| location(document, &function.line_info))
+            .collect()
+    } else {
+        let name = hash_const(&word.name);
+        let line_info = match ast.get_class(&name).filter(|_| name != MAIN_CLASS) {
+            Some(class) => &class.line_info,
+            None => &ast.get_function(&MAIN_CLASS, &name)?.line_info,
+        };
+        location(document, line_info).into_iter().collect()
+    };
+
+    match locations.len() {
+        0 => None,
+        1 => locations.into_iter().next(),
+        _ => Some(Json::Array(locations)),
+    }
+}
+
+fn completion_item(label: &str, kind: f64, detail: Option<String>) -> Json {
+    let mut item = vec![
+        ("label".to_string(), Json::string(label)),
+        ("kind".to_string(), Json::Number(kind)),
+    ];
+    if let Some(detail) = detail {
+        item.push(("detail".to_string(), Json::String(detail)));
+    }
+    Json::Object(item)
+}
+
+pub fn completion(document: &Document, line: u32, index: usize) -> Json {
+    let chars = line_chars(&document.text, line);
+    let mut start = index.min(chars.len());
+    while start > 0 && is_ident_char(chars[start - 1]) {
+        start -= 1;
+    }
+    let receiver = start
+        .checked_sub(1)
+        .and_then(|dot| receiver_before(&chars, dot));
+
+    let mut items = Vec::new();
+    let Some(ast) = &document.ast else {
+        if receiver.is_none() {
+            items.extend(
+                KEYWORDS
+                    .iter()
+                    .map(|keyword| completion_item(keyword, COMPLETION_KEYWORD, None)),
+            );
+        }
+        return Json::Array(items);
+    };
+    let semantic_index = ast.semantic_index();
+    let scope = semantic_index.scope_at(ast.source_map.main_file, line, start as u16 + 1);
+
+    match receiver {
+        Some(receiver) => {
+            let class_name = receiver_class(ast, &semantic_index, scope, &receiver);
+            let mut classes: Vec<_> = ast
+                .class_map
+                .iter()
+                .filter(|(name, _)| match &class_name {
+                    Some(class_name) => *name == class_name,
+                    None => *name != MAIN_CLASS,
+                })
+                .collect();
+            classes.sort_by_key(|(name, _)| ast.get_name(name));
+
+            let mut members = Vec::new();
+            for (name, class) in classes {
+                for (fn_name, function) in &class.functions {
+                    let signature = function_signature(ast, name, fn_name, function);
+                    members.push((
+                        member_name(ast.get_name(fn_name)),
+                        COMPLETION_METHOD,
+                        signature,
+                    ));
+                }
+                for var_name in &class.public_vars {
+                    let var_name = member_name(ast.get_name(var_name));
+                    let detail = format!("public {}.{}", ast.get_name(name), var_name);
+                    members.push((var_name, COMPLETION_FIELD, detail));
+                }
+            }
+            if class_name.is_none() {
+                for (name, detail) in NATIVE_METHODS {
+                    members.push((name, COMPLETION_METHOD, detail.to_string()));
+                }
+            }
+
+            members.sort_by(|left, right| (left.0, &left.2).cmp(&(right.0, &right.2)));
+            members.dedup_by(|left, right| left.0 == right.0);
+            for (label, kind, detail) in members {
+                items.push(completion_item(label, kind, Some(detail)));
+            }
+        }
+        None => {
+            let mut variables: Vec<_> = semantic_index
+                .visible_symbols(scope)
+                .map(|symbol| &semantic_index.symbols[symbol])
+                .filter(|symbol| is_variable(symbol.kind))
+                .map(|symbol| symbol.name.as_str())
+                .collect();
+            variables.sort();
+            variables.dedup();
+            for variable in variables {
+                items.push(completion_item(variable, COMPLETION_VARIABLE, None));
+            }
+
+            let mut functions: Vec<_> = ast.class_map[&MAIN_CLASS].functions.iter().collect();
+            functions.sort_by_key(|(fn_name, _)| ast.get_name(fn_name));
+            for (fn_name, function) in functions {
+                let signature = function_signature(ast, &MAIN_CLASS, fn_name, function);
+                items.push(completion_item(
+                    ast.get_name(fn_name),
+                    COMPLETION_FUNCTION,
+                    Some(signature),
+                ));
+            }
+
+            let mut classes: Vec<_> = ast
+                .class_map
+                .iter()
+                .filter(|(name, _)| *name != MAIN_CLASS)
+                .collect();
+            classes.sort_by_key(|(name, _)| ast.get_name(name));
+            for (name, class) in classes {
+                let signature = class_signature(ast, name, class);
+                items.push(completion_item(
+                    ast.get_name(name),
+                    COMPLETION_CLASS,
+                    Some(signature),
+                ));
+            }
+
+            items.extend(
+                KEYWORDS
+                    .iter()
+                    .map(|keyword| completion_item(keyword, COMPLETION_KEYWORD, None)),
+            );
+        }
+    }
+    Json::Array(items)
+}
+
+fn document_symbol(
+    document: &Document,
+    name: &str,
+    detail: String,
+    kind: f64,
+    line_info: &LineInfo,
+    children: Vec<Json>,
+) -> Json {
+    let range = document.range(line_info);
+    Json::object([
+        ("name", Json::string(name)),
+        ("detail", Json::String(detail)),
+        ("kind", Json::Number(kind)),
+        ("range", range.clone()),
+        ("selectionRange", range),
+        ("children", Json::Array(children)),
+    ])
+}
+
+/// Classes with their functions, methods and variables of the main program declared in the document.
+pub fn document_symbols(document: &Document) -> Json {
+    let Some(ast) = &document.ast else {
+        return Json::Array(Vec::new());
+    };
+    let main_file = ast.source_map.main_file;
+
+    let mut symbols: Vec<(u32, Json)> = Vec::new();
+    for (class_name, class) in &ast.class_map {
+        if class_name == MAIN_CLASS || class.line_info.file_id != main_file {
+            continue;
+        }
+
+        let children = sorted_functions(class, main_file)
+            .into_iter()
+            .map(|(fn_name, function)| {
+                document_symbol(
+                    document,
+                    member_name(ast.get_name(fn_name)),
+                    function_signature(ast, class_name, fn_name, function),
+                    SYMBOL_METHOD,
+                    &function.line_info,
+                    Vec::new(),
+                )
+            })
+            .collect();
+        let symbol = document_symbol(
+            document,
+            ast.get_name(class_name),
+            class_signature(ast, class_name, class),
+            SYMBOL_CLASS,
+            &class.line_info,
+            children,
+        );
+        symbols.push((class.line_info.start_line, symbol));
+    }
+
+    for (fn_name, function) in sorted_functions(&ast.class_map[&MAIN_CLASS], main_file) {
+        let symbol = document_symbol(
+            document,
+            ast.get_name(fn_name),
+            function_signature(ast, &MAIN_CLASS, fn_name, function),
+            SYMBOL_FUNCTION,
+            &function.line_info,
+            Vec::new(),
+        );
+        symbols.push((function.line_info.start_line, symbol));
+    }
+
+    let semantic_index = ast.semantic_index();
+    for variable in &semantic_index.symbols {
+        let scope = &semantic_index.scopes[variable.scope];
+        let in_main_program = scope.class_name == MAIN_CLASS && scope.fn_name.is_none();
+        if !in_main_program
+            || !is_variable(variable.kind)
+            || variable.line_info.file_id != main_file
+        {
+            continue;
+        }
+
+        let symbol = document_symbol(
+            document,
+            &variable.name,
+            String::new(),
+            SYMBOL_VARIABLE,
+            &variable.line_info,
+            Vec::new(),
+        );
+        symbols.push((variable.line_info.start_line, symbol));
+    }
+
+    symbols.sort_by_key(|(line, _)| *line);
+    Json::Array(symbols.into_iter().map(|(_, symbol)| symbol).collect())
+}
+
+/// Functions of `class` declared in the file `file_id`, in the order they are written.
+fn sorted_functions(class: &Class, file_id: u16) -> Vec<(&NameHash, &Function)> {
+    let mut functions: Vec<_> = class
+        .functions
+        .iter()
+        .filter(|(_, function)| function.line_info.file_id == file_id)
+        .collect();
+    functions.sort_by_key(|(_, function)| function.line_info.start_line);
+    functions
+}
@@ -0,0 +1,247 @@
+use crate::compiler::error_json::json_string;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// A JSON value, keeping the order of object keys so that messages are written deterministically.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    pub fn object<const N: usize>(entries: [(&str, Json); N]) -> Json {
+        Json::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    pub fn string(text: impl Into<String>) -> Json {
+        Json::String(text.into())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Follows `keys` through nested objects.
+    pub fn path(&self, keys: &[&str]) -> Option<&Json> {
+        keys.iter().try_fold(self, |json, key| json.get(key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    pub fn as_u32(&self) -> Option<u32> {
+        match self {
+            Json::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as u32),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match self {
+            Json::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Json, String> {
+        let mut chars = text.chars().peekable();
+        let json = parse_value(&mut chars)?;
+        skip_whitespace(&mut chars);
+        match chars.next() {
+            None => Ok(json),
+            Some(c) => Err(format!("unexpected `{}` after the value", c)),
+        }
+    }
+}
+
+impl Display for Json {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Json::Null => write!(f, "null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) if n.is_finite() => write!(f, "{}", n),
+            Json::Number(_) => write!(f, "null"),
+            Json::String(text) => write!(f, "{}", json_string(text)),
+            Json::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Json::Object(entries) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}:{}", json_string(key), value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+}
+
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<Json, String> {
+    skip_whitespace(chars);
+    match chars.peek() {
+        Some('{') => parse_object(chars),
+        Some('[') => parse_array(chars),
+        Some('"') => parse_string(chars).map(Json::String),
+        Some('t') => parse_literal(chars, "true", Json::Bool(true)),
+        Some('f') => parse_literal(chars, "false", Json::Bool(false)),
+        Some('n') => parse_literal(chars, "null", Json::Null),
+        Some(c) if *c == '-' || c.is_ascii_digit() => parse_number(chars),
+        Some(c) => Err(format!("unexpected `{}`", c)),
+        None => Err("unexpected end of input".to_string()),
+    }
+}
+
+fn expect(chars: &mut Peekable<Chars>, expected: char) -> Result<(), String> {
+    skip_whitespace(chars);
+    match chars.next() {
+        Some(c) if c == expected => Ok(()),
+        Some(c) => Err(format!("expected `{}`, found `{}`", expected, c)),
+        None => Err(format!("expected `{}`, found the end of input", expected)),
+    }
+}
+
+fn parse_object(chars: &mut Peekable<Chars>) -> Result<Json, String> {
+    expect(chars, '{')?;
+    let mut entries = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&'}').is_some() {
+        return Ok(Json::Object(entries));
+    }
+    loop {
+        skip_whitespace(chars);
+        let key = parse_string(chars)?;
+        expect(chars, ':')?;
+        entries.push((key, parse_value(chars)?));
+
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some('}') => return Ok(Json::Object(entries)),
+            _ => return Err("expected `,` or `}` in an object".to_string()),
+        }
+    }
+}
+
+fn parse_array(chars: &mut Peekable<Chars>) -> Result<Json, String> {
+    expect(chars, '[')?;
+    let mut items = Vec::new();
+
+    skip_whitespace(chars);
+    if chars.next_if_eq(&']').is_some() {
+        return Ok(Json::Array(items));
+    }
+    loop {
+        items.push(parse_value(chars)?);
+
+        skip_whitespace(chars);
+        match chars.next() {
+            Some(',') => continue,
+            Some(']') => return Ok(Json::Array(items)),
+            _ => return Err("expected `,` or `]` in an array".to_string()),
+        }
+    }
+}
+
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+    expect(chars, '"')?;
+    let mut text = String::new();
+
+    loop {
+        match chars.next() {
+            Some('"') => return Ok(text),
+            Some('\\') => match chars.next() {
+                Some('"') => text.push('"'),
+                Some('\\') => text.push('\\'),
+                Some('/') => text.push('/'),
+                Some('b') => text.push('\u{8}'),
+                Some('f') => text.push('\u{c}'),
+                Some('n') => text.push('\n'),
+                Some('r') => text.push('\r'),
+                Some('t') => text.push('\t'),
+                Some('u') => {
+                    let high = parse_hex4(chars)?;
+                    let code = if (0xD800..0xDC00).contains(&high) {
+                        // Characters outside the basic plane are written as a surrogate pair
+                        expect(chars, '\\')?;
+                        expect(chars, 'u')?;
+                        let low = parse_hex4(chars)?;
+                        0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF)
+                    } else {
+                        high
+                    };
+                    text.push(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER));
+                }
+                _ => return Err("invalid escape in a string".to_string()),
+            },
+            Some(c) => text.push(c),
+            None => return Err("unterminated string".to_string()),
+        }
+    }
+}
+
+fn parse_hex4(chars: &mut Peekable<Chars>) -> Result<u32, String> {
+    let mut code = 0;
+    for _ in 0..4 {
+        let digit = chars
+            .next()
+            .and_then(|c| c.to_digit(16))
+            .ok_or("invalid `\\u` escape in a string")?;
+        code = code * 16 + digit;
+    }
+    Ok(code)
+}
+
+fn parse_number(chars: &mut Peekable<Chars>) -> Result<Json, String> {
+    let mut text = String::new();
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || "+-.eE".contains(*c)) {
+        text.push(c);
+    }
+    text.parse()
+        .map(Json::Number)
+        .map_err(|_| format!("invalid number `{}`", text))
+}
+
+fn parse_literal(chars: &mut Peekable<Chars>, literal: &str, json: Json) -> Result<Json, String> {
+    for expected in literal.chars() {
+        if chars.next() != Some(expected) {
+            return Err(format!("expected `{}`", literal));
+        }
+    }
+    Ok(json)
+}
@@ -0,0 +1,300 @@
+use crate::data::diagnostic::{Diagnostic, Severity};
+use crate::lsp::document::Document;
+use crate::lsp::features::{completion, definition, document_symbols, hover};
+use crate::lsp::json::Json;
+use std::collections::HashMap;
+
+// Error codes from the JSON-RPC and LSP specifications
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const SERVER_NOT_INITIALIZED: i32 = -32002;
+
+const ERROR_SEVERITY: f64 = 1.0;
+const WARNING_SEVERITY: f64 = 2.0;
+const FULL_TEXT_SYNC: f64 = 1.0;
+
+/// State of the language server, turning every message from the client into the messages sent back.
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    initialized: bool,
+    shutdown_requested: bool,
+    exit_code: Option<i32>,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set once the client sent `exit`: 0 after a `shutdown` request, 1 otherwise.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    /// Handles the raw body of one message.
+    pub fn handle_text(&mut self, text: &str) -> Vec<Json> {
+        match Json::parse(text) {
+            Ok(message) => self.handle(&message),
+            Err(error) => vec![error_response(
+                Json::Null,
+                PARSE_ERROR,
+                &format!("invalid JSON: {}", error),
+            )],
+        }
+    }
+
+    pub fn handle(&mut self, message: &Json) -> Vec<Json> {
+        let Some(method) = message.get("method").and_then(Json::as_str) else {
+            // Responses to requests sent by the server are not expected, as it sends none
+            return match message.get("id") {
+                Some(id) if message.get("result").is_none() && message.get("error").is_none() => {
+                    vec![error_response(
+                        id.clone(),
+                        INVALID_REQUEST,
+                        "missing method",
+                    )]
+                }
+                _ => Vec::new(),
+            };
+        };
+        let params = message.get("params").unwrap_or(&Json::Null);
+
+        match message.get("id") {
+            Some(id) => {
+                let response = match self.request(method, params) {
+                    Ok(result) => Json::object([
+                        ("jsonrpc", Json::string("2.0")),
+                        ("id", id.clone()),
+                        ("result", result),
+                    ]),
+                    Err((code, error)) => error_response(id.clone(), code, &error),
+                };
+                vec![response]
+            }
+            None => self.notification(method, params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Json) -> Result<Json, (i32, String)> {
+        if method == "initialize" {
+            self.initialized = true;
+            return Ok(initialize_result());
+        }
+        if !self.initialized {
+            return Err((
+                SERVER_NOT_INITIALIZED,
+                "the server was not initialized".to_string(),
+            ));
+        }
+
+        match method {
+            "shutdown" => {
+                self.shutdown_requested = true;
+                Ok(Json::Null)
+            }
+            "textDocument/hover" => self.at_position(params, hover),
+            "textDocument/definition" => self.at_position(params, definition),
+            "textDocument/completion" => self.at_position(params, |document, line, index| {
+                Some(completion(document, line, index))
+            }),
+            "textDocument/documentSymbol" => Ok(document_symbols(self.document(params)?)),
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method `{}`", method))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Json) -> Vec<Json> {
+        if method == "exit" {
+            self.exit_code = Some(if self.shutdown_requested { 0 } else { 1 });
+            return Vec::new();
+        }
+        if !self.initialized {
+            return Vec::new();
+        }
+
+        let uri = params
+            .path(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .map(str::to_string);
+        let Some(uri) = uri else {
+            return Vec::new();
+        };
+
+        match method {
+            "textDocument/didOpen" => {
+                let Some(text) = params
+                    .path(&["textDocument", "text"])
+                    .and_then(Json::as_str)
+                else {
+                    return Vec::new();
+                };
+                let document = Document::new(uri.clone(), text.to_string());
+                self.documents.insert(uri.clone(), document);
+                vec![self.publish_diagnostics(&uri)]
+            }
+            "textDocument/didChange" => {
+                // Only full text changes are requested in the capabilities, so the last one is the whole file
+                let text = params
+                    .get("contentChanges")
+                    .and_then(Json::as_array)
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change.get("text"))
+                    .and_then(Json::as_str);
+
+                match (text, self.documents.get_mut(&uri)) {
+                    (Some(text), Some(document)) => {
+                        document.update(text.to_string());
+                        vec![self.publish_diagnostics(&uri)]
+                    }
+                    _ => Vec::new(),
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.remove(&uri);
+                vec![diagnostics_notification(&uri, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn document(&self, params: &Json) -> Result<&Document, (i32, String)> {
+        let uri = params
+            .path(&["textDocument", "uri"])
+            .and_then(Json::as_str)
+            .ok_or((INVALID_PARAMS, "missing `textDocument.uri`".to_string()))?;
+
+        self.documents
+            .get(uri)
+            .ok_or((INVALID_PARAMS, format!("document `{}` is not open", uri)))
+    }
+
+    fn at_position(
+        &self,
+        params: &Json,
+        feature: fn(&Document, u32, usize) -> Option<Json>,
+    ) -> Result<Json, (i32, String)> {
+        let document = self.document(params)?;
+        let (line, index) = params
+            .get("position")
+            .and_then(|position| document.offset(position))
+            .ok_or((INVALID_PARAMS, "missing `position`".to_string()))?;
+
+        Ok(feature(document, line, index).unwrap_or(Json::Null))
+    }
+
+    /// Errors of the main file of `uri`, errors in imported files are reported at their `import`.
+    fn publish_diagnostics(&self, uri: &str) -> Json {
+        let document = &self.documents[uri];
+        let diagnostics = document
+            .diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic_to_lsp(document, diagnostic))
+            .collect();
+        diagnostics_notification(uri, diagnostics)
+    }
+}
+
+fn initialize_result() -> Json {
+    Json::object([
+        (
+            "capabilities",
+            Json::object([
+                ("textDocumentSync", Json::Number(FULL_TEXT_SYNC)),
+                ("hoverProvider", Json::Bool(true)),
+                ("definitionProvider", Json::Bool(true)),
+                (
+                    "completionProvider",
+                    Json::object([("triggerCharacters", Json::Array(vec![Json::string(".")]))]),
+                ),
+                ("documentSymbolProvider", Json::Bool(true)),
+            ]),
+        ),
+        (
+            "serverInfo",
+            Json::object([
+                ("name", Json::string("ib_pcode_lsp")),
+                ("version", Json::string(env!("CARGO_PKG_VERSION"))),
+            ]),
+        ),
+    ])
+}
+
+fn error_response(id: Json, code: i32, message: &str) -> Json {
+    Json::object([
+        ("jsonrpc", Json::string("2.0")),
+        ("id", id),
+        (
+            "error",
+            Json::object([
+                ("code", Json::Number(code as f64)),
+                ("message", Json::string(message)),
+            ]),
+        ),
+    ])
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Json>) -> Json {
+    Json::object([
+        ("jsonrpc", Json::string("2.0")),
+        ("method", Json::string("textDocument/publishDiagnostics")),
+        (
+            "params",
+            Json::object([
+                ("uri", Json::string(uri)),
+                ("diagnostics", Json::Array(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+fn diagnostic_to_lsp(document: &Document, diagnostic: &Diagnostic) -> Json {
+    let mut message = diagnostic.message.clone();
+    let mut range = document.range(&diagnostic.line_info);
+
+    if !document.is_main_file(diagnostic.line_info.file_id) {
+        let file_name = document.file_name(diagnostic.line_info.file_id);
+        message = format!(
+            "in `{}` line {}: {}",
+            file_name, diagnostic.line_info.start_line, message
+        );
+        range = document.import_range(file_name);
+    }
+    if let Some(help) = &diagnostic.help {
+        message.push_str("\nhelp: ");
+        message.push_str(help);
+    }
+
+    let related = diagnostic
+        .labels
+        .iter()
+        .filter_map(|label| {
+            let uri = document.file_uri(label.line_info.file_id)?;
+            Some(Json::object([
+                (
+                    "location",
+                    Json::object([
+                        ("uri", Json::String(uri)),
+                        ("range", document.range(&label.line_info)),
+                    ]),
+                ),
+                ("message", Json::string(label.message.as_str())),
+            ]))
+        })
+        .collect();
+
+    let severity = match diagnostic.severity {
+        Severity::Error => ERROR_SEVERITY,
+        Severity::Warning => WARNING_SEVERITY,
+    };
+
+    Json::object([
+        ("range", range),
+        ("severity", Json::Number(severity)),
+        ("code", Json::string(diagnostic.code.as_str())),
+        ("source", Json::string("ib_pcode")),
+        ("message", Json::String(message)),
+        ("relatedInformation", Json::Array(related)),
+    ])
+}
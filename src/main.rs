@@ -1,13 +1,8 @@
-#[cfg(not(target_arch = "wasm32"))]
-const SOURCE: &str = "source";
-
 fn main() {
     #[cfg(not(target_arch = "wasm32"))]
     {
-        let contents =
-            std::fs::read_to_string(SOURCE).expect("Should have been able to read the file");
-
-        ib_pcode_compiler::run_program_native(contents.as_str());
+        let exit_code = ib_pcode_compiler::cli::main(std::env::args().skip(1));
+        std::process::exit(exit_code);
     }
     #[cfg(target_arch = "wasm32")]
     {
@@ -0,0 +1,223 @@
+use ib_pcode_compiler::cli::{
+    Command, InputMode, MessageFormat, TEST_FAILURE_EXIT_CODE, execute, modified_times, parse_args,
+};
+use ib_pcode_compiler::data::lint::{Lint, LintLevel};
+use ib_pcode_compiler::data::scoping::Scoping;
+use ib_pcode_compiler::env::limits::DEFAULT_MAX_CALL_DEPTH;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, SystemTime};
+
+fn args(args: &str) -> Vec<String> {
+    args.split_whitespace().map(str::to_string).collect()
+}
+
+#[test]
+fn parse_command_and_flags() {
+    let options = parse_args(&args("run main.pc --inputs main.in --no-color --watch")).unwrap();
+
+    assert_eq!(options.command, Command::Run);
+    assert_eq!(options.path, PathBuf::from("main.pc"));
+    assert_eq!(options.inputs, Some(PathBuf::from("main.in")));
+    assert_eq!(options.input_mode, InputMode::Prompt);
+    assert_eq!(options.color, Some(false));
+    assert!(options.watch);
+    assert_eq!(options.message_format, MessageFormat::Human);
+}
+
+#[test]
+fn parse_message_format() {
+    let options = parse_args(&args("check main.pc --message-format=json")).unwrap();
+    assert_eq!(options.message_format, MessageFormat::Json);
+
+    let options = parse_args(&args("check main.pc --message-format human")).unwrap();
+    assert_eq!(options.message_format, MessageFormat::Human);
+
+    assert!(parse_args(&args("check main.pc --message-format=xml")).is_err());
+}
+
+#[test]
+fn parse_context_lines() {
+    let options = parse_args(&args("check main.pc --context 3")).unwrap();
+    assert_eq!(options.context_lines, 3);
+    assert_eq!(parse_args(&args("check main.pc")).unwrap().context_lines, 0);
+
+    assert!(parse_args(&args("check main.pc --context")).is_err());
+    assert!(parse_args(&args("check main.pc --context many")).is_err());
+}
+
+#[test]
+fn parse_invalid_args() {
+    assert!(parse_args(&args("compile main.pc")).is_err());
+    assert!(parse_args(&args("check")).is_err());
+    assert!(parse_args(&args("check a.pc b.pc")).is_err());
+    assert!(parse_args(&args("run main.pc --inputs")).is_err());
+    assert!(parse_args(&args("ast main.pc --verbose")).is_err());
+}
+
+#[test]
+fn parse_format_check() {
+    let options = parse_args(&args("fmt main.pc --check")).unwrap();
+    assert_eq!(options.command, Command::Fmt);
+    assert!(options.check);
+
+    let options = parse_args(&args("fmt main.pc")).unwrap();
+    assert!(!options.check);
+}
+
+#[test]
+fn parse_lint_levels() {
+    let options = parse_args(&args("check main.pc --allow all --deny unused_class")).unwrap();
+    assert_eq!(options.lints.level(Lint::UnusedVariable), LintLevel::Allow);
+    assert_eq!(options.lints.level(Lint::UnusedClass), LintLevel::Deny);
+
+    assert!(parse_args(&args("check main.pc --deny unknown")).is_err());
+    assert!(parse_args(&args("check main.pc --warn")).is_err());
+}
+
+#[test]
+fn parse_style() {
+    assert!(parse_args(&args("check main.pc --style")).unwrap().style);
+    assert!(!parse_args(&args("check main.pc")).unwrap().style);
+}
+
+#[test]
+fn parse_scoping() {
+    let options = parse_args(&args("run main.pc --dynamic-scoping")).unwrap();
+    assert!(options.scoping.dynamic_calls);
+    assert!(!options.scoping.block_scoped);
+
+    let options = parse_args(&args("check main.pc --block-scoping")).unwrap();
+    assert!(options.scoping.block_scoped);
+
+    let options = parse_args(&args("run main.pc")).unwrap();
+    assert_eq!(options.scoping, Scoping::default());
+}
+
+#[test]
+fn parse_max_call_depth() {
+    let options = parse_args(&args("run main.pc --max-call-depth 50")).unwrap();
+    assert_eq!(options.max_call_depth, 50);
+
+    let options = parse_args(&args("run main.pc")).unwrap();
+    assert_eq!(options.max_call_depth, DEFAULT_MAX_CALL_DEPTH);
+
+    assert!(parse_args(&args("run main.pc --max-call-depth deep")).is_err());
+}
+
+#[test]
+fn test_dir_fails_programs_that_run_out_of_inputs() {
+    let dir = std::env::temp_dir().join("ib_pcode_cli_missing_inputs");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("p.pc"), "X = input()\nY = input()\noutput X + Y").unwrap();
+    fs::write(dir.join("p.in"), "1\n").unwrap();
+
+    let options = parse_args(&args(&format!("test {}", dir.display()))).unwrap();
+    assert_eq!(execute(&options), TEST_FAILURE_EXIT_CODE);
+}
+
+#[test]
+fn watching_a_directory_notices_changed_files() {
+    let dir = std::env::temp_dir().join("ib_pcode_cli_watch_dir");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("a.pc"), "output 1").unwrap();
+    let before = modified_times(&dir);
+
+    // Set explicitly, file systems can keep modification times at a coarse resolution
+    let changed = SystemTime::now() + Duration::from_secs(60);
+    let file = fs::File::options()
+        .write(true)
+        .open(dir.join("a.pc"))
+        .unwrap();
+    file.set_modified(changed).unwrap();
+    let after = modified_times(&dir);
+    assert_ne!(before, after);
+
+    fs::write(dir.join("b.pc"), "output 2").unwrap();
+    assert_eq!(modified_times(&dir).len(), 2);
+}
+
+#[test]
+fn watching_a_program_notices_imported_files() {
+    let dir = std::env::temp_dir().join("ib_pcode_cli_watch_imports");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("main.pc"), "import \"lib.pc\"\noutput double(2)").unwrap();
+    fs::write(
+        dir.join("lib.pc"),
+        "method double(N)\n    return N * 2\nend method",
+    )
+    .unwrap();
+    let before = modified_times(&dir.join("main.pc"));
+    assert_eq!(before.len(), 2);
+
+    let changed = SystemTime::now() + Duration::from_secs(60);
+    let file = fs::File::options()
+        .write(true)
+        .open(dir.join("lib.pc"))
+        .unwrap();
+    file.set_modified(changed).unwrap();
+    assert_ne!(before, modified_times(&dir.join("main.pc")));
+}
+
+#[test]
+fn test_dir_applies_lint_levels() {
+    let dir = std::env::temp_dir().join("ib_pcode_cli_test_lints");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("p.pc"), "X = 1\noutput 2").unwrap();
+
+    let options = parse_args(&args(&format!("test {}", dir.display()))).unwrap();
+    assert_eq!(execute(&options), 0);
+
+    let options = parse_args(&args(&format!(
+        "test {} --deny unused_variable",
+        dir.display()
+    )))
+    .unwrap();
+    assert_eq!(execute(&options), TEST_FAILURE_EXIT_CODE);
+}
+
+#[test]
+fn json_messages_go_to_stdout() {
+    let dir = std::env::temp_dir().join("ib_pcode_cli_json_messages");
+    fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("p.pc");
+    fs::write(&program, "output X").unwrap();
+
+    let output = process::Command::new(env!("CARGO_BIN_EXE_ib_pcode_compiler"))
+        .arg("check")
+        .arg(&program)
+        .arg("--message-format=json")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.stderr.is_empty());
+    assert_eq!(stdout.lines().count(), 1);
+    assert!(stdout.starts_with('{'));
+    assert!(stdout.contains("\"P0301\""));
+}
+
+#[test]
+fn json_messages_of_run_go_to_stderr() {
+    let dir = std::env::temp_dir().join("ib_pcode_cli_json_run");
+    fs::create_dir_all(&dir).unwrap();
+    let program = dir.join("p.pc");
+    fs::write(&program, "A = [1]\noutput \"{}\"\noutput A[3]").unwrap();
+
+    let output = process::Command::new(env!("CARGO_BIN_EXE_ib_pcode_compiler"))
+        .arg("run")
+        .arg(&program)
+        .arg("--message-format=json")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "{}\n");
+    assert_eq!(stderr.lines().count(), 1);
+    assert!(stderr.starts_with('{'));
+}
@@ -1,11 +1,13 @@
+#![allow(dead_code)]
+
 use ib_pcode_compiler::ast::AST;
-use ib_pcode_compiler::compiler::compile;
+use ib_pcode_compiler::compiler::compile_or_exit;
 use ib_pcode_compiler::env::{Env, EnvMode};
-use ib_pcode_compiler::run;
+use ib_pcode_compiler::{print_runtime_error, run};
 use std::collections::VecDeque;
 
 pub fn compile_test(code: &str) -> AST {
-    compile(code, true)
+    compile_or_exit(code, true)
 }
 
 pub fn compile_run_check_logs(code: &str, mock_inputs: &str, logs: &str) -> Env {
@@ -20,8 +22,13 @@ pub fn run_check_logs(ast: &AST, mock_inputs: &str, logs: &str) -> Env {
         mock_inputs_queue.push_back(line.to_string());
     }
 
-    let mut env = Env::test(mock_inputs_queue);
-    run(ast, &mut env);
+    let outcome = run(ast, Env::test(mock_inputs_queue));
+    if let Some(diagnostic) = outcome.diagnostic() {
+        print_runtime_error(ast, diagnostic);
+        panic!()
+    }
+
+    let mut env = outcome.env;
 
     assert_logs(&mut env, logs);
     env
@@ -29,7 +36,6 @@ pub fn run_check_logs(ast: &AST, mock_inputs: &str, logs: &str) -> Env {
 
 pub fn assert_logs(env: &mut Env, expected_logs: &str) {
     match &mut env.mode {
-        EnvMode::Release => panic!("Expected mode to be Test mode"),
         EnvMode::Test {
             mock_inputs: _,
             logs,
@@ -47,5 +53,6 @@ pub fn assert_logs(env: &mut Env, expected_logs: &str) {
                 panic!("Not all logs were checked, remaining: {}", logs.len());
             }
         }
+        _ => panic!("Expected mode to be Test mode"),
     }
 }
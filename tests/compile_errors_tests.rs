@@ -0,0 +1,362 @@
+use ib_pcode_compiler::compiler::compile;
+use ib_pcode_compiler::data::diagnostic::{ErrorCode, ErrorType};
+
+#[test]
+fn parse_error_is_returned() {
+    let code = r#"
+if X > 1
+    output X
+end if
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    assert_eq!(errors.diagnostics[0].error_type, ErrorType::Syntax);
+    assert_eq!(errors.diagnostics[0].line_info.start_line, 2);
+}
+
+#[test]
+fn all_validation_errors_are_returned() {
+    let code = r#"
+output A
+output B
+X = findValue()
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    assert_eq!(errors.diagnostics.len(), 3);
+    assert!(
+        errors
+            .diagnostics
+            .iter()
+            .all(|d| d.error_type == ErrorType::Uninitialized)
+    );
+}
+
+#[test]
+fn valid_program_compiles() {
+    let code = r#"
+X = 5
+output X
+    "#;
+
+    assert!(compile(code).is_ok());
+}
+
+#[test]
+fn duplicate_declarations_point_to_original() {
+    let code = r#"
+Class Point()
+    this.x = 0
+end Class
+
+Class Point()
+end Class
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    assert_eq!(errors.diagnostics.len(), 1);
+
+    let diagnostic = &errors.diagnostics[0];
+    assert_eq!(diagnostic.error_type, ErrorType::DuplicateName);
+    assert_eq!(diagnostic.line_info.start_line, 6);
+    assert_eq!(diagnostic.labels.len(), 1);
+    assert_eq!(diagnostic.labels[0].line_info.start_line, 2);
+    assert_eq!(diagnostic.labels[0].line_info.start_col, 7);
+}
+
+#[test]
+fn multi_line_spans() {
+    let code = r#"
+X = [1,
+    2,
+    Y]
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    let line_info = &errors.diagnostics[0].line_info;
+    assert_eq!((line_info.start_line, line_info.end_line), (4, 4));
+
+    let code = r#"
+[1,
+    2,
+    3] = 5
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    let line_info = &errors.diagnostics[0].line_info;
+    assert!(line_info.is_multi_line());
+    assert_eq!((line_info.start_line, line_info.start_col), (2, 1));
+    assert_eq!((line_info.end_line, line_info.end_col), (4, 7));
+}
+
+#[test]
+fn similar_names_are_suggested() {
+    let code = r#"
+COUNT = 1
+output CONT
+
+method calculateSum(A, B)
+    return A + B
+end method
+output calculateSun(1, 2)
+
+P = new Qeue()
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    let help: Vec<Option<&str>> = errors
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.help.as_deref())
+        .collect();
+
+    assert_eq!(
+        help,
+        vec![
+            Some("did you mean `COUNT`?"),
+            Some("did you mean `calculateSum`?"),
+            Some("did you mean `Queue`?"),
+        ]
+    );
+}
+
+#[test]
+fn unrelated_names_are_not_suggested() {
+    let code = r#"
+COUNT = 1
+output TOTAL
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    assert_eq!(errors.diagnostics.len(), 1);
+    assert!(errors.diagnostics[0].help.is_none());
+}
+
+#[test]
+fn method_reading_unassigned_caller_variable() {
+    let code = r#"
+method show()
+    output X
+end method
+if input() == "1" then
+    X = 1
+    show()
+end if
+show()
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    assert_eq!(errors.diagnostics.len(), 1);
+
+    let diagnostic = &errors.diagnostics[0];
+    assert_eq!(diagnostic.code, ErrorCode::UnassignedVariable);
+    assert_eq!(diagnostic.line_info.start_line, 3);
+    assert_eq!(diagnostic.labels.len(), 2);
+    assert_eq!(diagnostic.labels[0].line_info.start_line, 5);
+    assert_eq!(diagnostic.labels[1].line_info.start_line, 9);
+}
+
+#[test]
+fn variable_assigned_on_one_branch() {
+    let code = r#"
+Class Counter()
+    this.start = function(A) {
+        if A > 0 then
+            this.total = A
+        end if
+        return this.total
+    }
+
+    this.count = function(A) {
+        loop while A > 0
+            this.last = A
+            A = A - 1
+        end loop
+        return this.last
+    }
+end Class
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    let paths: Vec<(ErrorCode, u32, u32, &str)> = errors
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            let label = &diagnostic.labels[0];
+            (
+                diagnostic.code,
+                diagnostic.line_info.start_line,
+                label.line_info.start_line,
+                label.message.as_str(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            (
+                ErrorCode::UnassignedVariable,
+                7,
+                4,
+                "`this.total` is not assigned when this condition is false"
+            ),
+            (
+                ErrorCode::UnassignedVariable,
+                15,
+                11,
+                "`this.last` is not assigned when this condition is false at the start"
+            ),
+        ]
+    );
+}
+
+#[test]
+fn variable_assigned_on_every_path() {
+    let code = r#"
+Class Grade()
+    this.letter = function(SCORE) {
+        if SCORE >= 70 then
+            this.result = "A"
+        else if SCORE >= 50 then
+            this.result = "B"
+        else
+            return "F"
+        end if
+        return this.result
+    }
+end Class
+
+method factorial(N)
+    if N <= 1 then
+        return 1
+    end if
+    return N * factorial(N - 1)
+end method
+
+G = new Grade()
+output G.letter(60), factorial(5)
+    "#;
+
+    assert!(compile(code).is_ok());
+}
+
+#[test]
+fn compound_assignment_reads_the_variable() {
+    let code = r#"
+COUNT += 1
+TOTAL = TOTAL + 1
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    assert_eq!(errors.diagnostics.len(), 2);
+    assert!(
+        errors
+            .diagnostics
+            .iter()
+            .all(|d| d.code == ErrorCode::UndefinedVariable)
+    );
+}
+
+#[test]
+fn missing_return_points_at_path() {
+    let code = r#"
+method sign(N)
+    if N > 0 then
+        return 1
+    else if N < 0 then
+        return -1
+    end if
+end method
+
+method firstEven(ARR)
+    loop I from 0 to 2
+        if ARR[I] mod 2 == 0 then
+            return ARR[I]
+        end if
+    end loop
+end method
+
+method greet(NAME)
+    output "Hello " + NAME
+end method
+
+output sign(1), firstEven([1, 2, 3]), greet("Ann")
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    let paths: Vec<(u32, &str)> = errors
+        .diagnostics
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.code, ErrorCode::MissingReturn);
+            let label = &diagnostic.labels[0];
+            (label.line_info.start_line, label.message.as_str())
+        })
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            (3, "no value is returned when no condition is true"),
+            (11, "no value is returned when this loop ends"),
+            (18, "`greet` never returns a value"),
+        ]
+    );
+}
+
+#[test]
+fn missing_return_inside_a_statement_call_is_reported() {
+    let method = r#"
+method half(N)
+    if N > 0 then
+        return N / 2
+    end if
+end method
+
+method update()
+    X = half(0)
+    return X
+end method
+"#;
+
+    for main in ["update()", "output update()"] {
+        let errors = compile(&format!("{}{}", method, main)).err().unwrap();
+        assert!(
+            errors.diagnostics.iter().any(|diagnostic| {
+                diagnostic.code == ErrorCode::MissingReturn && diagnostic.line_info.start_line == 9
+            }),
+            "missing return of `half` not reported for `{}`",
+            main
+        );
+    }
+}
+
+#[test]
+fn every_path_returns() {
+    let code = r#"
+method grade(SCORE)
+    if SCORE >= 70 then
+        return "A"
+    else if SCORE >= 50 then
+        return "B"
+    else
+        return "C"
+    end if
+end method
+
+method firstPositive(ARR)
+    I = 0
+    loop while true
+        if ARR[I] > 0 then
+            return ARR[I]
+        end if
+        I = I + 1
+    end loop
+end method
+
+output grade(60), firstPositive([-1, 2])
+    "#;
+
+    assert!(compile(code).is_ok());
+}
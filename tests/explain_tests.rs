@@ -0,0 +1,114 @@
+use ib_pcode_compiler::compiler::explain::{explain, explanation};
+use ib_pcode_compiler::compiler::{CompileOptions, compile_with_options};
+use ib_pcode_compiler::data::diagnostic::{ErrorCode, Severity};
+use ib_pcode_compiler::data::lint::LintLevels;
+use ib_pcode_compiler::data::style::StyleProfile;
+use ib_pcode_compiler::env::Env;
+use ib_pcode_compiler::env::limits::ExecutionLimits;
+use ib_pcode_compiler::run;
+use std::collections::VecDeque;
+
+/// Import examples need files next to them, they are covered by the import tests
+const NEEDS_FILES: [ErrorCode; 3] = [
+    ErrorCode::ImportNotFound,
+    ErrorCode::ImportCycle,
+    ErrorCode::ImportUnreadable,
+];
+
+/// Only reported when the program is stopped from outside, covered by the limit tests
+const NEEDS_CANCELLING: ErrorCode = ErrorCode::ExecutionCancelled;
+
+/// Limits small enough for the examples of the execution limit errors to reach them
+fn example_limits() -> ExecutionLimits {
+    ExecutionLimits {
+        max_steps: Some(100_000),
+        max_call_depth: Some(20),
+        max_nesting_depth: Some(200),
+        max_array_elements: Some(10_000),
+        max_output_bytes: Some(10_000),
+        max_output_lines: Some(1_000),
+        cancel: None,
+    }
+}
+
+/// Codes of the errors of `code`, followed by the codes of its warnings with every check enabled.
+fn reported_codes(code: &str) -> (Vec<ErrorCode>, Vec<ErrorCode>) {
+    let options = CompileOptions {
+        style: Some(StyleProfile::ib()),
+        ..CompileOptions::default()
+    };
+    match compile_with_options(code, None, &options) {
+        Ok(ast) => {
+            let env = Env::test(VecDeque::new()).with_limits(example_limits());
+            let errors = run(&ast, env)
+                .diagnostic()
+                .map(|diagnostic| vec![diagnostic.code])
+                .unwrap_or_default();
+            let warnings = ast
+                .warnings
+                .iter()
+                .chain(&ast.lint(&LintLevels::default()))
+                .filter(|diagnostic| diagnostic.severity == Severity::Warning)
+                .map(|diagnostic| diagnostic.code)
+                .collect();
+            (errors, warnings)
+        }
+        Err(errors) => (
+            errors
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.code)
+                .collect(),
+            Vec::new(),
+        ),
+    }
+}
+
+#[test]
+fn codes_are_unique() {
+    for (i, code) in ErrorCode::ALL.iter().enumerate() {
+        assert_eq!(ErrorCode::parse(code.as_str()), Some(*code));
+        assert!(
+            ErrorCode::ALL[i + 1..]
+                .iter()
+                .all(|other| other.as_str() != code.as_str())
+        );
+    }
+}
+
+#[test]
+fn examples_match_their_code() {
+    for code in ErrorCode::ALL
+        .iter()
+        .filter(|code| !NEEDS_FILES.contains(code) && **code != NEEDS_CANCELLING)
+    {
+        let explanation = explanation(*code);
+
+        let (errors, warnings) = reported_codes(explanation.wrong);
+        assert!(
+            errors.contains(code) || warnings.contains(code),
+            "{}: wrong example reports {:?} {:?}",
+            code.as_str(),
+            errors,
+            warnings
+        );
+
+        let (errors, warnings) = reported_codes(explanation.fixed);
+        assert!(
+            errors.is_empty() && !warnings.contains(code),
+            "{}: fixed example reports {:?} {:?}",
+            code.as_str(),
+            errors,
+            warnings
+        );
+    }
+}
+
+#[test]
+fn explain_by_code() {
+    let text = explain("p0301").unwrap().to_string();
+    assert!(text.starts_with("P0301: Variable not found"));
+    assert!(text.contains("Wrong:\n    COUNT = 1\n    output CONT"));
+
+    assert!(explain("P9999").is_none());
+}
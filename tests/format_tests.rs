@@ -0,0 +1,147 @@
+mod common;
+
+use common::compile_test;
+use ib_pcode_compiler::compiler::formatter::{FormatError, format_code, is_formatted};
+use std::fs;
+use std::path::Path;
+
+const MESSY: &str = "\
+// Sum of the even numbers
+X=1+2*3   // start
+   Y = X^2
+
+
+if X>3 AND NOT Y=4 then
+output \"a,b\",X
+  else if X<>2 then
+// negative
+      output -X
+else
+output [1,2,
+  3]
+    end if
+loop while X<10
+X++
+end while
+Class  Point( A,B )
+public this.x=A
+  this.f=function(N) {
+return this.x+N
+}
+end Class
+P=new Point(1,2)
+output P.f(3), P.x , (1+2)*3";
+
+const FORMATTED: &str = "\
+// Sum of the even numbers
+X = 1 + 2 * 3 // start
+Y = X ^ 2
+
+if X > 3 AND NOT Y = 4 then
+    output \"a,b\", X
+else if X <> 2 then
+    // negative
+    output -X
+else
+    output [1, 2, 3]
+end if
+loop while X < 10
+    X++
+end loop
+Class Point(A, B)
+    public this.x = A
+    this.f = function(N)
+    {
+        return this.x + N
+    }
+end Class
+P = new Point(1, 2)
+output P.f(3), P.x, (1 + 2) * 3
+";
+
+/// The statements of the program, without the lines they are written on.
+fn nodes_without_lines(code: &str) -> String {
+    let ast = compile_test(code);
+    format!("{:#?}", ast.nodes)
+        .lines()
+        .filter(|line| !line.contains("lines: "))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[test]
+fn formats_blocks_and_operators() {
+    assert_eq!(format_code(MESSY).unwrap(), FORMATTED);
+}
+
+#[test]
+fn formatting_is_idempotent() {
+    let formatted = format_code(MESSY).unwrap();
+    assert_eq!(format_code(&formatted).unwrap(), formatted);
+    assert!(is_formatted(&formatted).unwrap());
+    assert!(!is_formatted(MESSY).unwrap());
+}
+
+#[test]
+fn formatting_keeps_the_ast() {
+    let formatted = format_code(MESSY).unwrap();
+    assert_eq!(nodes_without_lines(MESSY), nodes_without_lines(&formatted));
+}
+
+#[test]
+fn keeps_comments() {
+    let code = "\
+method f(A) // doubles
+    // body
+    return A * 2 // result
+    // after
+end method // done
+// end of file
+";
+    assert_eq!(format_code(code).unwrap(), code);
+
+    let code = "output \"// not a comment\"\n";
+    assert_eq!(format_code(code).unwrap(), code);
+}
+
+#[test]
+fn keeps_comments_between_class_functions() {
+    let code = "\
+Class Calc()
+    this.a = function(X)
+    {
+        return X
+    }
+
+    //-- Second group
+    this.b = function(X)
+    {
+        return X + 1
+    } // b
+    // last
+end Class
+";
+    assert_eq!(format_code(code).unwrap(), code);
+}
+
+#[test]
+fn reports_syntax_errors() {
+    assert_eq!(format_code("if X then\n"), Err(FormatError::InvalidSyntax));
+}
+
+#[test]
+fn library_files_are_stable() {
+    fn visit(dir: &Path) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                visit(&path);
+                continue;
+            }
+            let code = fs::read_to_string(&path).unwrap();
+            let formatted = format_code(&code).unwrap();
+            assert!(is_formatted(&formatted).unwrap(), "{}", path.display());
+        }
+    }
+    visit(&Path::new(env!("CARGO_MANIFEST_DIR")).join("include"));
+}
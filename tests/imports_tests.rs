@@ -0,0 +1,185 @@
+use crate::common::run_check_logs;
+use ib_pcode_compiler::compiler::compile_with_path;
+use ib_pcode_compiler::data::diagnostic::ErrorType;
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+fn write_files(test_name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ib_pcode_imports_{}", test_name));
+    let _ = fs::remove_dir_all(&dir);
+
+    for (name, code) in files {
+        let path = dir.join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, code).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn import_class_from_other_files() {
+    let dir = write_files(
+        "class",
+        &[
+            (
+                "model/Student",
+                r#"
+import "Person"
+
+Class Student(NAME, GRADE)
+    public this.person = new Person("Student")
+    public this.name = NAME
+    public this.grade = GRADE
+end Class
+"#,
+            ),
+            (
+                "model/Person",
+                r#"
+Class Person(NAME)
+    public this.name = NAME
+end Class
+"#,
+            ),
+        ],
+    );
+
+    let code = r#"
+import "model/Student"
+import "model/Person"
+
+S = new Student("Anna", 7)
+output S.person.name, S.name, S.grade
+    "#;
+
+    let ast = match compile_with_path(code, &dir.join("main")) {
+        Ok(ast) => ast,
+        Err(errors) => {
+            errors.print();
+            panic!()
+        }
+    };
+    run_check_logs(&ast, "", "Student Anna 7");
+}
+
+#[test]
+fn missing_import() {
+    let dir = write_files("missing", &[]);
+
+    let code = r#"
+import "Student"
+output 1
+    "#;
+
+    let errors = compile_with_path(code, &dir.join("main")).err().unwrap();
+    assert_eq!(errors.diagnostics.len(), 1);
+
+    let diagnostic = &errors.diagnostics[0];
+    assert_eq!(diagnostic.error_type, ErrorType::InvalidImport);
+    assert_eq!(diagnostic.line_info.file_id, errors.source_map.main_file);
+    assert_eq!(diagnostic.line_info.start_line, 2);
+}
+
+#[test]
+fn import_cycle() {
+    let dir = write_files(
+        "cycle",
+        &[
+            ("A", "import \"B\"\n"),
+            ("B", "import \"main\"\n"),
+            ("main", "import \"A\"\noutput 1\n"),
+        ],
+    );
+
+    let code = fs::read_to_string(dir.join("main")).unwrap();
+    let errors = compile_with_path(&code, &dir.join("main")).err().unwrap();
+
+    assert_eq!(errors.diagnostics.len(), 1);
+    assert_eq!(
+        errors.diagnostics[0].message,
+        "import cycle detected: main -> A -> B -> main"
+    );
+}
+
+#[test]
+fn import_without_trailing_line_break() {
+    let dir = write_files(
+        "no_trailing_line_break",
+        &[("lib.txt", "Class Box(V)\n    public this.v = V\nend Class")],
+    );
+
+    let code = "import \"lib.txt\"\noutput new Box(5).v";
+    let ast = compile_with_path(code, &dir.join("main.txt")).unwrap_or_else(|errors| {
+        errors.print();
+        panic!()
+    });
+    run_check_logs(&ast, "", "5");
+}
+
+#[test]
+fn imported_statements_run_once_before_the_importer() {
+    let dir = write_files(
+        "statements",
+        &[
+            ("A", "import \"B\"\noutput \"A\"\n"),
+            ("B", "output \"B\"\nLIMIT = 3\n"),
+        ],
+    );
+
+    let code = "import \"A\"\nimport \"B\"\noutput \"main\", LIMIT\n";
+    let ast = compile_with_path(code, &dir.join("main")).unwrap_or_else(|errors| {
+        errors.print();
+        panic!()
+    });
+    run_check_logs(&ast, "", "B\nA\nmain 3");
+}
+
+#[test]
+fn unclosed_block_is_closed_at_the_end_of_its_file() {
+    let dir = write_files(
+        "unclosed_block",
+        &[("lib", "X = 1\nif X > 0 then\n    output X\n")],
+    );
+
+    let code = "import \"lib\"\noutput \"main\"\noutput Y\n";
+    let errors = compile_with_path(code, &dir.join("main")).err().unwrap();
+    let lib = errors.source_map.file_id("lib").unwrap();
+
+    assert_eq!(errors.diagnostics.len(), 2);
+    assert_eq!(
+        errors.diagnostics[0].message,
+        "this `if` block is never closed"
+    );
+    assert_eq!(errors.diagnostics[0].line_info.file_id, lib);
+    assert_eq!(errors.diagnostics[0].line_info.start_line, 2);
+
+    // The main program is parsed outside the block, with its lines still in place
+    assert_eq!(
+        errors.diagnostics[1].message,
+        "cannot find variable `Y` in this scope"
+    );
+    assert_eq!(
+        errors.diagnostics[1].line_info.file_id,
+        errors.source_map.main_file
+    );
+    assert_eq!(errors.diagnostics[1].line_info.start_line, 3);
+}
+
+#[test]
+fn imports_of_a_file_with_syntax_errors_are_kept() {
+    let dir = write_files(
+        "syntax_errors",
+        &[("lib", "Class Box(V)\n    public this.v = V\nend Class\n")],
+    );
+
+    let code = "import \"lib\"\nX == 1\noutput new Box(5).v\n";
+    let errors = compile_with_path(code, &dir.join("main")).err().unwrap();
+
+    assert_eq!(errors.diagnostics.len(), 1);
+    assert_eq!(
+        errors.diagnostics[0].message,
+        "`==` compares two values, it cannot assign one"
+    );
+}
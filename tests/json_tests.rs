@@ -0,0 +1,57 @@
+use ib_pcode_compiler::compiler::error_json::json_string;
+use ib_pcode_compiler::env::Env;
+use ib_pcode_compiler::{check_program_json, run_program_json};
+use std::collections::VecDeque;
+
+#[test]
+fn valid_program_has_no_diagnostics() {
+    assert_eq!(check_program_json("X = 1\noutput X"), "[]");
+    assert_eq!(
+        run_program_json("X = 1\noutput X", Env::test(VecDeque::new())),
+        "[]"
+    );
+}
+
+#[test]
+fn compile_errors_as_json() {
+    let code = r#"
+method f()
+    return 1
+end method
+method f()
+    return 2
+end method
+output "say \"hi\"" + MISSING
+    "#;
+
+    let json = check_program_json(code);
+    assert_eq!(
+        json,
+        concat!(
+            r#"[{"category":"compilation","severity":"error","code":"P0307","error_type":"Duplicate Name","file":"main","#,
+            r#""span":{"start_line":5,"start_col":8,"end_line":5,"end_col":9},"#,
+            r#""message":"function `f` was already declared in this scope","note":"duplicate name used","help":null,"#,
+            r#""labels":[{"file":"main","span":{"start_line":2,"start_col":8,"end_line":2,"end_col":9},"message":"first declared here"}]},"#,
+            r#"{"category":"compilation","severity":"error","code":"P0301","error_type":"Uninitialized","file":"main","#,
+            r#""span":{"start_line":8,"start_col":23,"end_line":8,"end_col":30},"#,
+            r#""message":"cannot find variable `MISSING` in this scope","note":"not found in this scope","help":null,"labels":[]}]"#,
+        )
+    );
+}
+
+#[test]
+fn parse_and_runtime_errors_as_json() {
+    let json = check_program_json("X = ");
+    assert!(json.starts_with(r#"[{"category":"parsing","severity":"error","code":"P0101""#));
+
+    let json = run_program_json("A = [1]\noutput A[2]", Env::test(VecDeque::new()));
+    assert!(json.starts_with(r#"[{"category":"runtime","severity":"error","code":"P0601""#));
+}
+
+#[test]
+fn strings_are_escaped() {
+    assert_eq!(
+        json_string("say \"hi\"\n\\ \u{1}"),
+        r#""say \"hi\"\n\\ \u0001""#
+    );
+}
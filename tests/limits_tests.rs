@@ -0,0 +1,231 @@
+use crate::common::compile_test;
+use ib_pcode_compiler::compiler::analyze;
+use ib_pcode_compiler::data::diagnostic::{Diagnostic, ErrorCode};
+use ib_pcode_compiler::env::Env;
+use ib_pcode_compiler::env::limits::ExecutionLimits;
+use ib_pcode_compiler::run;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+mod common;
+
+/// Runtime error of `code` run within `limits`.
+fn limit_error(code: &str, limits: ExecutionLimits) -> Diagnostic {
+    let ast = compile_test(code);
+    let outcome = run(&ast, Env::test(VecDeque::new()).with_limits(limits));
+    outcome
+        .diagnostic()
+        .expect("expected a limit error")
+        .clone()
+}
+
+#[test]
+fn endless_loops_run_out_of_steps() {
+    let limits = ExecutionLimits {
+        max_steps: Some(1000),
+        ..ExecutionLimits::default()
+    };
+
+    let error = limit_error("loop while true\nend loop", limits.clone());
+    assert_eq!(error.code, ErrorCode::StepLimitExceeded);
+    assert_eq!(error.line_info.start_line, 1);
+
+    let error = limit_error("X = 0\nloop until X < 0\n    X = X + 1\nend loop", limits);
+    assert_eq!(error.code, ErrorCode::StepLimitExceeded);
+}
+
+#[test]
+fn deep_calls_are_stopped() {
+    let code = "\
+method down(N)
+    return down(N + 1)
+end method
+output down(0)";
+    let limits = ExecutionLimits {
+        max_call_depth: Some(10),
+        ..ExecutionLimits::default()
+    };
+
+    let error = limit_error(code, limits);
+    assert_eq!(error.code, ErrorCode::CallDepthExceeded);
+    assert_eq!(error.line_info.start_line, 2);
+}
+
+#[test]
+fn failing_calls_leave_the_call_stack() {
+    let code = "\
+method half(N)
+    if N > 0 then
+        return N / 2
+    end if
+end method
+method update(N)
+    X = half(N)
+    return X
+end method
+loop I from 1 to 1500
+    update(I)
+    if I mod 100 == 0 then
+        update(-I)
+    end if
+end loop";
+    let limits = ExecutionLimits {
+        max_call_depth: Some(10),
+        ..ExecutionLimits::default()
+    };
+
+    // Run without the compile time checks, which already report the missing return
+    let ast = analyze(code, None).ast.unwrap();
+    let outcome = run(&ast, Env::test(VecDeque::new()).with_limits(limits));
+
+    let error = outcome.diagnostic().unwrap();
+    assert_eq!(error.code, ErrorCode::MissingReturn);
+    assert_eq!(error.line_info.start_line, 7);
+    assert!(outcome.env.usage.calls.is_empty());
+}
+
+#[test]
+fn endless_recursion_is_stopped_by_default() {
+    let code = "\
+method down(N)
+    return down(N + 1)
+end method
+output down(0)";
+
+    let error = limit_error(code, ExecutionLimits::default());
+    assert_eq!(error.code, ErrorCode::CallDepthExceeded);
+    assert!(
+        error
+            .message
+            .starts_with("maximum recursion depth exceeded")
+    );
+    assert_eq!(error.line_info.start_line, 2);
+    assert_eq!(error.note, "`down` is called here 1000 times");
+}
+
+#[test]
+fn recursion_errors_show_the_call_chain() {
+    let code = "\
+method isEven(N)
+    if N == 0 then
+        return true
+    end if
+    return isOdd(N - 1)
+end method
+
+method isOdd(N)
+    return isEven(N - 1)
+end method
+
+output isEven(7)";
+    let limits = ExecutionLimits {
+        max_call_depth: Some(10),
+        ..ExecutionLimits::default()
+    };
+
+    let error = limit_error(code, limits);
+    assert_eq!(error.code, ErrorCode::CallDepthExceeded);
+    assert_eq!(error.line_info.start_line, 9);
+    assert_eq!(error.note, "`isEven` is called here 5 times");
+
+    let chain: Vec<(u32, &str)> = error
+        .labels
+        .iter()
+        .map(|label| (label.line_info.start_line, label.message.as_str()))
+        .collect();
+    assert_eq!(
+        chain,
+        [
+            (12, "`isEven` is called here"),
+            (5, "`isOdd` is called here 5 times"),
+        ]
+    );
+}
+
+#[test]
+fn recursion_through_nested_expressions_is_stopped() {
+    let nested = (0..20).fold("f(N - 1)".to_string(), |expr, _| format!("(1 + {})", expr));
+    let code = format!(
+        "method f(N)\n    if N == 0 then\n        return 0\n    end if\n    return {}\nend method\noutput f(100000)",
+        nested
+    );
+
+    let error = limit_error(&code, ExecutionLimits::default());
+    assert_eq!(error.code, ErrorCode::CallDepthExceeded);
+    assert!(error.message.contains("nested more than"));
+    assert_eq!(error.line_info.start_line, 5);
+}
+
+#[test]
+fn huge_depths_are_lowered_to_the_stack() {
+    let code = "\
+method down(N)
+    return down(N + 1)
+end method
+output down(0)";
+    let limits = ExecutionLimits {
+        max_call_depth: Some(usize::MAX),
+        max_nesting_depth: Some(usize::MAX),
+        ..ExecutionLimits::default()
+    };
+
+    let error = limit_error(code, limits);
+    assert_eq!(error.code, ErrorCode::CallDepthExceeded);
+    assert!(error.message.contains("nested more than"));
+}
+
+#[test]
+fn arrays_are_bounded_together() {
+    let limits = ExecutionLimits {
+        max_array_elements: Some(5),
+        ..ExecutionLimits::default()
+    };
+
+    let error = limit_error("A = [1, 2, 3]\nB = [4, 5, 6]", limits.clone());
+    assert_eq!(error.code, ErrorCode::ArrayLimitExceeded);
+    assert_eq!(error.line_info.start_line, 2);
+
+    // Growing an array by assigning past its end counts before anything is allocated
+    let error = limit_error("A = [1]\nA[1000000000000] = 2", limits);
+    assert_eq!(error.code, ErrorCode::ArrayLimitExceeded);
+}
+
+#[test]
+fn output_is_capped() {
+    let code = "loop I from 1 to 100\n    output \"line\", I\nend loop";
+
+    let limits = ExecutionLimits {
+        max_output_lines: Some(3),
+        ..ExecutionLimits::default()
+    };
+    let error = limit_error(code, limits);
+    assert_eq!(error.code, ErrorCode::OutputLinesExceeded);
+
+    let limits = ExecutionLimits {
+        max_output_bytes: Some(20),
+        ..ExecutionLimits::default()
+    };
+    let error = limit_error(code, limits);
+    assert_eq!(error.code, ErrorCode::OutputBytesExceeded);
+}
+
+#[test]
+fn programs_can_be_cancelled() {
+    let cancel = Arc::new(AtomicBool::new(false));
+    let limits = ExecutionLimits {
+        cancel: Some(cancel.clone()),
+        ..ExecutionLimits::default()
+    };
+
+    let canceller = thread::spawn(move || {
+        thread::sleep(Duration::from_millis(50));
+        cancel.store(true, Ordering::Relaxed);
+    });
+    let error = limit_error("X = 0\nloop while true\n    X = X + 1\nend loop", limits);
+    canceller.join().unwrap();
+
+    assert_eq!(error.code, ErrorCode::ExecutionCancelled);
+}
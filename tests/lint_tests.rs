@@ -0,0 +1,192 @@
+mod common;
+
+use common::compile_test;
+use ib_pcode_compiler::compiler::{CompileOptions, compile_with_options};
+use ib_pcode_compiler::data::diagnostic::{ErrorCode, Severity};
+use ib_pcode_compiler::data::lint::{Lint, LintLevel, LintLevels};
+use ib_pcode_compiler::data::scoping::Scoping;
+
+/// Code and line of every lint reported for `code` with the default levels.
+fn lints(code: &str) -> Vec<(ErrorCode, u32)> {
+    lints_with(code, &LintLevels::default())
+}
+
+fn lints_with(code: &str, levels: &LintLevels) -> Vec<(ErrorCode, u32)> {
+    compile_test(code)
+        .lint(levels)
+        .iter()
+        .map(|diagnostic| (diagnostic.code, diagnostic.line_info.start_line))
+        .collect()
+}
+
+#[test]
+fn clean_program() {
+    let code = "\
+method add(A, B)
+    return A + B
+end method
+TOTAL = 0
+loop I from 1 to 3
+    TOTAL = add(TOTAL, I)
+end loop
+output TOTAL";
+    assert_eq!(lints(code), []);
+}
+
+#[test]
+fn unused_declarations() {
+    let code = "\
+Class Box(SIZE)
+    public this.size = 1
+end Class
+method twice(N)
+    return 2
+end method
+X = 1
+Y = 2
+output Y";
+    assert_eq!(
+        lints(code),
+        [
+            (ErrorCode::UnusedClass, 1),
+            (ErrorCode::UnusedVariable, 1),
+            (ErrorCode::UnusedMethod, 4),
+            (ErrorCode::UnusedVariable, 4),
+            (ErrorCode::UnusedVariable, 7),
+        ]
+    );
+}
+
+#[test]
+fn assigned_but_never_read() {
+    let code = "\
+COUNT = 0
+COUNT = 5
+DONE = false
+DONE = true
+output DONE";
+    assert_eq!(lints(code), [(ErrorCode::UnreadVariable, 1)]);
+}
+
+#[test]
+fn loop_variable_written() {
+    let code = "\
+loop I from 1 to 10
+    if I > 5 then
+        I = 10
+    end if
+    loop I from 1 to 2
+        output I
+    end loop
+end loop";
+    assert_eq!(
+        lints(code),
+        [
+            (ErrorCode::LoopVariableWritten, 3),
+            (ErrorCode::LoopVariableWritten, 5),
+        ]
+    );
+}
+
+#[test]
+fn conditions() {
+    let code = "\
+X = 3
+if X = 3 AND X == 3 then
+    output X
+end if
+loop while 1 < X < 5
+    X = X + 1
+end loop
+if (1 < X) == true then
+    output X
+end if
+if X == 0.5 OR X != 1 / 3 then
+    output X
+end if";
+    assert_eq!(
+        lints(code),
+        [
+            (ErrorCode::AssignmentAsEquality, 2),
+            (ErrorCode::ChainedComparison, 5),
+            (ErrorCode::FloatEquality, 11),
+            (ErrorCode::FloatEquality, 11),
+        ]
+    );
+}
+
+#[test]
+fn unreachable_code() {
+    let code = "\
+method sign(N)
+    if N < 0 then
+        return -1
+        output N
+    end if
+    return 1
+    N = 0
+    output N
+end method
+output sign(2)";
+    let diagnostics = compile_test(code).lint(&LintLevels::default());
+    let lines: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.code, ErrorCode::UnreachableCode);
+            (
+                diagnostic.line_info.start_line,
+                diagnostic.line_info.end_line,
+            )
+        })
+        .collect();
+    assert_eq!(lines, [(4, 4), (7, 8)]);
+}
+
+#[test]
+fn dynamic_calls_read_caller_variables() {
+    let code = "\
+method inner()
+    output V
+end method
+method outer()
+    V = 5
+    inner()
+end method
+outer()";
+    let options = CompileOptions {
+        scoping: Scoping {
+            dynamic_calls: true,
+            ..Scoping::default()
+        },
+        ..CompileOptions::default()
+    };
+    let ast = compile_with_options(code, None, &options).unwrap();
+    assert!(ast.lint(&LintLevels::default()).is_empty());
+}
+
+#[test]
+fn allow_and_deny() {
+    let code = "X = 1\nY = 2";
+    let mut levels = LintLevels::default();
+    levels.set(Lint::UnusedVariable, LintLevel::Allow);
+    assert_eq!(lints_with(code, &levels), []);
+
+    levels.set_all(LintLevel::Deny);
+    let diagnostics = compile_test(code).lint(&levels);
+    assert_eq!(diagnostics.len(), 2);
+    assert!(
+        diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.severity == Severity::Error)
+    );
+}
+
+#[test]
+fn lint_names() {
+    for lint in Lint::ALL {
+        assert_eq!(Lint::parse(lint.name()), Some(*lint));
+        assert_eq!(Lint::parse(lint.code().as_str()), Some(*lint));
+    }
+    assert_eq!(Lint::parse("unused-method"), Some(Lint::UnusedMethod));
+    assert_eq!(Lint::parse("unknown"), None);
+}
@@ -0,0 +1,411 @@
+use ib_pcode_compiler::lsp::json::Json;
+use ib_pcode_compiler::lsp::run;
+use ib_pcode_compiler::lsp::transport::read_message;
+use std::io::Cursor;
+
+const URI: &str = "untitled:main.pc";
+
+/// Sends `messages` to a new server in one session, returning its exit code and everything it wrote back.
+fn session(messages: &[String]) -> (i32, Vec<Json>) {
+    let mut input = String::new();
+    for message in messages {
+        input.push_str(&format!(
+            "Content-Length: {}\r\n\r\n{}",
+            message.len(),
+            message
+        ));
+    }
+
+    let mut output = Vec::new();
+    let exit_code = run(Cursor::new(input), &mut output).unwrap();
+
+    let mut reader = Cursor::new(output);
+    let mut responses = Vec::new();
+    while let Some(body) = read_message(&mut reader).unwrap() {
+        responses.push(Json::parse(&body).unwrap());
+    }
+    (exit_code, responses)
+}
+
+fn request(id: u32, method: &str, params: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","id":{},"method":"{}","params":{}}}"#,
+        id, method, params
+    )
+}
+
+fn notification(method: &str, params: &str) -> String {
+    format!(
+        r#"{{"jsonrpc":"2.0","method":"{}","params":{}}}"#,
+        method, params
+    )
+}
+
+fn did_open(code: &str) -> String {
+    notification(
+        "textDocument/didOpen",
+        &format!(
+            r#"{{"textDocument":{{"uri":"{}","languageId":"pseudocode","version":1,"text":{}}}}}"#,
+            URI,
+            Json::string(code)
+        ),
+    )
+}
+
+fn at(id: u32, method: &str, line: u32, character: u32) -> String {
+    request(
+        id,
+        method,
+        &format!(
+            r#"{{"textDocument":{{"uri":"{}"}},"position":{{"line":{},"character":{}}}}}"#,
+            URI, line, character
+        ),
+    )
+}
+
+/// Opens `code`, sends `requests` and returns the result of each of them.
+fn results(code: &str, requests: Vec<String>) -> Vec<Json> {
+    let mut messages = vec![request(0, "initialize", "{}"), did_open(code)];
+    messages.extend(requests);
+
+    let (_, responses) = session(&messages);
+    responses
+        .into_iter()
+        .filter(|response| {
+            response
+                .get("id")
+                .is_some_and(|id| *id != Json::Number(0.0))
+        })
+        .map(|response| response.get("result").unwrap().clone())
+        .collect()
+}
+
+fn hover_text(result: &Json) -> &str {
+    result
+        .path(&["contents", "value"])
+        .unwrap()
+        .as_str()
+        .unwrap()
+}
+
+fn labels(result: &Json) -> Vec<&str> {
+    result
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item.get("label").unwrap().as_str().unwrap())
+        .collect()
+}
+
+const PROGRAM: &str = r#"Class Point(X, Y)
+    public this.x = X
+    public this.y = Y
+
+    this.distance = function(OTHER)
+    {
+        DX = this.x - OTHER.x
+        return DX
+    }
+end Class
+
+method area(W, H)
+    RESULT = W * H
+    return RESULT
+end method
+
+P = new Point(1, 2)
+Q = new Queue()
+Q.enqueue(P)
+output area(2, 3), Math.abs(-1)
+"#;
+
+#[test]
+fn json_round_trip() {
+    let text = r#"{"a":[1,-2.5,true,null],"b":"quote \" slash \\ é 😀","c":{}}"#;
+    let json = Json::parse(text).unwrap();
+
+    assert_eq!(
+        json.path(&["b"]).unwrap().as_str().unwrap(),
+        "quote \" slash \\ é 😀"
+    );
+    assert_eq!(Json::parse(&json.to_string()).unwrap(), json);
+    assert!(Json::parse("{\"a\":}").is_err());
+    assert!(Json::parse("[1] 2").is_err());
+}
+
+#[test]
+fn lifecycle() {
+    let (exit_code, responses) = session(&[
+        request(1, "initialize", "{}"),
+        notification("initialized", "{}"),
+        request(2, "shutdown", "null"),
+        notification("exit", "null"),
+    ]);
+
+    assert_eq!(exit_code, 0);
+    assert_eq!(responses.len(), 2);
+    let capabilities = responses[0].path(&["result", "capabilities"]).unwrap();
+    assert_eq!(capabilities.get("hoverProvider"), Some(&Json::Bool(true)));
+    assert_eq!(
+        capabilities.get("definitionProvider"),
+        Some(&Json::Bool(true))
+    );
+    assert_eq!(responses[1].get("result"), Some(&Json::Null));
+
+    let (exit_code, _) = session(&[request(1, "initialize", "{}"), notification("exit", "null")]);
+    assert_eq!(exit_code, 1);
+}
+
+#[test]
+fn invalid_messages_get_errors() {
+    let (_, responses) = session(&[
+        request(1, "hover", "{}"),
+        "{not json".to_string(),
+        request(2, "initialize", "{}"),
+        request(3, "workspace/unknown", "{}"),
+    ]);
+
+    let error_code = |response: &Json| response.path(&["error", "code"]).unwrap().clone();
+    assert_eq!(error_code(&responses[0]), Json::Number(-32002.0));
+    assert_eq!(error_code(&responses[1]), Json::Number(-32700.0));
+    assert_eq!(error_code(&responses[3]), Json::Number(-32601.0));
+}
+
+#[test]
+fn diagnostics_are_published_on_change() {
+    let change = notification(
+        "textDocument/didChange",
+        &format!(
+            r#"{{"textDocument":{{"uri":"{}","version":2}},"contentChanges":[{{"text":"X = 1\noutput X"}}]}}"#,
+            URI
+        ),
+    );
+    let (_, responses) = session(&[
+        request(1, "initialize", "{}"),
+        did_open("COUNT = 1\noutput CONT"),
+        change,
+    ]);
+
+    assert_eq!(
+        responses[1].get("method").unwrap().as_str(),
+        Some("textDocument/publishDiagnostics")
+    );
+    let diagnostics = responses[1].path(&["params", "diagnostics"]).unwrap();
+    assert_eq!(
+        diagnostics.to_string(),
+        concat!(
+            r#"[{"range":{"start":{"line":1,"character":7},"end":{"line":1,"character":11}},"#,
+            r#""severity":1,"code":"P0301","source":"ib_pcode","#,
+            r#""message":"cannot find variable `CONT` in this scope\nhelp: did you mean `COUNT`?","#,
+            r#""relatedInformation":[]}]"#
+        )
+    );
+
+    let diagnostics = responses[2].path(&["params", "diagnostics"]).unwrap();
+    assert_eq!(diagnostics, &Json::Array(Vec::new()));
+}
+
+#[test]
+fn syntax_errors_are_published() {
+    let (_, responses) = session(&[
+        request(1, "initialize", "{}"),
+        did_open("X = 1\nif X > 1\n    output X\nend if"),
+    ]);
+
+    let diagnostics = responses[1].path(&["params", "diagnostics"]).unwrap();
+    let diagnostic = &diagnostics.as_array().unwrap()[0];
+    assert_eq!(diagnostic.get("code").unwrap().as_str(), Some("P0105"));
+    assert_eq!(
+        diagnostic.path(&["range", "start", "line"]),
+        Some(&Json::Number(1.0))
+    );
+}
+
+#[test]
+fn hover() {
+    let results = results(
+        PROGRAM,
+        vec![
+            at(1, "textDocument/hover", 16, 9),
+            at(2, "textDocument/hover", 19, 8),
+            at(3, "textDocument/hover", 18, 4),
+            at(4, "textDocument/hover", 19, 25),
+            at(5, "textDocument/hover", 12, 4),
+            at(6, "textDocument/hover", 12, 13),
+            at(7, "textDocument/hover", 3, 1),
+        ],
+    );
+
+    assert_eq!(hover_text(&results[0]), "```\nClass Point(X, Y)\n```");
+    assert_eq!(
+        hover_text(&results[1]),
+        "```\nmethod area(W, H)\n```\n\nReturns a value"
+    );
+    assert_eq!(
+        hover_text(&results[2]),
+        "```\nQueue.enqueue(item)\n```\n\nDeclared in `Collections/Queue`"
+    );
+    assert_eq!(
+        hover_text(&results[3]),
+        "```\nMath.abs(x)\n```\n\nReturns a value\n\nDeclared in `Math`"
+    );
+    assert_eq!(
+        hover_text(&results[4]),
+        "```\nRESULT\n```\n\nLocal variable of `area`, first assigned on line 13"
+    );
+    assert_eq!(
+        hover_text(&results[5]),
+        "```\nW\n```\n\nParameter of `area`, first assigned on line 12"
+    );
+    assert_eq!(results[6], Json::Null);
+}
+
+#[test]
+fn definition() {
+    let results = results(
+        PROGRAM,
+        vec![
+            at(1, "textDocument/definition", 16, 10),
+            at(2, "textDocument/definition", 19, 9),
+            at(3, "textDocument/definition", 6, 9),
+            at(4, "textDocument/definition", 18, 4),
+        ],
+    );
+
+    let location = |line: f64, start: f64, end: f64| {
+        format!(
+            r#"{{"uri":"{}","range":{{"start":{{"line":{},"character":{}}},"end":{{"line":{},"character":{}}}}}}}"#,
+            URI, line, start, line, end
+        )
+    };
+    assert_eq!(results[0].to_string(), location(0.0, 6.0, 11.0));
+    assert_eq!(results[1].to_string(), location(11.0, 7.0, 11.0));
+    // Variables go to their first assignment, the standard library has no declaration to go to
+    assert_eq!(results[2].to_string(), location(6.0, 8.0, 10.0));
+    assert_eq!(results[3], Json::Null);
+}
+
+#[test]
+fn completion() {
+    let code = "Q = new Queue()\nQ.\nS = new Stack()\nS.pu\noutput Math.\n\nmethod f(A)\n    B = A\n    \nend method\nmethod g(C)\n    return C\nend method\n";
+    let results = results(
+        code,
+        vec![
+            at(1, "textDocument/completion", 1, 2),
+            at(2, "textDocument/completion", 3, 4),
+            at(3, "textDocument/completion", 4, 12),
+            at(4, "textDocument/completion", 8, 4),
+        ],
+    );
+
+    assert_eq!(labels(&results[0]), ["dequeue", "enqueue", "isEmpty"]);
+    assert!(labels(&results[1]).contains(&"push"));
+    assert!(!labels(&results[1]).contains(&"enqueue"));
+
+    let math = labels(&results[2]);
+    assert!(math.contains(&"abs") && math.contains(&"PI"));
+
+    let in_method = labels(&results[3]);
+    for label in ["A", "B", "Q", "S", "f", "g", "Queue", "Math", "loop", "end"] {
+        assert!(in_method.contains(&label), "missing {}", label);
+    }
+    assert!(!in_method.contains(&"C"));
+}
+
+#[test]
+fn document_symbols() {
+    let results = results(
+        PROGRAM,
+        vec![request(
+            1,
+            "textDocument/documentSymbol",
+            &format!(r#"{{"textDocument":{{"uri":"{}"}}}}"#, URI),
+        )],
+    );
+
+    let symbols = results[0].as_array().unwrap();
+    let names: Vec<_> = symbols
+        .iter()
+        .map(|symbol| symbol.get("name").unwrap().as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["Point", "area", "P", "Q"]);
+
+    let children = symbols[0].get("children").unwrap();
+    assert_eq!(labels_of(children), ["distance"]);
+    assert_eq!(
+        children.as_array().unwrap()[0]
+            .get("detail")
+            .unwrap()
+            .as_str(),
+        Some("Point.distance(OTHER)")
+    );
+    assert_eq!(
+        symbols[3]
+            .path(&["selectionRange", "start"])
+            .unwrap()
+            .to_string(),
+        r#"{"line":17,"character":0}"#
+    );
+}
+
+fn labels_of(symbols: &Json) -> Vec<&str> {
+    symbols
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|symbol| symbol.get("name").unwrap().as_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn imported_files() {
+    let dir = std::env::temp_dir().join("ib_pcode_lsp_imports");
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("shapes.pc"),
+        "Class Square(SIDE)\n    this.area = function()\n    {\n        return SIDE * SID\n    }\nend Class\n",
+    )
+    .unwrap();
+
+    let uri = format!("file://{}", dir.join("main.pc").display());
+    let code = "X = 1\nimport \"shapes.pc\"\nS = new Square(2)\noutput S.area()";
+    let open = notification(
+        "textDocument/didOpen",
+        &format!(
+            r#"{{"textDocument":{{"uri":"{}","text":{}}}}}"#,
+            uri,
+            Json::string(code)
+        ),
+    );
+    let goto = request(
+        2,
+        "textDocument/definition",
+        &format!(
+            r#"{{"textDocument":{{"uri":"{}"}},"position":{{"line":3,"character":10}}}}"#,
+            uri
+        ),
+    );
+    let (_, responses) = session(&[request(1, "initialize", "{}"), open, goto]);
+
+    let diagnostics = responses[1].path(&["params", "diagnostics"]).unwrap();
+    let diagnostic = &diagnostics.as_array().unwrap()[0];
+    assert_eq!(
+        diagnostic.get("message").unwrap().as_str().unwrap(),
+        "in `shapes.pc` line 4: cannot find variable `SID` in this scope\nhelp: did you mean `SIDE`?"
+    );
+    assert_eq!(
+        diagnostic.get("range").unwrap().to_string(),
+        r#"{"start":{"line":1,"character":0},"end":{"line":1,"character":18}}"#
+    );
+
+    let location = responses[2].get("result").unwrap();
+    assert_eq!(
+        location.get("uri").unwrap().as_str().unwrap(),
+        format!("file://{}", dir.join("shapes.pc").display())
+    );
+    assert_eq!(
+        location.path(&["range", "start", "line"]),
+        Some(&Json::Number(1.0))
+    );
+}
@@ -0,0 +1,72 @@
+use ib_pcode_compiler::compiler::compile;
+use ib_pcode_compiler::compiler::error_print::{
+    AnsiRenderer, HtmlRenderer, PlainRenderer, RenderOptions, Renderer, render_diagnostic,
+};
+
+fn render(code: &str, renderer: &dyn Renderer, context_lines: u32) -> String {
+    let errors = compile(code).err().unwrap();
+    render_diagnostic(
+        renderer,
+        &RenderOptions { context_lines },
+        &errors.source_map,
+        "Compilation",
+        &errors.diagnostics[0],
+    )
+}
+
+#[test]
+fn plain_renderer_has_no_markup() {
+    let text = render("X = 1\noutput X + W", &PlainRenderer, 0);
+    assert_eq!(
+        text,
+        "Compilation error[P0301]: cannot find variable `W` in this scope\n\
+         At line: 2 in main\n  | \n\
+         2 | output X + W\n  |            ^ not found in this scope\n\
+         help: did you mean `X`?\n"
+    );
+}
+
+#[test]
+fn ansi_renderer_colors_the_error() {
+    let text = render("output W", &AnsiRenderer, 0);
+    assert!(text.starts_with("\x1b[31mCompilation error[P0301]"));
+    assert!(text.ends_with("\x1b[0m"));
+}
+
+#[test]
+fn html_renderer_escapes_source_and_messages() {
+    let code = "X = \"<img src=x onerror=alert(1)>\"\noutput X + <script>";
+    let text = render(code, &HtmlRenderer { colored: true }, 1);
+
+    assert!(text.starts_with("<span style=\"color:red;\">"));
+    assert!(text.ends_with("</span>"));
+    assert!(!text.contains("<img"));
+    assert!(!text.contains("<script"));
+    assert!(text.contains("&lt;img src=x onerror=alert(1)&gt;"));
+    assert!(text.contains("&lt;script&gt;"));
+}
+
+#[test]
+fn html_renderer_escapes_all_special_characters() {
+    let renderer = HtmlRenderer { colored: false };
+    assert_eq!(
+        renderer.escape("<a href=\"x\">'&'</a>"),
+        "&lt;a href=&quot;x&quot;&gt;&#39;&amp;&#39;&lt;/a&gt;"
+    );
+    assert_eq!(renderer.start(), "");
+}
+
+#[test]
+fn context_lines_are_shown() {
+    let code = "A = 1\nB = 2\nC = 3\noutput W\nD = 4\nE = 5";
+    let text = render(code, &PlainRenderer, 2);
+    assert!(text.contains(
+        "  | \n\
+         2 | B = 2\n\
+         3 | C = 3\n\
+         4 | output W\n  |        ^ not found in this scope\n\
+         5 | D = 4\n\
+         6 | E = 5\n"
+    ));
+    assert!(!text.contains("A = 1"));
+}
@@ -0,0 +1,163 @@
+use crate::common::{compile_run_check_logs, compile_test};
+use ib_pcode_compiler::ast::hash_const;
+use ib_pcode_compiler::compiler::analyze;
+use ib_pcode_compiler::data::diagnostic::ErrorType;
+use ib_pcode_compiler::env::Env;
+use ib_pcode_compiler::{RUNTIME_ERROR_EXIT_CODE, RunStatus, run};
+use std::collections::VecDeque;
+
+mod common;
+
+#[test]
+fn runtime_error_is_returned() {
+    let code = r#"
+A = [1, 2, 3]
+output A[0]
+output A[3]
+output A[1]
+    "#;
+
+    let ast = compile_test(code);
+    let outcome = run(&ast, Env::test(VecDeque::new()));
+
+    assert!(!outcome.is_success());
+    assert_eq!(outcome.exit_code(), RUNTIME_ERROR_EXIT_CODE);
+    assert_eq!(
+        outcome.diagnostic().unwrap().error_type,
+        ErrorType::OutOfBounds
+    );
+}
+
+#[test]
+fn completed_run_keeps_env() {
+    let code = r#"
+X = 5
+output X
+    "#;
+
+    let ast = compile_test(code);
+    let outcome = run(&ast, Env::test(VecDeque::new()));
+
+    assert!(matches!(outcome.status, RunStatus::Completed));
+    assert_eq!(outcome.exit_code(), 0);
+
+    let env = compile_run_check_logs(code, "", "5");
+    assert!(env.get(&hash_const("X")).is_some());
+}
+
+#[test]
+fn error_inside_include_points_to_include_file() {
+    let code = r#"
+Q = new Queue()
+X = Q.dequeue()
+    "#;
+
+    let ast = compile_test(code);
+    let outcome = run(&ast, Env::test(VecDeque::new()));
+
+    let line_info = &outcome.diagnostic().unwrap().line_info;
+    let file = ast.source_map.file(line_info.file_id);
+    assert_eq!(file.name, "Collections/Queue");
+    assert_eq!(
+        file.line(line_info.start_line).unwrap().trim(),
+        "DEQUEUED = this.array[this.head]"
+    );
+}
+
+#[test]
+fn undefined_method_suggests_similar_name() {
+    let code = r#"
+QUEUES = [new Queue()]
+QUEUES[0].enqeue(1)
+    "#;
+
+    let ast = compile_test(code);
+    let outcome = run(&ast, Env::test(VecDeque::new()));
+
+    assert_eq!(
+        outcome.diagnostic().unwrap().help.as_deref(),
+        Some("did you mean `enqueue`?")
+    );
+}
+
+#[test]
+fn missing_return_points_at_path() {
+    let code = r#"
+Class Shape()
+    this.area = function(SIDES) {
+        if SIDES == 4 then
+            return 16
+        end if
+    }
+end Class
+SHAPES = [new Shape()]
+X = SHAPES[0].area(3)
+    "#;
+
+    let ast = compile_test(code);
+    let outcome = run(&ast, Env::test(VecDeque::new()));
+
+    let diagnostic = outcome.diagnostic().unwrap();
+    assert_eq!(diagnostic.error_type, ErrorType::NoReturn);
+    assert_eq!(diagnostic.line_info.start_line, 10);
+    assert_eq!(diagnostic.labels[0].line_info.start_line, 4);
+    assert_eq!(
+        diagnostic.labels[0].message,
+        "no value is returned when this condition is false"
+    );
+}
+
+#[test]
+fn missing_return_inside_a_statement_call_is_an_error() {
+    let code = r#"
+method half(N)
+    if N > 0 then
+        return N / 2
+    end if
+end method
+
+method update()
+    X = half(0)
+    return X
+end method
+
+update()
+output "not reached"
+    "#;
+
+    // Run without the compile time checks, which report the same problem
+    let ast = analyze(code, None).ast.unwrap();
+    let outcome = run(&ast, Env::test(VecDeque::new()));
+
+    let diagnostic = outcome.diagnostic().unwrap();
+    assert_eq!(diagnostic.error_type, ErrorType::NoReturn);
+    assert_eq!(diagnostic.line_info.start_line, 9);
+}
+
+#[test]
+fn calls_without_return_as_statements() {
+    let code = r#"
+method greet(NAME)
+    output "Hello " + NAME
+end method
+
+static Class Printer()
+    this.greet = function(NAME) {
+        output "Hi " + NAME
+    }
+end Class
+
+Class Greeter()
+    this.greet = function(NAME) {
+        output "Hey " + NAME
+    }
+end Class
+
+G = new Greeter()
+greet("Ann")
+Printer.greet("Bob")
+G.greet("Cid")
+    "#;
+
+    compile_run_check_logs(code, "", "Hello Ann\nHi Bob\nHey Cid");
+}
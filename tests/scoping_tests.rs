@@ -0,0 +1,157 @@
+use crate::common::run_check_logs;
+use ib_pcode_compiler::ast::AST;
+use ib_pcode_compiler::compiler::{CompileOptions, compile_with_options};
+use ib_pcode_compiler::data::diagnostic::ErrorCode;
+use ib_pcode_compiler::data::scoping::Scoping;
+
+mod common;
+
+fn compile_scoped(code: &str, scoping: Scoping) -> AST {
+    let options = CompileOptions {
+        scoping,
+        ..CompileOptions::default()
+    };
+    compile_with_options(code, None, &options).unwrap_or_else(|errors| {
+        errors.print();
+        panic!()
+    })
+}
+
+fn block_scoped() -> Scoping {
+    Scoping {
+        block_scoped: true,
+        ..Scoping::default()
+    }
+}
+
+/// Code and line of every compile error of `code`.
+fn compile_errors(code: &str, scoping: Scoping) -> Vec<(ErrorCode, u32)> {
+    let options = CompileOptions {
+        scoping,
+        ..CompileOptions::default()
+    };
+    let errors = compile_with_options(code, None, &options).err().unwrap();
+    errors
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.code, diagnostic.line_info.start_line))
+        .collect()
+}
+
+fn dynamic_calls() -> Scoping {
+    Scoping {
+        dynamic_calls: true,
+        ..Scoping::default()
+    }
+}
+
+#[test]
+fn recursive_calls_keep_their_own_variables() {
+    let code = r#"
+method countDown(N)
+    if N > 0 then
+        REST = N - 1
+        countDown(REST)
+        output REST
+    end if
+end method
+
+countDown(3)
+    "#;
+
+    let ast = compile_scoped(code, Scoping::default());
+    run_check_logs(&ast, "", "0\n1\n2");
+
+    let ast = compile_scoped(code, dynamic_calls());
+    run_check_logs(&ast, "", "0\n0\n0");
+}
+
+#[test]
+fn methods_share_main_program_variables() {
+    let code = r#"
+method add(N)
+    TOTAL = TOTAL + N
+end method
+
+TOTAL = 0
+add(2)
+add(3)
+output TOTAL
+    "#;
+
+    let ast = compile_scoped(code, Scoping::default());
+    run_check_logs(&ast, "", "5");
+}
+
+#[test]
+fn methods_cannot_see_caller_variables() {
+    let code = r#"
+method show()
+    output MESSAGE
+end method
+
+method greet()
+    MESSAGE = "hi"
+    show()
+end method
+
+greet()
+    "#;
+
+    assert_eq!(
+        compile_errors(code, Scoping::default()),
+        [(ErrorCode::UndefinedVariable, 3)]
+    );
+
+    let ast = compile_scoped(code, dynamic_calls());
+    run_check_logs(&ast, "", "hi");
+}
+
+#[test]
+fn variables_live_until_the_end_of_the_method() {
+    let code = r#"
+loop I from 1 to 3
+    LAST = I
+end loop
+if LAST > 2 then
+    SIGN = "big"
+else
+    SIGN = "small"
+end if
+output LAST, SIGN
+    "#;
+
+    let ast = compile_scoped(code, Scoping::default());
+    run_check_logs(&ast, "", "3 big");
+
+    assert_eq!(
+        compile_errors(code, block_scoped()),
+        [
+            (ErrorCode::UndefinedVariable, 5),
+            (ErrorCode::UndefinedVariable, 10),
+            (ErrorCode::UndefinedVariable, 10),
+        ]
+    );
+}
+
+#[test]
+fn variables_of_some_branches_may_be_unassigned() {
+    let code = r#"
+N = 5
+loop while N > 10
+    HALF = N / 2
+end loop
+if N > 3 then
+    BIG = true
+end if
+output HALF, BIG
+    "#;
+
+    assert_eq!(
+        compile_errors(code, Scoping::default()),
+        [
+            (ErrorCode::UnassignedVariable, 9),
+            (ErrorCode::UnassignedVariable, 9),
+        ]
+    );
+}
@@ -0,0 +1,267 @@
+mod common;
+
+use common::compile_test;
+use ib_pcode_compiler::ast::AST;
+use ib_pcode_compiler::compiler::{CompileOptions, compile_with_options};
+use ib_pcode_compiler::data::SemanticIndex;
+use ib_pcode_compiler::data::scoping::Scoping;
+use ib_pcode_compiler::data::semantic_index::{ReferenceKind, ScopeKind, SymbolKind};
+
+/// Line, column and kind of every use of the symbol found at `line` and `col` of the main file.
+fn references_at(code: &str, line: u32, col: u16) -> Vec<(u32, u16, ReferenceKind)> {
+    references_in(&compile_test(code), line, col)
+}
+
+fn references_in(ast: &AST, line: u32, col: u16) -> Vec<(u32, u16, ReferenceKind)> {
+    let index = ast.semantic_index();
+    let symbol = index
+        .symbol_at(ast.source_map.main_file, line, col)
+        .expect("no symbol at position");
+
+    index
+        .references(symbol)
+        .map(|reference| {
+            (
+                reference.line_info.start_line,
+                reference.line_info.start_col,
+                reference.kind,
+            )
+        })
+        .collect()
+}
+
+fn main_symbols(code: &str) -> (SemanticIndex, u16) {
+    let ast = compile_test(code);
+    (ast.semantic_index(), ast.source_map.main_file)
+}
+
+#[test]
+fn variable_references() {
+    let code = "X = 1\nX += 2\noutput X\nX++";
+    assert_eq!(
+        references_at(code, 3, 8),
+        [
+            (1, 1, ReferenceKind::Declaration),
+            (2, 1, ReferenceKind::ReadWrite),
+            (3, 8, ReferenceKind::Read),
+            (4, 1, ReferenceKind::ReadWrite),
+        ]
+    );
+}
+
+#[test]
+fn block_scopes() {
+    let code = "\
+if true then
+    A = 1
+    output A
+end if
+if true then
+    A = 2
+end if
+B = 0
+loop I from 0 to 2
+    B = B + I
+end loop";
+    // Variables live until the end of the main program
+    assert_eq!(
+        references_at(code, 3, 12),
+        [
+            (2, 5, ReferenceKind::Declaration),
+            (3, 12, ReferenceKind::Read),
+            (6, 5, ReferenceKind::Write)
+        ]
+    );
+
+    // With block scoping, each `if` declares its own `A`
+    let options = CompileOptions {
+        scoping: Scoping {
+            block_scoped: true,
+            ..Scoping::default()
+        },
+        ..CompileOptions::default()
+    };
+    let ast = compile_with_options(code, None, &options).unwrap();
+    assert_eq!(
+        references_in(&ast, 3, 12),
+        [
+            (2, 5, ReferenceKind::Declaration),
+            (3, 12, ReferenceKind::Read)
+        ]
+    );
+    assert_eq!(
+        references_at(code, 10, 13),
+        [
+            (9, 6, ReferenceKind::Declaration),
+            (10, 13, ReferenceKind::Read)
+        ]
+    );
+    // Assignments inside the loop update the outer variable
+    assert_eq!(references_at(code, 10, 5).len(), 3);
+
+    let (index, main_file) = main_symbols(code);
+    let counter = index.symbol_at(main_file, 9, 6).unwrap();
+    assert_eq!(index.symbols[counter].kind, SymbolKind::LoopCounter);
+    assert_eq!(
+        index.scopes[index.symbols[counter].scope].kind,
+        ScopeKind::Block
+    );
+}
+
+#[test]
+fn methods_and_parameters() {
+    let code = "\
+method double(N)
+    RESULT = N * 2
+    return RESULT
+end method
+output double(3)
+output double(4)";
+    assert_eq!(
+        references_at(code, 5, 8),
+        [
+            (1, 8, ReferenceKind::Declaration),
+            (5, 8, ReferenceKind::Call),
+            (6, 8, ReferenceKind::Call),
+        ]
+    );
+    assert_eq!(
+        references_at(code, 2, 14),
+        [
+            (1, 15, ReferenceKind::Declaration),
+            (2, 14, ReferenceKind::Read)
+        ]
+    );
+
+    let (index, main_file) = main_symbols(code);
+    let scope = index.scope_at(main_file, 3, 5);
+    assert_eq!(index.scopes[scope].kind, ScopeKind::Method);
+    let local = index.lookup(scope, "RESULT").unwrap();
+    assert_eq!(index.symbols[local].kind, SymbolKind::Variable);
+    assert!(index.lookup(0, "RESULT").is_none());
+    assert!(index.lookup(scope, "double").is_some());
+}
+
+#[test]
+fn main_methods_see_the_main_program() {
+    let code = "\
+COUNT = 0
+method increment()
+    COUNT = COUNT + 1
+end method
+increment()";
+    assert_eq!(
+        references_at(code, 1, 1),
+        [
+            (1, 1, ReferenceKind::Declaration),
+            (3, 5, ReferenceKind::Write),
+            (3, 13, ReferenceKind::Read),
+        ]
+    );
+}
+
+#[test]
+fn class_members() {
+    let code = "\
+Class Counter(START)
+    public this.count = START
+    this.step = function(N) {
+        this.count = this.count + N
+    }
+end Class
+C = new Counter(1)
+C.step(2)
+output C.count";
+    assert_eq!(
+        references_at(code, 8, 3),
+        [
+            (3, 10, ReferenceKind::Declaration),
+            (8, 3, ReferenceKind::Call)
+        ]
+    );
+    assert_eq!(
+        references_at(code, 2, 17),
+        [
+            (2, 17, ReferenceKind::Declaration),
+            (4, 14, ReferenceKind::Write),
+            (4, 27, ReferenceKind::Read),
+            (9, 10, ReferenceKind::Read),
+        ]
+    );
+    assert_eq!(
+        references_at(code, 7, 9),
+        [
+            (1, 7, ReferenceKind::Declaration),
+            (7, 9, ReferenceKind::Call)
+        ]
+    );
+    assert_eq!(
+        references_at(code, 2, 25),
+        [
+            (1, 15, ReferenceKind::Declaration),
+            (2, 25, ReferenceKind::Read)
+        ]
+    );
+
+    // Class functions only see their own variables and the class members
+    let (index, main_file) = main_symbols(code);
+    let scope = index.scope_at(main_file, 4, 9);
+    assert_eq!(index.scopes[scope].kind, ScopeKind::Method);
+    assert!(index.lookup(scope, "N").is_some());
+    assert!(index.lookup(scope, "count").is_some());
+    assert!(index.lookup(scope, "C").is_none());
+    assert!(index.lookup(scope, "Counter").is_some());
+}
+
+#[test]
+fn static_classes() {
+    let code = "\
+static Class Util()
+    this.twice = function(N) {
+        return N * 2
+    }
+end Class
+output Util.twice(2)";
+    assert_eq!(
+        references_at(code, 6, 13),
+        [
+            (2, 10, ReferenceKind::Declaration),
+            (6, 13, ReferenceKind::Call)
+        ]
+    );
+    assert_eq!(
+        references_at(code, 6, 8),
+        [
+            (1, 14, ReferenceKind::Declaration),
+            (6, 8, ReferenceKind::Read)
+        ]
+    );
+}
+
+#[test]
+fn unknown_receivers_are_unresolved() {
+    let code = "\
+Class A()
+    this.run = function() {
+        return 1
+    }
+end Class
+Class B()
+    this.run = function() {
+        return 2
+    }
+end Class
+method call(OBJECT)
+    return OBJECT.run()
+end method
+output call(new A())";
+    let (index, main_file) = main_symbols(code);
+    assert!(index.symbol_at(main_file, 12, 19).is_none());
+    let unresolved: Vec<_> = index
+        .unresolved
+        .iter()
+        .filter(|reference| reference.line_info.file_id == main_file)
+        .map(|reference| (reference.name.as_str(), reference.line_info.start_line))
+        .collect();
+    assert_eq!(unresolved, [("run", 12)]);
+}
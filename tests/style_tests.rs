@@ -0,0 +1,107 @@
+use ib_pcode_compiler::compiler::{CompileOptions, compile_with_options};
+use ib_pcode_compiler::data::diagnostic::{ErrorCode, Severity};
+use ib_pcode_compiler::data::style::StyleProfile;
+
+/// Code, line and suggestion of every style warning of `code`.
+fn style_warnings(code: &str, profile: StyleProfile) -> Vec<(ErrorCode, u32, Option<String>)> {
+    let options = CompileOptions {
+        style: Some(profile),
+        ..CompileOptions::default()
+    };
+    let ast = compile_with_options(code, None, &options).unwrap_or_else(|errors| {
+        errors.print();
+        panic!()
+    });
+
+    ast.warnings
+        .iter()
+        .map(|diagnostic| {
+            assert_eq!(diagnostic.severity, Severity::Warning);
+            (
+                diagnostic.code,
+                diagnostic.line_info.start_line,
+                diagnostic.help.clone(),
+            )
+        })
+        .collect()
+}
+
+fn rename(name: &str) -> Option<String> {
+    Some(format!("rename it to `{}`", name))
+}
+
+const CODE: &str = "\
+Class bank_account(startBalance)
+    public this.balance = startBalance
+    this.Deposit = function(AMOUNT) {
+        this.balance = this.balance + AMOUNT
+    }
+end Class
+method Find_max(FIRST, SECOND)
+    if FIRST > SECOND then
+        return FIRST
+    end if
+    return SECOND
+end method
+account = new bank_account(5)
+account.Deposit(3)
+X = Find_max(1, 2)
+output X, account.balance
+loop I from 1 to 2
+    output I
+end loop";
+
+#[test]
+fn naming_conventions() {
+    let help = "choose a name that describes the value, such as `TOTAL` or `STUDENT_COUNT`";
+    assert_eq!(
+        style_warnings(CODE, StyleProfile::ib()),
+        [
+            (ErrorCode::ClassNameStyle, 1, rename("BankAccount")),
+            (ErrorCode::VariableNameStyle, 1, rename("START_BALANCE")),
+            (ErrorCode::MethodNameStyle, 3, rename("deposit")),
+            (ErrorCode::MethodNameStyle, 7, rename("findMax")),
+            (ErrorCode::VariableNameStyle, 13, rename("ACCOUNT")),
+            (ErrorCode::MeaninglessName, 15, Some(help.to_string())),
+        ]
+    );
+}
+
+#[test]
+fn conventions_can_be_turned_off() {
+    let profile = StyleProfile {
+        uppercase_variables: false,
+        meaningful_names: false,
+        ..StyleProfile::ib()
+    };
+    assert_eq!(
+        style_warnings(CODE, profile),
+        [
+            (ErrorCode::ClassNameStyle, 1, rename("BankAccount")),
+            (ErrorCode::MethodNameStyle, 3, rename("deposit")),
+            (ErrorCode::MethodNameStyle, 7, rename("findMax")),
+        ]
+    );
+}
+
+#[test]
+fn style_is_off_by_default() {
+    let ast = compile_with_options(CODE, None, &CompileOptions::default()).unwrap();
+    assert!(ast.warnings.is_empty());
+}
+
+#[test]
+fn names_following_the_conventions() {
+    let code = "\
+static Class MathUtils()
+    this.squareOf = function(NUMBER) {
+        return NUMBER * NUMBER
+    }
+end Class
+method isEven(NUMBER)
+    return NUMBER mod 2 == 0
+end method
+TOTAL_SCORE = MathUtils.squareOf(3)
+output isEven(TOTAL_SCORE)";
+    assert_eq!(style_warnings(code, StyleProfile::ib()), []);
+}
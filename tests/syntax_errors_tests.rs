@@ -0,0 +1,170 @@
+use ib_pcode_compiler::compiler::compile;
+use ib_pcode_compiler::data::diagnostic::{Diagnostic, ErrorType};
+
+fn syntax_errors(code: &str) -> Vec<Diagnostic> {
+    let errors = compile(code).err().unwrap();
+    errors
+        .diagnostics
+        .into_iter()
+        .filter(|diagnostic| diagnostic.error_type == ErrorType::Syntax)
+        .collect()
+}
+
+fn syntax_error(code: &str) -> Diagnostic {
+    syntax_errors(code).remove(0)
+}
+
+#[test]
+fn missing_then() {
+    let code = r#"
+X = 2
+if X > 1
+    output X
+end if
+    "#;
+
+    let diagnostic = syntax_error(code);
+    assert_eq!(diagnostic.message, "missing `then` after the condition");
+    assert_eq!(diagnostic.line_info.start_line, 3);
+}
+
+#[test]
+fn unclosed_if_points_to_opening_line() {
+    let code = r#"
+X = 2
+if X > 1 then
+    output X
+else
+    output 0
+
+output X
+    "#;
+
+    let diagnostic = syntax_error(code);
+    assert_eq!(diagnostic.message, "this `if` block is never closed");
+    assert_eq!(diagnostic.line_info.start_line, 3);
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("add `end if` after the last line of the block")
+    );
+}
+
+#[test]
+fn unclosed_loop_inside_if() {
+    let code = r#"
+X = 1
+if X < 3 then
+    loop while X < 3
+        X = X + 1
+end if
+    "#;
+
+    let diagnostic = syntax_error(code);
+    assert_eq!(diagnostic.message, "this `loop` block is never closed");
+    assert_eq!(diagnostic.line_info.start_line, 4);
+    assert_eq!(diagnostic.labels[0].line_info.start_line, 6);
+}
+
+#[test]
+fn unmatched_end() {
+    let code = r#"
+X = 1
+end loop
+    "#;
+
+    let diagnostic = syntax_error(code);
+    assert_eq!(
+        diagnostic.message,
+        "`end loop` without a matching opening line"
+    );
+    assert_eq!(diagnostic.line_info.start_line, 3);
+}
+
+#[test]
+fn double_equals_assignment() {
+    let diagnostic = syntax_error("X == 5");
+    assert_eq!(
+        diagnostic.message,
+        "`==` compares two values, it cannot assign one"
+    );
+}
+
+#[test]
+fn expected_rules_are_described() {
+    let diagnostic = syntax_error("X = ");
+    assert_eq!(
+        diagnostic.message,
+        "expected an expression, found the end of the line"
+    );
+
+    let diagnostic = syntax_error("output (1 + 2");
+    assert_eq!(
+        diagnostic.message,
+        "expected an operator, found the end of the line"
+    );
+    assert_eq!(
+        diagnostic.help.as_deref(),
+        Some("a `(` on this line is never closed with `)`")
+    );
+}
+
+#[test]
+fn all_syntax_errors_are_reported() {
+    let code = r#"
+X = 
+output X
+Y == 2
+if X > 1
+    output (X
+end if
+loop while X < 3
+    X = X + 1
+output X
+    "#;
+
+    let lines: Vec<u32> = syntax_errors(code)
+        .iter()
+        .map(|diagnostic| diagnostic.line_info.start_line)
+        .collect();
+    assert_eq!(lines, vec![2, 4, 5, 6, 8]);
+}
+
+#[test]
+fn partial_ast_is_validated() {
+    let code = r#"
+X = 1
+output X +
+output UNKNOWN
+    "#;
+
+    let errors = compile(code).err().unwrap();
+    let types: Vec<ErrorType> = errors
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.error_type.clone())
+        .collect();
+    assert_eq!(types, vec![ErrorType::Syntax, ErrorType::Uninitialized]);
+    assert_eq!(errors.diagnostics[1].line_info.start_line, 4);
+}
+
+#[test]
+fn broken_method_is_skipped() {
+    let code = r#"
+method add(A, B
+    return A + B
+end method
+
+method double(A)
+    return A * 2
+end method
+
+output double(2)
+output 1 +
+    "#;
+
+    let lines: Vec<u32> = syntax_errors(code)
+        .iter()
+        .map(|diagnostic| diagnostic.line_info.start_line)
+        .collect();
+    assert_eq!(lines, vec![2, 11]);
+}
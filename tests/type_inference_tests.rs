@@ -0,0 +1,189 @@
+use ib_pcode_compiler::compiler::compile;
+use ib_pcode_compiler::data::diagnostic::ErrorCode;
+
+/// Code and line of every compile error of `code`, which must not compile.
+fn type_errors(code: &str) -> Vec<(ErrorCode, u32)> {
+    let errors = compile(code).err().expect("expected type errors");
+    errors
+        .diagnostics
+        .iter()
+        .map(|diagnostic| (diagnostic.code, diagnostic.line_info.start_line))
+        .collect()
+}
+
+#[test]
+fn definite_type_errors() {
+    let code = r#"
+NAME = "abc"
+output NAME - 1
+ARR = [1, 2]
+output ARR.substring(0, 1)
+N = 5
+output N.length
+output -"a"
+if ARR then
+    output N[0]
+end if
+output N.enqueue(1), N.head
+NAME -= 1
+    "#;
+
+    assert_eq!(
+        type_errors(code),
+        [
+            (ErrorCode::UnsupportedOperand, 3),
+            (ErrorCode::InvalidMethodTarget, 5),
+            (ErrorCode::InvalidMethodTarget, 7),
+            (ErrorCode::InvalidUnaryOperand, 8),
+            (ErrorCode::NotABoolean, 9),
+            (ErrorCode::InvalidMethodTarget, 10),
+            (ErrorCode::CallOnNonInstance, 12),
+            (ErrorCode::AccessOnNonInstance, 12),
+            (ErrorCode::UnsupportedOperand, 13),
+        ]
+    );
+}
+
+#[test]
+fn uncertain_types_are_left_to_runtime() {
+    let code = r#"
+method half(N)
+    return N / 2
+end method
+
+X = 1
+if half(4) > 1 then
+    X = "two"
+end if
+output X - 1
+
+ANSWER = input()
+output ANSWER - 1
+
+ARR = [1, "a"]
+output ARR[1] - 1, half("a")
+    "#;
+
+    assert!(compile(code).is_ok());
+}
+
+#[test]
+fn types_after_every_branch() {
+    let code = r#"
+Y = "a"
+if Y == "a" then
+    Y = "b"
+else
+    Y = "c"
+end if
+output Y * 2
+    "#;
+
+    assert_eq!(type_errors(code), [(ErrorCode::UnsupportedOperand, 8)]);
+}
+
+#[test]
+fn types_settle_in_loops() {
+    let code = r#"
+X = 0
+loop I from 1 to 3
+    output X - 1
+    X = "many"
+end loop
+
+Y = [1]
+loop while Y.length < 3
+    Y = "three"
+end loop
+output Y.length, Y / 3
+    "#;
+
+    assert_eq!(type_errors(code), [(ErrorCode::UnsupportedOperand, 12)]);
+
+    let code = r#"
+X = "a"
+loop I from 1 to 3
+    X = X + "a"
+end loop
+output X * 2
+    "#;
+
+    assert_eq!(type_errors(code), [(ErrorCode::UnsupportedOperand, 6)]);
+}
+
+#[test]
+fn methods_can_change_caller_variables() {
+    let code = r#"
+method reset()
+    TOTAL = 0
+end method
+
+TOTAL = "none"
+reset()
+output TOTAL - 1
+    "#;
+
+    assert!(compile(code).is_ok());
+}
+
+#[test]
+fn calls_on_instances_are_checked() {
+    let code = r#"
+Class Counter(START)
+    public this.count = START
+    this.step = 1
+
+    this.add = function(N) {
+        this.count = this.count + N * this.step
+    }
+
+    this.total = function() {
+        return this.count
+    }
+end Class
+
+C = new Counter(0)
+C.add(2)
+C.ad(2)
+C.add(1, 2)
+output C.add(3)
+output C.total(), C.count, C.step
+    "#;
+
+    assert_eq!(
+        type_errors(code),
+        [
+            (ErrorCode::UndefinedClassFunction, 17),
+            (ErrorCode::WrongArgumentCount, 18),
+            (ErrorCode::MissingReturn, 19),
+            (ErrorCode::UndefinedPublicVariable, 20),
+        ]
+    );
+
+    let errors = compile("Q = new Queue()\nQ.enqeue(1)").err().unwrap();
+    assert_eq!(
+        errors.diagnostics[0].help.as_deref(),
+        Some("did you mean `enqueue`?")
+    );
+}
+
+#[test]
+fn uncertain_instances_are_left_to_runtime() {
+    let code = r#"
+method swap()
+    S = new Stack()
+end method
+
+S = new Queue()
+QUEUES = [S]
+QUEUES[0].push(1)
+if S.isEmpty() then
+    S = new Stack()
+end if
+S.push(1)
+swap()
+S.enqueue(1)
+    "#;
+
+    assert!(compile(code).is_ok());
+}
@@ -5,6 +5,9 @@ let control = null;
 let respBuf = null; // Uint8Array view
 let reqId = 0;
 
+// Exit codes returned by `run_program_wasm`, the same as those of the command line
+const COMPILE_ERROR_EXIT_CODE = 1;
+
 self.onmessage = (ev) => {
     const msg = ev.data;
     
@@ -17,9 +20,17 @@ self.onmessage = (ev) => {
     } else if (msg.type === 'run') {
         try {
             console.log("[worker] Running wasm program...");
-            wasm.run_program_wasm(msg.source);
-            self.postMessage({ type: 'finish', text: "Program finished successfully" });
-            console.log("[worker] Program finished");
+            const exitCode = wasm.run_program_wasm(msg.source);
+            if (exitCode === 0) {
+                self.postMessage({ type: 'finish', text: "Program finished successfully" });
+                console.log("[worker] Program finished");
+            } else {
+                const text = exitCode === COMPILE_ERROR_EXIT_CODE
+                    ? "Program did not compile"
+                    : "Program stopped with an error";
+                self.postMessage({ type: 'failed', text });
+                console.log(`[worker] Program failed with exit code ${exitCode}`);
+            }
         } catch (e) {
             console.error("[worker] Error during run:", e);
             if (e && e.stack) console.error(e.stack);
@@ -70,3 +81,7 @@ globalThis.blocking_request_input = function (prompt) {
 globalThis.write_output = function (s) {
     self.postMessage({ type: 'output', text: s });
 };
+
+globalThis.write_diagnostic = function (html) {
+    self.postMessage({ type: 'diagnostic', html });
+};
@@ -181,6 +181,7 @@ runBtn.addEventListener('click', () => {
 });
 
 const GREEN = "<span style=\"color:green;\">"
+const RED = "<span style=\"color:red;\">"
 const RESET = "</span>"
 
 /* Worker messaging */
@@ -188,17 +189,20 @@ worker.onmessage = (ev) => {
     const msg = ev.data;
     if (msg.type === 'started') {
         worker.postMessage({ type: 'init', controlSab: sab });
-        appendOutput(GREEN + msg.text + RESET);
+        appendHtml(GREEN + escapeHtml(msg.text) + RESET);
     } else if (msg.type === 'request-input') {
         lastRequestId = msg.id;
         showModalPrompt(msg.prompt);
     } else if (msg.type === 'output') {
         appendOutput(msg.text);
-    } else if (msg.type === 'finish') {
+    } else if (msg.type === 'diagnostic') {
+        appendHtml(msg.html);
+    } else if (msg.type === 'finish' || msg.type === 'failed') {
         if (terminal.innerText.length !== 0) {
             appendOutput("\n");
         }
-        appendOutput(GREEN + msg.text + RESET);
+        const color = msg.type === 'finish' ? GREEN : RED;
+        appendHtml(color + escapeHtml(msg.text) + RESET);
     } else if (msg.type === 'error') {
         appendOutput('ERROR: ' + msg.message);
     } else {
@@ -244,9 +248,26 @@ function showModalPrompt(promptText) {
 
 function appendOutput(text) {
     let node = document.createElement('div');
-    node.innerHTML = text;
+    node.textContent = text;
+    appendNode(node);
+}
+
+// Only for markup built here or by the diagnostic renderer, which escapes the source it quotes.
+function appendHtml(html) {
+    let node = document.createElement('div');
+    node.innerHTML = html;
+    appendNode(node);
+}
+
+function appendNode(node) {
     terminal.appendChild(node);
     terminal.scrollTop = terminal.scrollHeight;
 }
 
+function escapeHtml(text) {
+    let node = document.createElement('div');
+    node.textContent = text;
+    return node.innerHTML;
+}
+
 loadReadme();
\ No newline at end of file